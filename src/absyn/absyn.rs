@@ -2,46 +2,61 @@
 
 use std::collections::LinkedList;
 
+use crate::diagnostics::Span;
+
 use super::{
     array_access::ArrayAccess, array_type_expression::ArrayTypeExpression,
     assign_statement::AssignStatement, binary_expression::BinaryExpression,
-    call_statement::CallStatement, if_statement::IfStatement,
-    procedure_definition::ProcedureDefinition, type_definition::TypeDefinition,
-    unary_expression::UnaryExpression, while_statement::WhileStatement,
+    call_statement::CallStatement, case_statement::CaseStatement,
+    const_definition::ConstDefinition, field_access::FieldAccess, for_statement::ForStatement,
+    if_statement::IfStatement, import_definition::ImportDefinition,
+    procedure_definition::ProcedureDefinition, record_type_expression::RecordTypeExpression,
+    repeat_statement::RepeatStatement, return_statement::ReturnStatement,
+    type_definition::TypeDefinition, unary_expression::UnaryExpression,
+    while_statement::WhileStatement,
 };
 
 /// Represents a complete program consisting of multiple definitions.
 #[derive(Debug, Clone)]
 pub struct Program {
-    /// List of definitions (procedures or types) contained in the program.
+    /// List of definitions (procedures, types, constants or imports) contained in the program.
+    /// Programs of several files contain the definitions of the imported files first.
     pub definitions: LinkedList<Box<Definition>>,
 }
 
-/// Represents either a procedure or type definition in the program.
+/// Represents a procedure, type, constant or import definition in the program.
 #[derive(Debug, Clone)]
 pub enum Definition {
     /// A procedure definition (function or method).
     ProcedureDefinition(Box<ProcedureDefinition>),
     /// A user-defined type definition.
     TypeDefinition(Box<TypeDefinition>),
+    /// A named constant.
+    ConstDefinition(Box<ConstDefinition>),
+    /// The import of the definitions of another file.
+    ImportDefinition(Box<ImportDefinition>),
 }
 
-/// Represents a variable, which can be a named variable or an array access.
+/// Represents a variable, which can be a named variable, an array access or a field access.
 #[derive(Debug, Clone)]
 pub enum Variable {
     /// A simple named variable identified by a string.
-    NamedVariable(String),
+    NamedVariable(String, Span),
     /// Access to an array element via an index expression.
     ArrayAccess(Box<ArrayAccess>),
+    /// Access to a field of a record by its name (e.g., `t.kind`).
+    FieldAccess(Box<FieldAccess>),
 }
 
-/// Represents the type of an expression, either a named type or an array type.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Represents the type of an expression, either a named type, an array type or a record type.
+#[derive(Debug, Clone)]
 pub enum TypeExpression {
     /// An array type expression (e.g., int[]).
     ArrayTypeExpression(Box<ArrayTypeExpression>),
+    /// A record type expression with named fields (e.g., `record x: int; end`).
+    RecordTypeExpression(Box<RecordTypeExpression>),
     /// A named type expression (e.g., int, float, custom types).
-    NamedTypeExpression(String),
+    NamedTypeExpression(String, Span),
 }
 
 /// Represents expressions in the language.
@@ -52,9 +67,15 @@ pub enum Expression {
     /// A unary expression with one operand and an operator.
     UnaryExpression(Box<UnaryExpression>),
     /// An integer literal value.
-    IntLiteral(i32),
+    IntLiteral(i32, Span),
+    /// A boolean literal, `true` or `false`.
+    BoolLiteral(bool, Span),
+    /// A string literal with its escape sequences replaced, only used as an argument.
+    StringLiteral(String, Span),
     /// A variable expression, referring to a variable.
     VariableExpression(Box<Variable>),
+    /// A call of a function, evaluating to its return value.
+    CallExpression(Box<CallStatement>),
 }
 
 /// Represents statements that can appear in the program.
@@ -66,10 +87,75 @@ pub enum Statement {
     IfStatement(Box<IfStatement>),
     /// While loop statement.
     WhileStatement(Box<WhileStatement>),
+    /// Counting loop statement.
+    ForStatement(Box<ForStatement>),
+    /// Loop statement with the condition after the body.
+    RepeatStatement(Box<RepeatStatement>),
+    /// Selects a branch by the value of an integer expression.
+    CaseStatement(Box<CaseStatement>),
+    /// Leaves the innermost loop.
+    BreakStatement(Span),
+    /// Goes on with the next iteration of the innermost loop.
+    ContinueStatement(Span),
     /// Procedure or function call statement.
     CallStatement(Box<CallStatement>),
+    /// Return from the procedure, with a value in functions.
+    ReturnStatement(Box<ReturnStatement>),
     /// An empty statement (no operation).
-    EmptyStatement,
+    EmptyStatement(Span),
     /// A compound statement containing a list of statements.
-    CompoundStatement(LinkedList<Box<Statement>>),
+    CompoundStatement(LinkedList<Box<Self>>, Span),
+}
+
+impl Variable {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::NamedVariable(_, span) => *span,
+            Self::ArrayAccess(array_access) => array_access.span,
+            Self::FieldAccess(field_access) => field_access.span,
+        }
+    }
+}
+
+impl TypeExpression {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::ArrayTypeExpression(array_type) => array_type.span,
+            Self::RecordTypeExpression(record_type) => record_type.span,
+            Self::NamedTypeExpression(_, span) => *span,
+        }
+    }
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::BinaryExpression(binary) => binary.span,
+            Self::UnaryExpression(unary) => unary.span,
+            Self::IntLiteral(_, span)
+            | Self::BoolLiteral(_, span)
+            | Self::StringLiteral(_, span) => *span,
+            Self::VariableExpression(var) => var.span(),
+            Self::CallExpression(call) => call.span,
+        }
+    }
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::AssignStatement(assign) => assign.span,
+            Self::IfStatement(if_statement) => if_statement.span,
+            Self::WhileStatement(while_statement) => while_statement.span,
+            Self::ForStatement(for_statement) => for_statement.span,
+            Self::RepeatStatement(repeat_statement) => repeat_statement.span,
+            Self::CaseStatement(case_statement) => case_statement.span,
+            Self::CallStatement(call) => call.span,
+            Self::ReturnStatement(ret) => ret.span,
+            Self::EmptyStatement(span)
+            | Self::BreakStatement(span)
+            | Self::ContinueStatement(span)
+            | Self::CompoundStatement(_, span) => *span,
+        }
+    }
 }
@@ -1,10 +1,12 @@
 use crate::table::types::ArrayType;
 
 use super::absyn::{Expression, Variable};
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct ArrayAccess {
     pub array: Variable,
     pub index: Expression,
     pub typ: Option<ArrayType>,
+    pub span: Span,
 }
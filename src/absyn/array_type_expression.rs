@@ -1,7 +1,10 @@
-use super::absyn::TypeExpression;
+use super::absyn::{Expression, TypeExpression};
+use crate::diagnostics::Span;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct ArrayTypeExpression {
-    pub array_size: usize,
+    /// A constant expression, evaluated during the semantic analysis.
+    pub array_size: Expression,
     pub base_type: TypeExpression,
+    pub span: Span,
 }
@@ -1,7 +1,9 @@
 use super::absyn::{Expression, Variable};
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct AssignStatement {
     pub target: Variable,
     pub value: Expression,
+    pub span: Span,
 }
@@ -1,10 +1,14 @@
+use std::fmt;
+
 use super::absyn::Expression;
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct BinaryExpression {
     pub operator: Operator,
     pub left: Expression,
     pub right: Expression,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,10 +17,49 @@ pub enum Operator {
     Sub,
     Mul,
     Div,
+    /// The remainder of `Div`, it has the sign of the left operand
+    Mod,
+    BitAnd,
+    BitOr,
+    BitXor,
+    /// Shifts by the lower five bits of the right operand
+    Shl,
+    /// Arithmetic shift, the sign bit is kept
+    Shr,
     Equ,
     Neq,
     Lst,
     Lse,
     Grt,
     Gre,
+    /// `&&`, the right operand is only evaluated if the left one is true
+    And,
+    /// `||`, the right operand is only evaluated if the left one is false
+    Or,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Mod => "%",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+            Self::Equ => "=",
+            Self::Neq => "#",
+            Self::Lst => "<",
+            Self::Lse => "<=",
+            Self::Grt => ">",
+            Self::Gre => ">=",
+            Self::And => "&&",
+            Self::Or => "||",
+        };
+        write!(f, "{op}")
+    }
 }
@@ -3,9 +3,11 @@
 use std::collections::LinkedList;
 
 use super::absyn::Expression;
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct CallStatement {
     pub name: String,
     pub arguments: LinkedList<Expression>,
+    pub span: Span,
 }
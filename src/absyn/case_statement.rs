@@ -0,0 +1,26 @@
+#![expect(clippy::linkedlist)]
+
+use std::collections::LinkedList;
+
+use super::absyn::{Expression, Statement};
+use crate::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub struct CaseStatement {
+    pub selector: Expression,
+    /// The branches in the order of the source code, at least one.
+    pub branches: LinkedList<CaseBranch>,
+    /// The `else` branch, taken if no label matches.
+    pub default: Option<Statement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct CaseBranch {
+    /// Constant expressions, at least one.
+    pub labels: LinkedList<Expression>,
+    /// The values of the labels, filled in by the semantic analysis.
+    pub values: Vec<i32>,
+    pub statement: Statement,
+    pub span: Span,
+}
@@ -0,0 +1,10 @@
+use super::absyn::Expression;
+use crate::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub struct ConstDefinition {
+    pub name: String,
+    /// A constant expression, evaluated during the semantic analysis.
+    pub value: Expression,
+    pub span: Span,
+}
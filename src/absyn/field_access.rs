@@ -0,0 +1,12 @@
+use crate::table::types::RecordType;
+
+use super::absyn::Variable;
+use crate::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub struct FieldAccess {
+    pub record: Variable,
+    pub field: String,
+    pub typ: Option<RecordType>,
+    pub span: Span,
+}
@@ -0,0 +1,15 @@
+use super::absyn::{Expression, Statement, Variable};
+use crate::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub struct ForStatement {
+    pub variable: Variable,
+    pub start: Expression,
+    /// The last value of the loop, evaluated once before the first iteration.
+    pub end: Expression,
+    /// The constant added after each iteration, `1` if none is given.
+    /// With a negative step the loop counts down to `end`.
+    pub step: i32,
+    pub body: Statement,
+    pub span: Span,
+}
@@ -1,8 +1,10 @@
 use super::absyn::{Expression, Statement};
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct IfStatement {
     pub condition: Expression,
     pub then_branch: Statement,
     pub else_branch: Option<Statement>,
+    pub span: Span,
 }
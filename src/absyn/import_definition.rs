@@ -0,0 +1,8 @@
+use crate::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub struct ImportDefinition {
+    /// The imported file, relative to the directory of the importing file.
+    pub path: String,
+    pub span: Span,
+}
@@ -5,9 +5,17 @@ pub mod array_type_expression;
 pub mod assign_statement;
 pub mod binary_expression;
 pub mod call_statement;
+pub mod case_statement;
+pub mod const_definition;
+pub mod field_access;
+pub mod for_statement;
 pub mod if_statement;
+pub mod import_definition;
 pub mod parameter_definition;
 pub mod procedure_definition;
+pub mod record_type_expression;
+pub mod repeat_statement;
+pub mod return_statement;
 pub mod type_definition;
 pub mod unary_expression;
 pub mod variable_definition;
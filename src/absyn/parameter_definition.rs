@@ -1,8 +1,10 @@
 use super::absyn::TypeExpression;
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct ParameterDefinition {
     pub name: String,
     pub type_expression: TypeExpression,
     pub is_reference: bool,
+    pub span: Span,
 }
@@ -3,14 +3,19 @@
 use std::collections::LinkedList;
 
 use super::{
-    absyn::Statement, parameter_definition::ParameterDefinition,
+    absyn::{Statement, TypeExpression},
+    parameter_definition::ParameterDefinition,
     variable_definition::VariableDefinition,
 };
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct ProcedureDefinition {
     pub name: String,
     pub parameters: LinkedList<ParameterDefinition>,
+    /// The result type of a function, `None` for a procedure without a value.
+    pub return_type: Option<TypeExpression>,
     pub body: LinkedList<Statement>,
     pub variables: LinkedList<VariableDefinition>,
+    pub span: Span,
 }
@@ -0,0 +1,20 @@
+#![expect(clippy::linkedlist)]
+
+use std::collections::LinkedList;
+
+use super::absyn::TypeExpression;
+use crate::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub struct RecordTypeExpression {
+    /// The fields in the order of their layout, at least one.
+    pub fields: LinkedList<FieldDefinition>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldDefinition {
+    pub name: String,
+    pub type_expression: TypeExpression,
+    pub span: Span,
+}
@@ -0,0 +1,10 @@
+use super::absyn::{Expression, Statement};
+use crate::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub struct RepeatStatement {
+    pub body: Statement,
+    /// The loop ends as soon as the condition holds after the body.
+    pub condition: Expression,
+    pub span: Span,
+}
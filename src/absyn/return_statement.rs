@@ -0,0 +1,9 @@
+use super::absyn::Expression;
+use crate::diagnostics::Span;
+
+#[derive(Debug, Clone)]
+pub struct ReturnStatement {
+    /// The returned value, functions return one and procedures don't.
+    pub value: Option<Expression>,
+    pub span: Span,
+}
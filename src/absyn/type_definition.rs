@@ -1,7 +1,9 @@
 use super::absyn::TypeExpression;
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct TypeDefinition {
     pub name: String,
     pub type_expression: TypeExpression,
+    pub span: Span,
 }
@@ -1,12 +1,26 @@
+use std::fmt;
+
 use super::absyn::Expression;
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct UnaryExpression {
     pub operator: UnaryOperator,
     pub operand: Expression,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UnaryOperator {
     Minus,
+    Not,
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Minus => write!(f, "-"),
+            Self::Not => write!(f, "!"),
+        }
+    }
 }
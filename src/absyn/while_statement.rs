@@ -1,7 +1,9 @@
 use super::absyn::{Expression, Statement};
+use crate::diagnostics::Span;
 
 #[derive(Debug, Clone)]
 pub struct WhileStatement {
     pub condition: Expression,
     pub body: Statement,
+    pub span: Span,
 }
@@ -53,6 +53,7 @@ impl Iterator for BlockStartIterator<'_> {
                         arg1: QuadrupelArg::Empty,
                         arg2: QuadrupelArg::Empty,
                         result: QuadrupelResult::Label(_),
+                        ..
                     }
                 ) && i > last
                 {
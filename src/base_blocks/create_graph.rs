@@ -48,6 +48,7 @@ pub fn phase_2(
 /// Phase 3: Analyze control flow to fix the edges in the `BlockGraph`.
 ///
 /// Replaces or adds control flow edges for `goto` and conditional jumps.
+/// A block ending with an indexed jump gets an edge to every target of its table.
 pub fn phase_3(mut block_graph: BlockGraph) -> BlockGraph {
     let blocks = block_graph.blocks.clone();
 
@@ -55,20 +56,23 @@ pub fn phase_3(mut block_graph: BlockGraph) -> BlockGraph {
         // If the block ends with an unconditional GOTO, replace its edge
         if let Some(label) = b.contains_goto() {
             block_graph.edges[i] = HashSet::new();
-            let label_block = block_graph
-                .label_to_id
-                .get(&label)
-                .unwrap_or_else(|| panic!("block corresponding to label {label} not found"));
-            block_graph.add_edge(i, *label_block);
+            let label_block = block_graph.label_to_id(&label);
+            block_graph.add_edge(i, label_block);
         }
 
         // If the block ends with a conditional jump, add an edge to the target label
         if let Some(label) = b.contains_if() {
-            let label_block = block_graph
-                .label_to_id
-                .get(&label)
-                .unwrap_or_else(|| panic!("block corresponding to label {label} not found"));
-            block_graph.add_edge(i, *label_block);
+            let label_block = block_graph.label_to_id(&label);
+            block_graph.add_edge(i, label_block);
+        }
+
+        // If the block ends with an indexed jump, replace its edge by one per target
+        if let Some(labels) = b.contains_jump_table() {
+            block_graph.edges[i] = HashSet::new();
+            for label in labels {
+                let label_block = block_graph.label_to_id(&label);
+                block_graph.add_edge(i, label_block);
+            }
         }
     }
 
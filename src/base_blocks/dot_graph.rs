@@ -120,6 +120,9 @@ fn to_dot_html(b: &str, re: &Regex) -> String {
             |color| match color.as_str() {
                 "35" => "magenta".to_string(),
                 "94" => "lightblue".to_string(),
+                // fallbacks for truecolor if the terminal does not support it
+                "37" => "lightgrey".to_string(),
+                "90" => "grey".to_string(),
                 c => panic!("unknown color {c}"),
             },
         );
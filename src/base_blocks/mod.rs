@@ -3,7 +3,7 @@ use std::collections::{HashMap, HashSet};
 use create_graph::{phase_1, phase_2, phase_3};
 
 use crate::{
-    code_gen::quadrupel::{Quadrupel, QuadrupelOp},
+    code_gen::quadrupel::{Quadrupel, QuadrupelOp, QuadrupelResult},
     optimizations::tarjan::Scc,
 };
 
@@ -65,6 +65,21 @@ impl Block {
             _ => None,
         }
     }
+
+    /// Checks if the block ends with an indexed jump, and returns all target labels if present.
+    fn contains_jump_table(&self) -> Option<Vec<String>> {
+        match &self.content {
+            BlockContent::Code(quads) => match quads.last() {
+                Some(Quadrupel {
+                    op: QuadrupelOp::JumpTable,
+                    result: QuadrupelResult::Table(labels),
+                    ..
+                }) => Some(labels.clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
 }
 
 /// Represents the content of a basic block.
@@ -123,14 +138,24 @@ impl BlockGraph {
         self.edges[start].remove(&end);
     }
 
+    /// Iterates over the quadruples of all code blocks.
+    pub fn quads(&self) -> impl Iterator<Item = &Quadrupel> {
+        self.blocks.iter().flat_map(|b| match &b.content {
+            BlockContent::Code(quads) => quads.as_slice(),
+            BlockContent::Start | BlockContent::Stop => &[],
+        })
+    }
+
     /// Creates an empty graph.
     fn new() -> Self {
         Self::default()
     }
 
     /// Gets the ID of a block by its label (panics if not found).
-    #[expect(dead_code)]
     pub fn label_to_id(&self, label: &str) -> BlockId {
-        *self.label_to_id.get(label).unwrap()
+        *self
+            .label_to_id
+            .get(label)
+            .unwrap_or_else(|| panic!("block corresponding to label {label} not found"))
     }
 }
@@ -9,8 +9,8 @@ impl QuadrupelOp {
         )
     }
 
-    /// Checks if the operation is a jump operation (e.g., GOTO, or any relational operator).
+    /// Checks if the operation is a jump operation (e.g., GOTO, JUMPTABLE, or any relational operator).
     pub const fn is_any_jump(self) -> bool {
-        matches!(self, Self::Goto) || self.is_relop()
+        matches!(self, Self::Goto | Self::JumpTable) || self.is_relop()
     }
 }
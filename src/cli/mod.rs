@@ -1,31 +1,34 @@
-use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::{self, Display, Write as _};
 use std::io::{IsTerminal, Write as _};
 use std::path::Path;
 use std::process::Stdio;
-use std::rc::Rc;
-use std::{fs::File, process};
+use std::{fs::File, panic, process};
 
 use anyhow::{anyhow, bail};
 use bitvec::vec::BitVec;
-use clap::{ArgGroup, Command, Id, arg};
+use clap::{ArgAction, ArgGroup, Command, Id, arg};
 use colored::Colorize;
-use dialoguer::{Select, theme::ColorfulTheme};
+use dialoguer::{
+    Select,
+    theme::{ColorfulTheme, Theme},
+};
 
 use crate::interpreter::definition_evaluator::start_main;
-use crate::interpreter::tac_interpreter::eval_tac;
+use crate::interpreter::expression_evaluator;
+use crate::interpreter::tac_interpreter::{self, eval_tac};
 use crate::{
     base_blocks::BlockGraph,
     code_gen::Tac,
-    optimizations::constant_propagation::ConstantPropagation,
-    optimizations::live_variables::LiveVariables,
-    optimizations::reaching_expressions::ReachingDefinitions,
-    optimizations::worklist::{Lattice, Worklist},
-    parser::parse_everything_else::parse,
-    semant::{build_symbol_table::build_symbol_table, check_def_global},
-    table::entry::Entry,
+    diagnostics::{Diagnostic, Source, SourceMap, Span},
+    formatter,
+    optimizations::call_graph::CallGraph,
+    optimizations::pass_manager::{self, PassManager, Pipeline},
+    optimizations::worklist::Lattice,
+    parser::{error::ParseError, lexer::tokenize, loader::load},
+    semant::{SemanticErrors, build_symbol_table::build_symbol_table, check_program},
     table::symbol_table::SymbolTable,
+    warnings::{self, LintLevels},
 };
 
 #[expect(clippy::cognitive_complexity)]
@@ -34,6 +37,7 @@ pub fn load_program_data() -> Command {
         .version("0.1.0")
         .args([
             arg!(file: <file> "Path to SPL code"),
+            arg!(tokens: --tokens "Prints the token stream of the input file"),
             arg!(parse: -p --parse "Parse input file, returns the abstract syntax tree"),
             arg!(tables: -t --tables "Fills symbol tables and prints them"),
             arg!(semant: -s --semant "Semantic analysis"),
@@ -41,47 +45,116 @@ pub fn load_program_data() -> Command {
             arg!(interpretoptimized: -j --interpret_optimized "TAC Interpreter with optimizations"),
             arg!(interprettac: -I --interprettac "TAC Interpreter"),
             arg!(tac: -'3' --tac "Generates three address code"),
+            arg!(callgraph: -c --callgraph "Builds the call graph and prints side-effect summaries"),
             arg!(proc: -P --proc <name> "Name of the procedure to be examined"),
-            arg!(optis: -O --optis <optis> "Optimizations to apply: [cse, rch, lv, dead, gcp, scc, licm]")
+            arg!(optis: -O --optis <optis>)
+                .help(format!(
+                    "Optimizations to apply: [{}], `name+` repeats until stable, `a:b+` repeats a group; -O0, -O1, -O2 select a predefined pipeline",
+                    pass_manager::available_passes()
+                ))
                 .num_args(1..)
                 .value_delimiter(','),
             arg!(dot: -d --dot ["output"] "Generates block graph").require_equals(true),
-            arg!(optimization: -o --optimize "All optimizations"),
+            arg!(optimization: -o --optimize "All optimizations (same as -O2)").conflicts_with("optis"),
+            arg!(warn: -W --warn <lint>)
+                .help(format!("Enables lints: [{}] or `all`", warnings::available_lints()))
+                .action(ArgAction::Append)
+                .value_delimiter(','),
+            arg!(allow: -A --allow <lint> "Disables lints, same names as -W")
+                .action(ArgAction::Append)
+                .value_delimiter(','),
+            arg!(deny_warnings: --"deny-warnings" "Treat all warnings as errors"),
         ])
         .group(
             ArgGroup::new("phase")
                 .required(false)
                 .multiple(false)
-                .args(["parse", "tables", "semant", "interpret", "interprettac", "interpretoptimized", "tac", "dot"]),
+                .args(["tokens", "parse", "tables", "semant", "interpret", "interprettac", "interpretoptimized", "tac", "callgraph", "dot"]),
         )
+        .subcommand(
+            Command::new("fmt")
+                .about("Formats SPL source files, prints the result unless --write or --check is given")
+                .args([
+                    arg!(files: <file> ... "Paths to SPL code"),
+                    arg!(check: --check "Fails if a file is not formatted, without changing it"),
+                    arg!(write: -w --write "Rewrites the files in place").conflicts_with("check"),
+                ]),
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
 }
 
+#[expect(clippy::too_many_lines)]
 pub fn process_matches(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    if let Some(("fmt", matches)) = matches.subcommand() {
+        return format_files(matches);
+    }
+
     let file = matches.get_one::<String>("file").unwrap();
     let input = std::fs::read_to_string(file)?.leak();
 
-    let mut absyn = parse(input)?;
+    if matches.get_flag("tokens") {
+        let (tokens, errors) = tokenize(input);
+        for token in &tokens {
+            eprintln!("{token}");
+        }
+        if !errors.is_empty() {
+            let source = Source {
+                name: file.clone(),
+                text: input,
+            };
+            return Err(parse_error(&SourceMap::from(source), &errors));
+        }
+        return Ok(());
+    }
+
+    let mut sources = SourceMap::default();
+    let mut absyn =
+        load(file, input, &mut sources).map_err(|errors| parse_error(&sources, &errors.0))?;
 
     let Some(phase) = matches.get_one::<Id>("phase") else {
         bail!("Code Generation for ECO32 not yet implemented")
     };
 
+    let pipeline = selected_pipeline(matches)?;
+    let lint_levels = LintLevels::from_matches(matches)?;
+
     if phase == "parse" {
         eprintln!("{absyn:#?}");
         return Ok(());
     }
 
-    let table = build_symbol_table(&absyn)?;
+    let semantic_errors = |errors: SemanticErrors| {
+        let exit_code = errors.exit_code();
+        let diagnostics = errors
+            .0
+            .into_iter()
+            .map(|err| {
+                Diagnostic::error(err.kind.to_string())
+                    .with_code(format!("E{:04}", err.kind.exit_code()))
+                    .with_span(err.span)
+                    .with_location(sources.main().name.clone())
+            })
+            .collect::<Vec<_>>();
+        compile_error(&sources, &diagnostics, exit_code)
+    };
 
     if phase == "tables" {
+        let table = build_symbol_table(&absyn).map_err(semantic_errors)?;
         eprintln!("{table:#?}");
         return Ok(());
     }
 
-    absyn
-        .definitions
-        .iter_mut()
-        .try_for_each(|def| check_def_global(def, &table))?;
+    let table = check_program(&mut absyn).map_err(semantic_errors)?;
+
+    // The lints work on the three address code, every later phase reports their warnings
+    let mut address_code = Tac::new(table.clone());
+    address_code.code_generation(&absyn);
+    let call_graph = CallGraph::new(&address_code.proc_table, &table.borrow());
+    call_graph.annotate(&mut table.borrow_mut());
+
+    let warnings = warnings::check_program(&absyn, &address_code, &table, &call_graph);
+    warnings::emit(&warnings, &lint_levels, &sources)?;
 
     if phase == "semant" {
         return Ok(());
@@ -89,18 +162,25 @@ pub fn process_matches(matches: &clap::ArgMatches) -> anyhow::Result<()> {
 
     if phase == "interpret" {
         let t = table.borrow();
+        report_runtime_errors(sources, expression_evaluator::current_span);
         start_main(&absyn, &t);
         return Ok(());
     }
 
-    let mut address_code = Tac::new(table.clone());
-    address_code.code_generation(&absyn);
-
     if phase == "tac" {
         eprintln!("{address_code}");
         return Ok(());
     }
 
+    if let Some(pipeline) = &pipeline {
+        pass_manager::run_program_passes(pipeline, &mut address_code, &table)?;
+    }
+
+    if phase == "callgraph" {
+        eprintln!("{}", show_call_graph(&call_graph, &table.borrow()));
+        return Ok(());
+    }
+
     let graphs = address_code.proc_table.keys().collect::<Vec<_>>();
     let theme = ColorfulTheme::default();
 
@@ -116,8 +196,8 @@ pub fn process_matches(matches: &clap::ArgMatches) -> anyhow::Result<()> {
             bail!("cannot interpret a single procedure");
         }
 
-        if let Some(optis) = matches.get_many::<String>("optis") {
-            graph.run_optimizations(optis, &table, proc_name, matches, &theme)?;
+        if let Some(pipeline) = &pipeline {
+            PassManager::new(&mut graph, &table, proc_name, matches, &theme).run(pipeline)?;
         }
 
         if phase == "dot" {
@@ -132,8 +212,8 @@ pub fn process_matches(matches: &clap::ArgMatches) -> anyhow::Result<()> {
 
             unoptimized_graphs.insert((*proc_name).clone(), graph.clone());
 
-            if let Some(optis) = matches.get_many::<String>("optis") {
-                graph.run_optimizations(optis, &table, proc_name, matches, &theme)?;
+            if let Some(pipeline) = &pipeline {
+                PassManager::new(&mut graph, &table, proc_name, matches, &theme).run(pipeline)?;
             }
             optimized_graphs.insert((*proc_name).clone(), graph.clone());
 
@@ -153,12 +233,14 @@ pub fn process_matches(matches: &clap::ArgMatches) -> anyhow::Result<()> {
         }
         if phase == "interprettac" {
             let t = table.borrow();
+            report_runtime_errors(sources, tac_interpreter::current_span);
             eval_tac(&unoptimized_graphs, &t);
             return Ok(());
         }
 
         if phase == "interpretoptimized" {
             let t = table.borrow();
+            report_runtime_errors(sources, tac_interpreter::current_span);
             eval_tac(&optimized_graphs, &t);
             return Ok(());
         }
@@ -167,120 +249,138 @@ pub fn process_matches(matches: &clap::ArgMatches) -> anyhow::Result<()> {
     unreachable!()
 }
 
-impl BlockGraph {
-    fn run_optimizations<'a>(
-        &mut self,
-        optis: impl Iterator<Item = &'a String>,
-        symbol_table: &Rc<RefCell<SymbolTable>>,
-        proc_name: &str,
-        matches: &clap::ArgMatches,
-        theme: &impl dialoguer::theme::Theme,
-    ) -> anyhow::Result<()> {
-        for opti in optis {
-            let proc_def = symbol_table.borrow().lookup(proc_name);
-            let Some(Entry::ProcedureEntry(proc_def)) = proc_def else {
-                unreachable!()
-            };
+/// Output of `--callgraph`, together with the procedures that whole-program passes
+/// like `dpe` removed.
+pub fn show_call_graph(call_graph: &CallGraph, symbol_table: &SymbolTable) -> String {
+    let eliminated = symbol_table.eliminated_procedures();
+    format!(
+        "{call_graph}\n>>> Eliminated:\n{}",
+        if eliminated.is_empty() {
+            "-".to_string()
+        } else {
+            eliminated.join(", ")
+        }
+    )
+}
 
-            match opti.as_str() {
-                "dot" => {
-                    println!("{}", ">>> Showing Dot Graph...".green());
-                    self.show_dot(proc_name, matches, theme)?;
-                }
-                "cse" => {
-                    eprintln!("{}", ">>> Common Subexpression Elimination".green());
-                    let local_table = proc_def.local_table.clone();
-                    self.common_subexpression_elimination(&local_table);
-                    match symbol_table.borrow_mut().entries.get_mut(proc_name) {
-                        Some(Entry::ProcedureEntry(pe)) => pe.local_table = local_table,
-                        _ => unreachable!(),
-                    }
-                }
-                "rch" => {
-                    eprintln!("{}", ">>> Reaching Definitions:".green());
-                    let rch = ReachingDefinitions::run(self, &proc_def.local_table);
-                    show_worklist_table(
-                        ("Definitions", &rch.defs),
-                        (1, 1),
-                        ("GEN", &rch.gen_bits),
-                        ("PRSV", &rch.prsv),
-                        ("RCHin", &rch.rchin),
-                        ("RCHout", &rch.rchout),
-                        fmt_bitvec,
-                    )?;
-                }
-                "lv" => {
-                    eprintln!("{}", ">>> Live Variables:".green());
-                    let lv = LiveVariables::run(self, &proc_def.local_table);
-                    show_worklist_table(
-                        ("Variables", &lv.vars),
-                        (1, 1),
-                        ("DEF", &lv.def),
-                        ("USE", &lv.use_bits),
-                        ("LIVin", &lv.livin),
-                        ("LIVout", &lv.livout),
-                        fmt_bitvec,
-                    )?;
-                }
-                "dead" => {
-                    eprintln!("{}", ">>> Dead Code Elimination".green());
-                    let lv = LiveVariables::run(self, &proc_def.local_table);
-                    self.dead_code_elimination(&lv);
-                }
-                "gcp" => {
-                    eprintln!("{}", ">>> Constant Propagation:".green());
-                    let gcp = ConstantPropagation::run(self, &proc_def.local_table);
-                    show_worklist_table(
-                        ("Variables", &gcp.vars),
-                        (5, 14),
-                        ("GEN", &gcp.gens),
-                        ("PRSV", &gcp.prsv),
-                        ("IN", &gcp.r#in),
-                        ("OUT", &gcp.out),
-                        |v| format!("{v:?}"),
-                    )?;
-                }
-                "cf" => {
-                    println!("{}", ">>> Constant Folding (×1)".green());
-                    let mut gcp = ConstantPropagation::run(self, &proc_def.local_table);
-                    _ = self.constant_folding(&mut gcp, &symbol_table.borrow());
-                }
-                "cf+" => {
-                    println!("{}", ">>> Constant Folding (until stable)".green());
-                    let mut gcp = ConstantPropagation::run(self, &proc_def.local_table);
-                    let mut iterations = 0;
-                    while { self.constant_folding(&mut gcp, &symbol_table.borrow()) }.is_continue()
-                    {
-                        iterations += 1;
-                    }
-                    println!("    iterations: {iterations}");
-                }
-                "scc" => {
-                    eprintln!("{}", ">>> Strongly Connected Components:".green());
-                    let scc = self.tarjan();
-                    eprintln!("{scc:#?}");
-                }
-                "licm" => {
-                    eprintln!("{}", ">>> Loop Invariant Code Motion:".green());
-                    self.loop_optimization(&proc_def.local_table);
-                }
-                "licm+" => {
-                    eprintln!("{}", ">>> Loop Invariant Code Motion:".green());
-                    while self.loop_optimization(&proc_def.local_table) {}
-                }
-                _ => panic!("Unknown optimization: {opti}"),
+/// The `fmt` subcommand: prints, checks or rewrites the formatted files.
+fn format_files(matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let check = matches.get_flag("check");
+    let mut unformatted = 0;
+
+    for file in matches.get_many::<String>("files").unwrap() {
+        let input = std::fs::read_to_string(file)?;
+        let source = Source {
+            name: file.clone(),
+            text: &input,
+        };
+        let formatted = formatter::format_source(&input)
+            .map_err(|errors| parse_error(&source.into(), &errors.0))?;
+
+        if check {
+            if formatted != input {
+                eprintln!("{}: {file}", "not formatted".yellow().bold());
+                unformatted += 1;
+            }
+        } else if matches.get_flag("write") {
+            if formatted != input {
+                std::fs::write(file, formatted)?;
             }
-            eprintln!();
+        } else {
+            print!("{formatted}");
         }
+    }
 
-        Ok(())
+    if unformatted > 0 {
+        bail!("{unformatted} file(s) would be reformatted");
     }
+    Ok(())
+}
 
-    fn show_dot(
+/// Prints the lexical and syntax errors, see `compile_error`.
+fn parse_error(sources: &SourceMap, errors: &[ParseError]) -> anyhow::Error {
+    let diagnostics = errors
+        .iter()
+        .map(|err| Diagnostic::error(err.to_string()).with_span(Some(err.span)))
+        .collect::<Vec<_>>();
+    compile_error(sources, &diagnostics, errors[0].exit_code())
+}
+
+/// Prints the diagnostics and returns the error that aborts the compilation.
+fn compile_error(sources: &SourceMap, diagnostics: &[Diagnostic], exit_code: i32) -> anyhow::Error {
+    for diagnostic in diagnostics {
+        eprintln!("{}\n", sources.render(diagnostic));
+    }
+    CompileError {
+        file: sources.main().name.clone(),
+        count: diagnostics.len(),
+        exit_code,
+    }
+    .into()
+}
+
+/// A program with errors, which are printed already.
+#[derive(Debug)]
+pub struct CompileError {
+    file: String,
+    count: usize,
+    /// The exit code of the reference compiler for the first error
+    pub exit_code: i32,
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.count {
+            1 => write!(f, "could not compile `{}` due to previous error", self.file),
+            n => write!(
+                f,
+                "could not compile `{}` due to {n} previous errors",
+                self.file
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// The exit code for an error of `process_matches`.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.downcast_ref::<CompileError>()
+        .map_or(1, |err| err.exit_code)
+}
+
+/// Shows panics of an interpreter together with the code that caused them,
+/// `current_span` is the code the interpreter evaluates.
+fn report_runtime_errors(sources: SourceMap<'static>, current_span: fn() -> Option<Span>) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        match (current_span(), info.payload_as_str()) {
+            (Some(span), Some(msg)) => {
+                let diagnostic = Diagnostic::error(format!("runtime error: {msg}"));
+                eprintln!("{}", sources.render(&diagnostic.with_span(Some(span))));
+            }
+            _ => default_hook(info),
+        }
+    }));
+}
+
+/// The optimization pipeline given by `-o` or `-O`, if any.
+fn selected_pipeline(matches: &clap::ArgMatches) -> anyhow::Result<Option<Pipeline>> {
+    if matches.get_flag("optimization") {
+        return Pipeline::level("2").map(Some);
+    }
+    matches
+        .get_many::<String>("optis")
+        .map(|optis| Pipeline::parse(optis.map(String::as_str)))
+        .transpose()
+}
+
+impl BlockGraph {
+    pub fn show_dot(
         &self,
         proc_name: &str,
         matches: &clap::ArgMatches,
-        theme: &impl dialoguer::theme::Theme,
+        theme: &dyn Theme,
     ) -> Result<(), anyhow::Error> {
         let mut filename = format!("{proc_name}.dot");
         let outputname = format!("as file: {filename}");
@@ -293,7 +393,7 @@ impl BlockGraph {
                     .extension()
                     .is_some_and(|ext| ext.eq_ignore_ascii_case("dot"))
                 {
-                    filename = arg.to_string();
+                    filename.clone_from(arg);
                     Some(1)
                 } else {
                     outputs.iter().position(|o| o == arg)
@@ -337,7 +437,7 @@ impl BlockGraph {
     }
 }
 
-fn show_worklist_table<L: Lattice, D: FmtTable>(
+pub fn show_worklist_table<L: Lattice, D: FmtTable>(
     (defs_name, defs): (&str, &[D]),
     (col_width_factor, col_width_factor_colored): (usize, usize),
     (al, av): (&str, &[L]),
@@ -370,7 +470,7 @@ fn show_worklist_table<L: Lattice, D: FmtTable>(
     Ok(())
 }
 
-fn fmt_bitvec(bv: &BitVec) -> String {
+pub fn fmt_bitvec(bv: &BitVec) -> String {
     bv.iter()
         .map(|b| b.then_some('1').unwrap_or('0'))
         .collect::<String>()
@@ -29,6 +29,16 @@ pub struct Tac {
     global_table: Rc<RefCell<SymbolTable>>,
     /// Name of the procedure currently being processed (if any).
     current_proc: Option<String>,
+    /// Label at the end of the current procedure, created by its first `return`.
+    return_label: Option<QuadrupelResult>,
+    /// Targets of `break` and `continue` in the enclosing loops, innermost last.
+    loop_labels: Vec<LoopLabels>,
+}
+
+/// The labels a `break` and a `continue` of a loop jump to.
+struct LoopLabels {
+    break_label: QuadrupelResult,
+    continue_label: QuadrupelResult,
 }
 
 impl fmt::Display for Tac {
@@ -57,6 +67,8 @@ impl Tac {
             temp_var_count: 0,
             global_table,
             current_proc: None,
+            return_label: None,
+            loop_labels: Vec::new(),
         }
     }
 
@@ -82,8 +94,10 @@ impl Tac {
                     self.quadrupels.clear();
                     self.temp_var_count = 0;
                 }
-                // Type definitions do not generate code, so ignore
-                Definition::TypeDefinition(_) => {}
+                // Type, constant and import definitions do not generate code, so ignore
+                Definition::TypeDefinition(_)
+                | Definition::ConstDefinition(_)
+                | Definition::ImportDefinition(_) => {}
             }
         }
     }
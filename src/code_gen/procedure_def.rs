@@ -5,18 +5,24 @@ use crate::{
         assign_statement::AssignStatement,
         binary_expression::Operator,
         call_statement::CallStatement,
+        case_statement::CaseStatement,
+        field_access::FieldAccess,
+        for_statement::ForStatement,
         if_statement::IfStatement,
         procedure_definition::ProcedureDefinition,
+        repeat_statement::RepeatStatement,
+        return_statement::ReturnStatement,
         unary_expression::UnaryOperator,
         while_statement::WhileStatement,
     },
+    diagnostics::Span,
     table::{
         entry::{Entry, VariableEntry},
         types::Type,
     },
 };
 
-use super::{Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar, Tac};
+use super::{LoopLabels, Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar, Tac};
 
 impl<'a> Tac {
     pub(super) fn eval_proc_def(&mut self, proc_def: &'a ProcedureDefinition) {
@@ -27,6 +33,9 @@ impl<'a> Tac {
         for statement in &proc_def.body {
             self.eval_statement(statement);
         }
+        if let Some(label) = self.return_label.take() {
+            self.emit_label(label);
+        }
     }
 
     fn eval_statement(&mut self, statement: &'a Statement) {
@@ -40,25 +49,53 @@ impl<'a> Tac {
             Statement::WhileStatement(while_state) => {
                 self.eval_while_statement(while_state.as_ref());
             }
+            Statement::ForStatement(for_state) => {
+                self.eval_for_statement(for_state.as_ref());
+            }
+            Statement::RepeatStatement(repeat_state) => {
+                self.eval_repeat_statement(repeat_state.as_ref());
+            }
+            Statement::CaseStatement(case_state) => {
+                self.eval_case_statement(case_state.as_ref());
+            }
+            Statement::BreakStatement(_) => {
+                let label = self.loop_labels.last().unwrap().break_label.clone();
+                self.emit_goto(label);
+            }
+            Statement::ContinueStatement(_) => {
+                let label = self.loop_labels.last().unwrap().continue_label.clone();
+                self.emit_goto(label);
+            }
+            Statement::CallStatement(call_state) if call_state.name == "prints" => {
+                self.eval_prints(call_state.as_ref());
+            }
             Statement::CallStatement(call_state) => {
-                self.eval_call_statement(call_state.as_ref());
+                // The value of a function is not stored
+                let quad = self.eval_call(call_state.as_ref());
+                self.quadrupels.push(quad);
             }
-            Statement::CompoundStatement(inner) => {
+            Statement::ReturnStatement(return_state) => {
+                self.eval_return_statement(return_state.as_ref());
+            }
+            Statement::CompoundStatement(inner, _) => {
                 for statement in inner {
                     self.eval_statement(statement.as_ref());
                 }
             }
-            Statement::EmptyStatement => {}
+            Statement::EmptyStatement(_) => {}
         }
     }
 
     fn eval_assign_statement(&mut self, assign: &'a AssignStatement) {
-        #![expect(clippy::similar_names)]
-        let mut assign_quad;
         let val = self.eval_expression(&assign.value);
+        self.emit_assign(&assign.target, val, assign.span);
+    }
+
+    fn emit_assign(&mut self, target: &Variable, val: Expr, span: Span) {
+        let mut assign_quad;
 
-        match &assign.target {
-            Variable::NamedVariable(name) => {
+        match target {
+            Variable::NamedVariable(name, _) => {
                 match val {
                     Expr::Quad(quad) => {
                         assign_quad = quad;
@@ -67,14 +104,16 @@ impl<'a> Tac {
                         assign_quad = Quadrupel::new();
                         assign_quad.op = QuadrupelOp::Assign;
                         assign_quad.arg1 = arg;
+                        assign_quad.span = Some(span);
                     }
                 }
-                assign_quad.result = QuadrupelResult::Var(QuadrupelVar::Spl(name.to_string()));
+                assign_quad.result = QuadrupelResult::Var(QuadrupelVar::Spl(name.clone()));
             }
-            Variable::ArrayAccess(array_access) => {
-                let (var, offset) = self.eval_array_access(array_access);
+            Variable::ArrayAccess(_) | Variable::FieldAccess(_) => {
+                let (var, offset) = self.eval_access(target);
 
                 assign_quad = Quadrupel::new();
+                assign_quad.span = Some(target.span());
                 assign_quad.op = QuadrupelOp::ArrayStore;
                 assign_quad.result = QuadrupelResult::Var(var);
                 assign_quad.arg2 = self.into_tmp(offset, &Type::INT, false);
@@ -84,55 +123,74 @@ impl<'a> Tac {
         self.quadrupels.push(assign_quad);
     }
 
+    // Returns the variable and the offset in bytes of an array element or record field
+    fn eval_access(&mut self, var: &Variable) -> (QuadrupelVar, Expr) {
+        match var {
+            Variable::NamedVariable(..) => unreachable!("named variables have no offset"),
+            Variable::ArrayAccess(array_access) => self.eval_array_access(array_access),
+            Variable::FieldAccess(field_access) => self.eval_field_access(field_access),
+        }
+    }
+
+    // Adds the offset of the array or record containing an access to its offset
+    fn eval_container(
+        &mut self,
+        container: &Variable,
+        offset: Expr,
+        span: Span,
+    ) -> (QuadrupelVar, Expr) {
+        match container {
+            Variable::NamedVariable(name, _) => (QuadrupelVar::Spl(name.clone()), offset),
+            Variable::ArrayAccess(_) | Variable::FieldAccess(_) => {
+                let (var, inner_offset) = self.eval_access(container);
+                let offset = self.emit_expression_bin(Operator::Add, offset, inner_offset, span);
+                (var, offset)
+            }
+        }
+    }
+
+    fn eval_field_access(&mut self, field_access: &FieldAccess) -> (QuadrupelVar, Expr) {
+        let record_type = field_access.typ.as_ref().unwrap();
+        let offset = record_type.get_field_offset(&field_access.field);
+        self.eval_container(
+            &field_access.record,
+            Expr::Arg(QuadrupelArg::Const(offset)),
+            field_access.span,
+        )
+    }
+
     fn eval_array_access(&mut self, array_access: &ArrayAccess) -> (QuadrupelVar, Expr) {
         let index = self.eval_expression(&array_access.index);
-        let base_size = array_access.typ.as_ref().unwrap().base_type.get_byte_size();
-        let var;
-        let mut offset = self.emit_expression_bin(
+        let array_type = array_access.typ.as_ref().unwrap();
+        let base_size = array_type.base_type.get_byte_size();
+
+        // Every index is checked against the size of its dimension
+        let index = self.into_tmp(index, &Type::INT, false);
+        let mut check_quad = Quadrupel::new();
+        check_quad.op = QuadrupelOp::BoundsCheck;
+        check_quad.arg1 = index.clone();
+        check_quad.arg2 = QuadrupelArg::Const(i32::try_from(array_type.size).unwrap());
+        check_quad.span = Some(array_access.span);
+        self.quadrupels.push(check_quad);
+
+        let offset = self.emit_expression_bin(
             Operator::Mul,
-            index,
+            Expr::Arg(index),
             Expr::Arg(QuadrupelArg::Const(base_size)),
+            array_access.span,
         );
 
-        match &array_access.array {
-            Variable::NamedVariable(name) => {
-                var = QuadrupelVar::Spl(name.to_string());
-            }
-            Variable::ArrayAccess(inner) => {
-                let (inner_var, inner_offset) = self.eval_array_access(inner);
-                var = inner_var;
-                offset = self.emit_expression_bin(Operator::Add, offset, inner_offset);
-            }
-        }
-
-        (var, offset)
+        self.eval_container(&array_access.array, offset, array_access.span)
     }
 
     fn eval_if_statement(&mut self, if_state: &'a IfStatement) {
         let else_label = self.create_label(None);
         let end_label = self.create_label(None);
-        let mut if_quad = Quadrupel::new();
-        let ex = &if_state.condition;
-        match ex {
-            Expression::BinaryExpression(binex) => {
-                let left = self.eval_expression(&binex.left);
-                let right = self.eval_expression(&binex.right);
-
-                if_quad.op = QuadrupelOp::from(binex.operator).inv();
-                if_quad.arg1 = self.into_tmp(left, &Type::INT, false);
-                if_quad.arg2 = self.into_tmp(right, &Type::INT, false);
-                if_quad.result = else_label.clone();
-                self.quadrupels.push(if_quad);
-            }
-            _ => panic!("mistake in 'if' expression!"),
-        }
+        self.eval_condition(&if_state.condition, &else_label, false);
         // begin then
         self.eval_statement(&if_state.then_branch);
         if if_state.else_branch.is_some() {
-            let mut quad = Quadrupel::new();
-            quad.op = QuadrupelOp::Goto;
-            quad.result = end_label.clone();
-            self.quadrupels.push(quad);
+            self.emit_goto(end_label.clone());
         }
         // end then
 
@@ -149,101 +207,445 @@ impl<'a> Tac {
         let jmp_label = self.create_label(None);
         let while_label = self.create_label(None);
         self.emit_label(while_label.clone());
-        let mut while_quad = Quadrupel::new();
-        let ex = &while_state.condition;
-        match ex {
+        self.eval_condition(&while_state.condition, &jmp_label, false);
+        self.eval_loop_body(&while_state.body, &jmp_label, &while_label);
+        self.emit_goto(while_label);
+        self.emit_label(jmp_label);
+    }
+
+    // The end is copied into a temporary before the loop, the variable is
+    // compared with it at the head and advanced at the `continue` label
+    fn eval_for_statement(&mut self, for_state: &'a ForStatement) {
+        let end_label = self.create_label(None);
+        let head_label = self.create_label(None);
+        let step_label = self.create_label(None);
+
+        let start = self.eval_expression(&for_state.start);
+        self.emit_assign(&for_state.variable, start, for_state.span);
+        let end = match self.eval_expression(&for_state.end) {
+            // The body may change the variable
+            Expr::Arg(arg @ QuadrupelArg::Var(_)) => {
+                let tmp = self.create_tmp_var(&Type::INT, false);
+                let mut quad = Quadrupel::new();
+                quad.op = QuadrupelOp::Assign;
+                quad.arg1 = arg;
+                quad.result = QuadrupelResult::Var(tmp.clone());
+                self.quadrupels.push(quad);
+                QuadrupelArg::Var(tmp)
+            }
+            end => self.into_tmp(end, &Type::INT, false),
+        };
+
+        self.emit_label(head_label.clone());
+        let value = self.eval_expression_var(&for_state.variable);
+        let mut quad = Quadrupel::new();
+        quad.op = if for_state.step > 0 {
+            QuadrupelOp::Grt
+        } else {
+            QuadrupelOp::Lst
+        };
+        quad.arg1 = self.into_tmp(value, &Type::INT, false);
+        quad.arg2 = end;
+        quad.result = end_label.clone();
+        self.quadrupels.push(quad);
+
+        self.eval_loop_body(&for_state.body, &end_label, &step_label);
+
+        self.emit_label(step_label);
+        let value = self.eval_expression_var(&for_state.variable);
+        let next = self.emit_expression_bin(
+            Operator::Add,
+            value,
+            Expr::Arg(QuadrupelArg::Const(for_state.step)),
+            for_state.span,
+        );
+        self.emit_assign(&for_state.variable, next, for_state.span);
+        self.emit_goto(head_label);
+        self.emit_label(end_label);
+    }
+
+    // The body is repeated as long as the condition after it is false
+    fn eval_repeat_statement(&mut self, repeat_state: &'a RepeatStatement) {
+        let end_label = self.create_label(None);
+        let body_label = self.create_label(None);
+        let cond_label = self.create_label(None);
+        self.emit_label(body_label.clone());
+        self.eval_loop_body(&repeat_state.body, &end_label, &cond_label);
+        self.emit_label(cond_label);
+        self.eval_condition(&repeat_state.condition, &body_label, false);
+        self.emit_label(end_label);
+    }
+
+    // Emits the body of a loop, `break` and `continue` jump to the given labels
+    fn eval_loop_body(
+        &mut self,
+        body: &'a Statement,
+        break_label: &QuadrupelResult,
+        continue_label: &QuadrupelResult,
+    ) {
+        self.loop_labels.push(LoopLabels {
+            break_label: break_label.clone(),
+            continue_label: continue_label.clone(),
+        });
+        self.eval_statement(body);
+        self.loop_labels.pop();
+    }
+
+    // Sparse labels are compared with the selector one after the other, dense ones
+    // select their branch by an indexed jump. Each branch jumps to the end.
+    fn eval_case_statement(&mut self, case_state: &'a CaseStatement) {
+        let default_label = self.create_label(None);
+        let end_label = self.create_label(None);
+        let selector = self.eval_expression(&case_state.selector);
+        let selector = self.into_tmp(selector, &Type::INT, false);
+
+        let branch_labels = case_state
+            .branches
+            .iter()
+            .map(|_| self.create_label(None))
+            .collect::<Vec<_>>();
+        // The values of the labels with their branch, each compare is reported at its label
+        let mut targets = case_state
+            .branches
+            .iter()
+            .zip(&branch_labels)
+            .flat_map(|(branch, label)| {
+                let spans = branch.labels.iter().map(Expression::span);
+                branch
+                    .values
+                    .iter()
+                    .zip(spans)
+                    .map(move |(v, span)| (*v, label, span))
+            })
+            .collect::<Vec<_>>();
+        targets.sort_by_key(|(value, ..)| *value);
+
+        if is_dense(&targets) {
+            self.emit_jump_table(
+                selector,
+                &targets,
+                &default_label,
+                case_state.selector.span(),
+            );
+        } else {
+            for (value, label, span) in targets {
+                self.emit_jump(
+                    QuadrupelOp::Equ,
+                    selector.clone(),
+                    QuadrupelArg::Const(value),
+                    label.clone(),
+                    Some(span),
+                );
+            }
+            self.emit_goto(default_label.clone());
+        }
+
+        for (branch, label) in case_state.branches.iter().zip(branch_labels) {
+            self.emit_label(label);
+            self.eval_statement(&branch.statement);
+            self.emit_goto(end_label.clone());
+        }
+        self.emit_label(default_label);
+        if let Some(state) = &case_state.default {
+            self.eval_statement(state);
+        }
+        self.emit_label(end_label);
+    }
+
+    // Jumps to the default if the selector is outside of the sorted labels,
+    // the table holds a target for every value between them
+    fn emit_jump_table(
+        &mut self,
+        selector: QuadrupelArg,
+        targets: &[(i32, &QuadrupelResult, Span)],
+        default_label: &QuadrupelResult,
+        span: Span,
+    ) {
+        let low = targets.first().unwrap().0;
+        let high = targets.last().unwrap().0;
+        self.emit_jump(
+            QuadrupelOp::Lst,
+            selector.clone(),
+            QuadrupelArg::Const(low),
+            default_label.clone(),
+            None,
+        );
+        self.emit_jump(
+            QuadrupelOp::Grt,
+            selector.clone(),
+            QuadrupelArg::Const(high),
+            default_label.clone(),
+            None,
+        );
+        let index = if low == 0 {
+            selector
+        } else {
+            let index = self.emit_expression_bin(
+                Operator::Sub,
+                Expr::Arg(selector),
+                Expr::Arg(QuadrupelArg::Const(low)),
+                span,
+            );
+            self.into_tmp(index, &Type::INT, false)
+        };
+
+        let offset = |value: i32| usize::try_from(i64::from(value) - i64::from(low)).unwrap();
+        let mut table = vec![default_label.to_string(); offset(high) + 1];
+        for (value, label, _) in targets {
+            table[offset(*value)] = label.to_string();
+        }
+
+        let mut quad = Quadrupel::new();
+        quad.op = QuadrupelOp::JumpTable;
+        quad.arg1 = index;
+        quad.result = QuadrupelResult::Table(table);
+        quad.span = Some(span);
+        self.quadrupels.push(quad);
+    }
+
+    // Emits short-circuit jumping code: jumps to `label` if the condition
+    // evaluates to `jump_if`, falls through otherwise
+    fn eval_condition(&mut self, cond: &Expression, label: &QuadrupelResult, jump_if: bool) {
+        match cond {
+            Expression::UnaryExpression(unary) if unary.operator == UnaryOperator::Not => {
+                self.eval_condition(&unary.operand, label, !jump_if);
+            }
+            Expression::BinaryExpression(binex)
+                if matches!(binex.operator, Operator::And | Operator::Or) =>
+            {
+                // `a && b` is false as soon as `a` is, `a || b` is true as soon as `a` is
+                let decided_by_left = binex.operator == Operator::Or;
+                if decided_by_left == jump_if {
+                    self.eval_condition(&binex.left, label, jump_if);
+                    self.eval_condition(&binex.right, label, jump_if);
+                } else {
+                    let skip_label = self.create_label(None);
+                    self.eval_condition(&binex.left, &skip_label, !jump_if);
+                    self.eval_condition(&binex.right, label, jump_if);
+                    self.emit_label(skip_label);
+                }
+            }
             Expression::BinaryExpression(binex) => {
                 let left = self.eval_expression(&binex.left);
                 let right = self.eval_expression(&binex.right);
 
-                while_quad.op = QuadrupelOp::from(binex.operator).inv();
-                while_quad.arg1 = self.into_tmp(left, &Type::INT, false);
-                while_quad.arg2 = self.into_tmp(right, &Type::INT, false);
-                while_quad.result = jmp_label.clone();
-                self.quadrupels.push(while_quad);
+                let op = QuadrupelOp::from(binex.operator);
+                let mut quad = Quadrupel::new();
+                quad.op = if jump_if { op } else { op.inv() };
+                quad.arg1 = self.into_tmp(left, &Type::INT, false);
+                quad.arg2 = self.into_tmp(right, &Type::INT, false);
+                quad.result = label.clone();
+                quad.span = Some(binex.span);
+                self.quadrupels.push(quad);
+            }
+            // Boolean variables and literals hold 0 or 1
+            _ => {
+                let value = self.eval_expression(cond);
+                let mut quad = Quadrupel::new();
+                quad.op = if jump_if {
+                    QuadrupelOp::Neq
+                } else {
+                    QuadrupelOp::Equ
+                };
+                quad.arg1 = self.into_tmp(value, &Type::BOOL, false);
+                quad.arg2 = QuadrupelArg::Const(0);
+                quad.result = label.clone();
+                quad.span = Some(cond.span());
+                self.quadrupels.push(quad);
             }
-            _ => panic!("mistake in 'while' expression!"),
         }
-        self.eval_statement(&while_state.body);
-        self.quadrupels.push(Quadrupel {
-            op: QuadrupelOp::Goto,
-            arg1: QuadrupelArg::Empty,
-            arg2: QuadrupelArg::Empty,
-            result: while_label,
-        });
-        self.emit_label(jmp_label);
     }
 
-    fn eval_call_statement(&mut self, call_state: &'a CallStatement) {
-        let mut count = 0;
+    // Stores the value of a condition as 0 or 1 in a new temporary
+    fn materialize_condition(&mut self, cond: &Expression) -> Expr {
+        let tmp = self.create_tmp_var(&Type::BOOL, false);
+        let end_label = self.create_label(None);
+
+        let mut quad = Quadrupel::new();
+        quad.op = QuadrupelOp::Assign;
+        quad.arg1 = QuadrupelArg::Const(1);
+        quad.result = QuadrupelResult::Var(tmp.clone());
+        self.quadrupels.push(quad.clone());
+
+        self.eval_condition(cond, &end_label, true);
+        quad.arg1 = QuadrupelArg::Const(0);
+        self.quadrupels.push(quad);
+        self.emit_label(end_label);
+
+        Expr::Arg(QuadrupelArg::Var(tmp))
+    }
+
+    // Emits the parameters of a call and returns the call itself,
+    // its result is set if the value of a function is used
+    fn eval_call(&mut self, call_state: &CallStatement) -> Quadrupel {
         let name = call_state.name.clone();
         let Some(Entry::ProcedureEntry(proc_entry)) = self.global_table.borrow().lookup(&name)
         else {
             unreachable!()
         };
-        for (param, param_entry) in call_state.arguments.iter().zip(proc_entry.parameters) {
-            count += 1;
-            let param = self.eval_expression(param);
+
+        // All arguments are evaluated before the first parameter is passed,
+        // so the parameters of calls in the arguments are not mixed up with them
+        let args = call_state
+            .arguments
+            .iter()
+            .zip(proc_entry.parameters)
+            .map(|(arg, param_entry)| {
+                let value = self.eval_expression(arg);
+                let value = self.into_tmp(value, &param_entry.typ, param_entry.is_reference);
+                (value, arg.span())
+            })
+            .collect::<Vec<_>>();
+        let count = i32::try_from(args.len()).unwrap();
+        for (value, span) in args {
             let mut quad = Quadrupel::new();
             quad.op = QuadrupelOp::Param;
-            quad.arg1 = self.into_tmp(param, &param_entry.typ, param_entry.is_reference);
+            quad.arg1 = value;
+            quad.span = Some(span);
             self.quadrupels.push(quad);
         }
+
         let mut quad = Quadrupel::new();
         quad.op = QuadrupelOp::Call;
         quad.arg1 = QuadrupelArg::Var(QuadrupelVar::Spl(name));
         quad.arg2 = QuadrupelArg::Const(count);
-        self.quadrupels.push(quad);
+        quad.span = Some(call_state.span);
+        quad
+    }
+
+    // TAC has no strings, `prints` calls `printc` for every character instead
+    fn eval_prints(&mut self, call_state: &CallStatement) {
+        let Some(Expression::StringLiteral(text, span)) = call_state.arguments.front() else {
+            unreachable!("the argument of `prints` is a string literal")
+        };
+        for c in text.chars() {
+            let mut quad = Quadrupel::new();
+            quad.op = QuadrupelOp::Param;
+            quad.arg1 = QuadrupelArg::Const(u32::from(c).cast_signed());
+            quad.span = Some(*span);
+            self.quadrupels.push(quad);
+
+            let mut quad = Quadrupel::new();
+            quad.op = QuadrupelOp::Call;
+            quad.arg1 = QuadrupelArg::Var(QuadrupelVar::Spl("printc".to_string()));
+            quad.arg2 = QuadrupelArg::Const(1);
+            quad.span = Some(call_state.span);
+            self.quadrupels.push(quad);
+        }
+    }
+
+    // The value is passed to the caller by the `RETURN` quad,
+    // followed by a jump to the end of the procedure
+    fn eval_return_statement(&mut self, return_state: &ReturnStatement) {
+        if let Some(value) = &return_state.value {
+            let typ = self.return_type(self.current_proc.as_ref().unwrap());
+            let value = self.eval_expression(value);
+            let mut quad = Quadrupel::new();
+            quad.op = QuadrupelOp::Return;
+            quad.arg1 = self.into_tmp(value, &typ, false);
+            quad.span = Some(return_state.span);
+            self.quadrupels.push(quad);
+        }
+
+        if self.return_label.is_none() {
+            self.return_label = Some(self.create_label(None));
+        }
+        let label = self.return_label.clone().unwrap();
+        self.emit_goto(label);
+    }
+
+    fn return_type(&self, proc_name: &str) -> Type {
+        let Some(Entry::ProcedureEntry(proc_entry)) = self.global_table.borrow().lookup(proc_name)
+        else {
+            unreachable!()
+        };
+        proc_entry.return_type.unwrap()
     }
 
     fn eval_expression(&mut self, exp: &Expression) -> Expr {
         match exp {
+            Expression::BinaryExpression(binex) if binex.operator.is_boolean() => {
+                self.materialize_condition(exp)
+            }
+            Expression::UnaryExpression(unary) if unary.operator == UnaryOperator::Not => {
+                self.materialize_condition(exp)
+            }
             Expression::BinaryExpression(exp) => {
                 let left = self.eval_expression(&exp.left);
                 let right = self.eval_expression(&exp.right);
-                self.emit_expression_bin(exp.operator, left, right)
+                self.emit_expression_bin(exp.operator, left, right, exp.span)
             }
             Expression::UnaryExpression(exp) => {
                 let left = self.eval_expression(&exp.operand);
-                self.emit_expression_un(exp.operator, left)
+                self.emit_expression_un(exp.operator, left, exp.span)
             }
-            Expression::IntLiteral(val) => Expr::Arg(QuadrupelArg::Const(*val)),
+            Expression::IntLiteral(val, _) => Expr::Arg(QuadrupelArg::Const(*val)),
+            Expression::BoolLiteral(val, _) => Expr::Arg(QuadrupelArg::Const(i32::from(*val))),
+            Expression::StringLiteral(..) => unreachable!("strings are only passed to `prints`"),
             Expression::VariableExpression(var) => self.eval_expression_var(var),
+            Expression::CallExpression(call) => {
+                // The call is emitted right away, the parameters of other calls
+                // in the expression must not be passed to it
+                let quad = self.eval_call(call);
+                let typ = self.return_type(&call.name);
+                Expr::Arg(self.into_tmp(Expr::Quad(quad), &typ, false))
+            }
         }
     }
 
     fn eval_expression_var(&mut self, var: &Variable) -> Expr {
         match var {
-            Variable::NamedVariable(name) => {
-                Expr::Arg(QuadrupelArg::Var(QuadrupelVar::Spl(name.to_string())))
-            }
-            Variable::ArrayAccess(array_access) => {
-                let (var, offset) = self.eval_array_access(array_access);
-                self.emit_expression_arr_acc(var, offset)
+            Variable::NamedVariable(name, _) => self.constant(name).map_or_else(
+                || Expr::Arg(QuadrupelArg::Var(QuadrupelVar::Spl(name.clone()))),
+                |value| Expr::Arg(QuadrupelArg::Const(value)),
+            ),
+            Variable::ArrayAccess(_) | Variable::FieldAccess(_) => {
+                let (array_var, offset) = self.eval_access(var);
+                self.emit_expression_arr_acc(array_var, offset, var.span())
             }
         }
     }
 
-    fn emit_expression_arr_acc(&mut self, array_var: QuadrupelVar, offset: Expr) -> Expr {
+    /// Returns the value of a named constant, which is folded into the code.
+    /// Local variables and parameters shadow global constants.
+    fn constant(&self, name: &str) -> Option<i32> {
+        if self.local_table().entries.contains_key(name) {
+            return None;
+        }
+        match self.global_table.borrow().lookup(name) {
+            Some(Entry::ConstEntry(entry)) => Some(entry.value),
+            _ => None,
+        }
+    }
+
+    fn emit_expression_arr_acc(
+        &mut self,
+        array_var: QuadrupelVar,
+        offset: Expr,
+        span: Span,
+    ) -> Expr {
         let mut quad = Quadrupel::new();
         quad.op = QuadrupelOp::ArrayLoad;
         quad.arg1 = QuadrupelArg::Var(array_var);
         quad.arg2 = self.into_tmp(offset, &Type::INT, false);
+        quad.span = Some(span);
         Expr::Quad(quad)
     }
 
-    fn emit_expression_bin(&mut self, op: Operator, left: Expr, right: Expr) -> Expr {
+    fn emit_expression_bin(&mut self, op: Operator, left: Expr, right: Expr, span: Span) -> Expr {
         let mut quad = Quadrupel::new();
         quad.op = op.into();
         quad.arg1 = self.into_tmp(left, &Type::INT, false);
         quad.arg2 = self.into_tmp(right, &Type::INT, false);
+        quad.span = Some(span);
         Expr::Quad(quad)
     }
 
-    fn emit_expression_un(&mut self, op: UnaryOperator, left: Expr) -> Expr {
+    fn emit_expression_un(&mut self, op: UnaryOperator, left: Expr, span: Span) -> Expr {
         let mut quad = Quadrupel::new();
         quad.op = op.into();
         quad.arg1 = self.into_tmp(left, &Type::INT, false);
+        quad.span = Some(span);
         Expr::Quad(quad)
     }
 
@@ -258,6 +660,30 @@ impl<'a> Tac {
         QuadrupelResult::Label(label)
     }
 
+    fn emit_goto(&mut self, label: QuadrupelResult) {
+        let mut quad = Quadrupel::new();
+        quad.op = QuadrupelOp::Goto;
+        quad.result = label;
+        self.quadrupels.push(quad);
+    }
+
+    fn emit_jump(
+        &mut self,
+        op: QuadrupelOp,
+        arg1: QuadrupelArg,
+        arg2: QuadrupelArg,
+        label: QuadrupelResult,
+        span: Option<Span>,
+    ) {
+        let mut quad = Quadrupel::new();
+        quad.op = op;
+        quad.arg1 = arg1;
+        quad.arg2 = arg2;
+        quad.result = label;
+        quad.span = span;
+        self.quadrupels.push(quad);
+    }
+
     fn emit_label(&mut self, label: QuadrupelResult) {
         assert!(matches!(label, QuadrupelResult::Label(_)));
         let mut new_quad: Quadrupel = Quadrupel::new();
@@ -305,10 +731,22 @@ impl Quadrupel {
             arg1: QuadrupelArg::Empty,
             arg2: QuadrupelArg::Empty,
             result: QuadrupelResult::Empty,
+            span: None,
         }
     }
 }
 
+// A jump table pays off for a few labels that fill at least half of their range
+fn is_dense(targets: &[(i32, &QuadrupelResult, Span)]) -> bool {
+    const MIN_LABELS: usize = 4;
+
+    let (Some((low, ..)), Some((high, ..))) = (targets.first(), targets.last()) else {
+        return false;
+    };
+    let range = i64::from(*high) - i64::from(*low) + 1;
+    targets.len() >= MIN_LABELS && range <= 2 * i64::try_from(targets.len()).unwrap()
+}
+
 enum Expr {
     Quad(Quadrupel),
     Arg(QuadrupelArg),
@@ -1,12 +1,20 @@
 use colored::Colorize;
 use std::fmt;
 
+use crate::diagnostics::Span;
+
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum QuadrupelOp {
     Add,
     Sub,
     Mul,
     Div,
+    Mod,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
     Neg,
     Equ,
     Neq,
@@ -14,12 +22,15 @@ pub enum QuadrupelOp {
     Lse,
     Grt,
     Gre,
-    Assign,     // v := w
-    ArrayLoad,  // x = y[i]   =[]
-    ArrayStore, // x[i] = y   []=
-    Goto,       // let the fun begin
+    Assign,      // v := w
+    ArrayLoad,   // x = y[i]   =[]
+    ArrayStore,  // x[i] = y   []=
+    BoundsCheck, // 0 <= i < n
+    Goto,        // let the fun begin
+    JumpTable,   // goto L[i], 0 <= i < n
     Param,
-    Call, // call p, n
+    Call,   // call p, n
+    Return, // return v
     #[default]
     Default,
 }
@@ -77,7 +88,7 @@ pub enum QuadrupelVar {
 impl QuadrupelVar {
     pub fn to_identifier(&self) -> String {
         match self {
-            Self::Spl(name) => name.to_string(),
+            Self::Spl(name) => name.clone(),
             Self::Tmp(t) => format!("T{t}"),
         }
     }
@@ -96,6 +107,8 @@ impl fmt::Display for QuadrupelVar {
 pub enum QuadrupelResult {
     Var(QuadrupelVar),
     Label(String),
+    /// The targets of a `JumpTable`, indexed from 0
+    Table(Vec<String>),
     #[default]
     Empty,
 }
@@ -118,17 +131,20 @@ impl fmt::Display for QuadrupelResult {
         match self {
             Self::Var(var) => write!(f, "{}", var.to_string().truecolor(200, 200, 200)),
             Self::Label(name) => write!(f, "{name}"),
+            Self::Table(labels) => write!(f, "{}", labels.join(", ")),
             Self::Empty => Ok(()),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Quadrupel {
     pub op: QuadrupelOp,
     pub arg1: QuadrupelArg,
     pub arg2: QuadrupelArg,
     pub result: QuadrupelResult,
+    /// The source code the quad was generated from, used for runtime errors
+    pub span: Option<Span>,
 }
 
 impl Quadrupel {
@@ -137,9 +153,21 @@ impl Quadrupel {
         arg1: QuadrupelArg::Empty,
         arg2: QuadrupelArg::Empty,
         result: QuadrupelResult::Empty,
+        span: None,
     };
 }
 
+// Quads are compared by what they compute, not by where they came from
+impl PartialEq for Quadrupel {
+    fn eq(&self, other: &Self) -> bool {
+        self.op == other.op
+            && self.arg1 == other.arg1
+            && self.arg2 == other.arg2
+            && self.result == other.result
+    }
+}
+impl Eq for Quadrupel {}
+
 impl fmt::Display for Quadrupel {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.op == QuadrupelOp::Default {
@@ -163,6 +191,7 @@ macro_rules! quad {
             arg1: quad!(@arg $arg1),
             arg2: quad!(@arg $arg2),
             result: $result,
+            span: None,
         }
     }};
     ($op:expr, $arg1:tt, $arg2:tt => $result:expr ) => {{
@@ -171,6 +200,7 @@ macro_rules! quad {
             arg1: quad!(@arg $arg1),
             arg2: quad!(@arg $arg2),
             result: $result,
+            span: None,
         }
     }};
 
@@ -180,6 +210,12 @@ macro_rules! quad {
     (@op -   ) => { quad!(@@op Sub       ) };
     (@op *   ) => { quad!(@@op Mul       ) };
     (@op /   ) => { quad!(@@op Div       ) };
+    (@op %   ) => { quad!(@@op Mod       ) };
+    (@op &   ) => { quad!(@@op And       ) };
+    (@op |   ) => { quad!(@@op Or        ) };
+    (@op ^   ) => { quad!(@@op Xor       ) };
+    (@op <<  ) => { quad!(@@op Shl       ) };
+    (@op >>  ) => { quad!(@@op Shr       ) };
     (@op ~   ) => { quad!(@@op Neg       ) };
     (@op ==  ) => { quad!(@@op Equ       ) };
     (@op !=  ) => { quad!(@@op Neq       ) };
@@ -193,6 +229,7 @@ macro_rules! quad {
     (@op =>  ) => { quad!(@@op Goto      ) };
     (@op p   ) => { quad!(@@op Param     ) };
     (@op c   ) => { quad!(@@op Call      ) };
+    (@op r   ) => { quad!(@@op Return    ) };
     (@op d   ) => { quad!(@@op Default   ) };
     (@op _   ) => { _                      };
     (@op ($($op:tt)+) $(($($ops:tt)+))* ) => { quad!(@op $($op)+) $(| quad!(@op $($ops)+))* };
@@ -215,6 +252,7 @@ macro_rules! quad_match {
             arg1: quad_match!(@arg $arg1),
             arg2: quad_match!(@arg $arg2),
             result: $result,
+            ..
         }
     };
     ($op:pat, $arg1:tt, $arg2:tt => $result:pat ) => {
@@ -224,6 +262,7 @@ macro_rules! quad_match {
             arg1: quad_match!(@arg $arg1),
             arg2: quad_match!(@arg $arg2),
             result: $result,
+            ..
         }
     };
 
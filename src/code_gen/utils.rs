@@ -1,21 +1,37 @@
 use crate::{
     absyn::{binary_expression::Operator, unary_expression::UnaryOperator},
-    table::types::{PrimitiveType, Type},
+    table::types::{PrimitiveType, RecordType, Type},
 };
 
 use super::QuadrupelOp;
 
 impl Type {
     pub fn get_byte_size(&self) -> i32 {
-        #[expect(clippy::match_wildcard_for_single_variants)]
         match self {
             Self::ArrayType(a) => a.base_type.get_byte_size() * i32::try_from(a.size).unwrap(),
-            Self::PrimitiveType(PrimitiveType::Int) => 4,
+            // The fields of a record are stored one after another
+            Self::RecordType(r) => r.fields.iter().map(|f| f.typ.get_byte_size()).sum(),
+            // Booleans are stored as 0 or 1 in a full word
+            // Strings are passed as the address of their characters in read-only data
+            Self::PrimitiveType(
+                PrimitiveType::Int | PrimitiveType::Bool | PrimitiveType::String,
+            ) => 4,
             _ => panic!("Byte size not defined for {self:?}"),
         }
     }
 }
 
+impl RecordType {
+    /// The offset of the field with the given name from the start of the record in bytes.
+    pub fn get_field_offset(&self, name: &str) -> i32 {
+        self.fields
+            .iter()
+            .take_while(|f| f.name != name)
+            .map(|f| f.typ.get_byte_size())
+            .sum()
+    }
+}
+
 impl From<Operator> for QuadrupelOp {
     fn from(op: Operator) -> Self {
         match op {
@@ -23,12 +39,21 @@ impl From<Operator> for QuadrupelOp {
             Operator::Sub => Self::Sub,
             Operator::Mul => Self::Mul,
             Operator::Div => Self::Div,
+            Operator::Mod => Self::Mod,
+            Operator::BitAnd => Self::And,
+            Operator::BitOr => Self::Or,
+            Operator::BitXor => Self::Xor,
+            Operator::Shl => Self::Shl,
+            Operator::Shr => Self::Shr,
             Operator::Equ => Self::Equ,
             Operator::Neq => Self::Neq,
             Operator::Lst => Self::Lst,
             Operator::Lse => Self::Lse,
             Operator::Grt => Self::Grt,
             Operator::Gre => Self::Gre,
+            Operator::And | Operator::Or => {
+                unreachable!("logical operators are translated to jumps")
+            }
         }
     }
 }
@@ -51,6 +76,7 @@ impl From<UnaryOperator> for QuadrupelOp {
     fn from(op: UnaryOperator) -> Self {
         match op {
             UnaryOperator::Minus => Self::Neg,
+            UnaryOperator::Not => unreachable!("logical operators are translated to jumps"),
         }
     }
 }
@@ -0,0 +1,229 @@
+use std::fmt::Write as _;
+
+use colored::{ColoredString, Colorize};
+
+/// A range of bytes in the source code, together with the line and column it starts at.
+/// Lines and columns are counted from 1, columns in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Span {
+    /// The span from the start of `self` to the end of `other`.
+    pub const fn to(self, other: Self) -> Self {
+        Self {
+            end: other.end,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn colorize(self, s: &str) -> ColoredString {
+        match self {
+            Self::Error => s.red().bold(),
+            Self::Warning => s.yellow().bold(),
+        }
+    }
+}
+
+/// A message for the user, shown together with the code it refers to.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Code of the error or lint, e.g. `W0003`
+    pub code: Option<String>,
+    pub message: String,
+    pub span: Option<Span>,
+    /// Shown instead of a source location if there is no span
+    pub location: Option<String>,
+}
+
+impl Diagnostic {
+    pub const fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            code: None,
+            message,
+            span: None,
+            location: None,
+        }
+    }
+
+    pub fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(message)
+        }
+    }
+
+    pub fn with_code(self, code: String) -> Self {
+        Self {
+            code: Some(code),
+            ..self
+        }
+    }
+
+    pub fn with_span(self, span: Option<Span>) -> Self {
+        Self { span, ..self }
+    }
+
+    pub fn with_location(self, location: String) -> Self {
+        Self {
+            location: Some(location),
+            ..self
+        }
+    }
+}
+
+/// A source file, used to render diagnostics.
+#[derive(Debug, Clone)]
+pub struct Source<'a> {
+    pub name: String,
+    pub text: &'a str,
+}
+
+impl Source<'_> {
+    /// Renders a diagnostic like rustc does:
+    ///
+    /// ```text
+    /// error: undefined variable `x`
+    ///  --> test.spl:3:2
+    ///   |
+    /// 3 |     x := 1;
+    ///   |     ^
+    /// ```
+    ///
+    /// Spans covering several lines are underlined up to the end of their first line.
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let severity = diagnostic.severity;
+        let mut out = match severity {
+            Severity::Error => severity.colorize("error"),
+            Severity::Warning => severity.colorize("warning"),
+        }
+        .to_string();
+        if let Some(code) = &diagnostic.code {
+            out += &severity.colorize(&format!("[{code}]")).to_string();
+        }
+        let _ = write!(out, "{}", format!(": {}", diagnostic.message).bold());
+
+        let Some(span) = diagnostic.span else {
+            if let Some(location) = &diagnostic.location {
+                let _ = write!(out, "\n  {} {location}", "-->".blue().bold());
+            }
+            return out;
+        };
+
+        let line_start = self.text[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.text[span.start..]
+            .find('\n')
+            .map_or(self.text.len(), |i| span.start + i);
+        let line = self.text[line_start..line_end].trim_end_matches('\r');
+        let end = span.end.clamp(span.start, line_start + line.len());
+
+        // Tabs are shown as four spaces, the underline has to be shifted accordingly
+        let width = |s: &str| {
+            s.chars()
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum::<usize>()
+        };
+        let indent = width(&self.text[line_start..span.start]);
+        let carets = width(&self.text[span.start..end]).max(1);
+
+        let number = span.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let pipe = "|".blue().bold();
+
+        let _ = write!(
+            out,
+            "\n{gutter}{} {}:{}:{}",
+            "-->".blue().bold(),
+            self.name,
+            span.line,
+            span.col
+        );
+        let _ = write!(out, "\n{gutter} {pipe}");
+        let _ = write!(
+            out,
+            "\n{} {pipe} {}",
+            number.blue().bold(),
+            line.replace('\t', "    ")
+        );
+        let _ = write!(
+            out,
+            "\n{gutter} {pipe} {}{}",
+            " ".repeat(indent),
+            severity.colorize(&"^".repeat(carets))
+        );
+
+        out
+    }
+}
+
+/// The files of a program. Each file starts at an offset, spans of the program
+/// are positions in this combined space, so every span belongs to exactly one file.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap<'a> {
+    files: Vec<(usize, Source<'a>)>,
+}
+
+impl<'a> SourceMap<'a> {
+    /// Adds a file and returns its offset, which is passed to the parser.
+    /// Files are separated by one position, so the end of one file is not
+    /// the start of the next one.
+    pub fn add(&mut self, source: Source<'a>) -> usize {
+        let offset = self
+            .files
+            .last()
+            .map_or(0, |(offset, source)| offset + source.text.len() + 1);
+        self.files.push((offset, source));
+        offset
+    }
+
+    /// The main file, which was added first.
+    pub fn main(&self) -> &Source<'a> {
+        &self.files[0].1
+    }
+
+    /// The file containing the given span, together with the span within that file.
+    pub fn lookup(&self, span: Span) -> (&Source<'a>, Span) {
+        let index = self
+            .files
+            .partition_point(|(offset, _)| *offset <= span.start)
+            .saturating_sub(1);
+        let (offset, source) = &self.files[index];
+        let span = Span {
+            start: span.start - offset,
+            end: span.end - offset,
+            ..span
+        };
+        (source, span)
+    }
+
+    /// Renders a diagnostic with the file it refers to, see `Source::render`.
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let Some(span) = diagnostic.span else {
+            return self.main().render(diagnostic);
+        };
+        let (source, span) = self.lookup(span);
+        source.render(&diagnostic.clone().with_span(Some(span)))
+    }
+}
+
+impl<'a> From<Source<'a>> for SourceMap<'a> {
+    fn from(source: Source<'a>) -> Self {
+        let mut sources = Self::default();
+        sources.add(source);
+        sources
+    }
+}
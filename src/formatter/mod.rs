@@ -0,0 +1,560 @@
+#![expect(clippy::linkedlist)]
+
+use std::{cell::Cell, collections::LinkedList, fmt::Write as _};
+
+use crate::{
+    absyn::{
+        absyn::{Definition, Expression, Program, Statement, TypeExpression, Variable},
+        binary_expression::Operator,
+        call_statement::CallStatement,
+        const_definition::ConstDefinition,
+        parameter_definition::ParameterDefinition,
+        procedure_definition::ProcedureDefinition,
+        type_definition::TypeDefinition,
+    },
+    diagnostics::Span,
+    parser::{
+        error::ParseErrors, lexer::tokenize_with_comments, parse_everything_else::parse,
+        tokens::Comment,
+    },
+};
+
+const INDENT: &str = "  ";
+const UNARY_PRECEDENCE: u8 = 5;
+
+/// Parses the input and prints it in the canonical SPL style:
+/// two spaces of indentation, one declaration per line, spaces around
+/// binary operators and only the parentheses the precedence requires.
+/// Comments are kept, blank lines are limited to two between definitions
+/// and one inside procedures.
+pub fn format_source(input: &str) -> Result<String, ParseErrors> {
+    let program = parse(input)?;
+    let (_, comments, _) = tokenize_with_comments(input);
+    Ok(format(&program, input, &comments))
+}
+
+/// Prints a program parsed from `source`. Integer literals are taken from
+/// the source, so hexadecimal and character literals stay as they are.
+pub fn format(program: &Program, source: &str, comments: &[Comment]) -> String {
+    let mut formatter = Formatter {
+        source,
+        comments,
+        next_comment: Cell::new(0),
+        out: String::new(),
+        indent: 0,
+        last_end: 0,
+    };
+    formatter.program(program);
+    formatter.out
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    comments: &'a [Comment],
+    // Comments in parameter and argument lists are printed while the
+    // list is built, which only borrows the formatter
+    next_comment: Cell<usize>,
+    out: String,
+    indent: usize,
+    // End of the source text printed last, blank lines and comments
+    // are placed relative to it
+    last_end: usize,
+}
+
+impl Formatter<'_> {
+    fn program(&mut self, program: &Program) {
+        let mut previous: Option<&Definition> = None;
+        for def in &program.definitions {
+            // Procedures are separated by two blank lines, a group of imports,
+            // types and constants may stay together
+            let (min_blank, max_blank) = match (previous, def.as_ref()) {
+                (None, _) => (0, 2),
+                (Some(Definition::ProcedureDefinition(_)), _)
+                | (_, Definition::ProcedureDefinition(_)) => (2, 2),
+                _ => (0, 1),
+            };
+            match def.as_ref() {
+                Definition::ProcedureDefinition(p) => {
+                    self.separate(p.span.start, min_blank, max_blank);
+                    self.procedure(p);
+                }
+                Definition::TypeDefinition(t) => {
+                    self.separate(t.span.start, min_blank, max_blank);
+                    self.type_definition(t);
+                }
+                Definition::ConstDefinition(c) => {
+                    self.separate(c.span.start, min_blank, max_blank);
+                    self.const_definition(c);
+                }
+                Definition::ImportDefinition(i) => {
+                    self.separate(i.span.start, min_blank, max_blank);
+                    self.line(&format!("import \"{}\";", i.path));
+                    self.last_end = i.span.end;
+                }
+            }
+            previous = Some(def);
+        }
+
+        self.separate(self.source.len(), 0, 2);
+        if self.out.is_empty() {
+            return;
+        }
+        let trimmed = self.out.trim_end().len();
+        self.out.truncate(trimmed);
+        self.out.push('\n');
+    }
+
+    fn type_definition(&mut self, def: &TypeDefinition) {
+        self.line(&format!(
+            "type {} = {};",
+            def.name,
+            self.type_expression(&def.type_expression)
+        ));
+        self.last_end = def.span.end;
+    }
+
+    fn const_definition(&mut self, def: &ConstDefinition) {
+        self.line(&format!(
+            "const {} = {};",
+            def.name,
+            self.expression(&def.value, 0)
+        ));
+        self.last_end = def.span.end;
+    }
+
+    fn procedure(&mut self, def: &ProcedureDefinition) {
+        let parameters = self.list(
+            def.parameters
+                .iter()
+                .map(|p| (self.parameter(p), p.span.end)),
+        );
+        let return_type = def
+            .return_type
+            .as_ref()
+            .map(|te| format!(": {}", self.type_expression(te)))
+            .unwrap_or_default();
+        self.line(&format!("proc {}({parameters}){return_type} {{", def.name));
+        self.last_end = self.find_token(def.span.start, "{") + 1;
+
+        self.indent += 1;
+        for var in &def.variables {
+            self.separate(var.span.start, 0, 1);
+            self.line(&format!(
+                "var {}: {};",
+                var.name,
+                self.type_expression(&var.type_expression)
+            ));
+            self.last_end = var.span.end;
+        }
+        // The declarations are followed by a blank line
+        let min_blank = usize::from(!def.variables.is_empty());
+        for (i, statement) in def.body.iter().enumerate() {
+            self.separate(
+                statement.span().start,
+                if i == 0 { min_blank } else { 0 },
+                1,
+            );
+            self.statement(statement);
+        }
+        self.close_block(def.span);
+    }
+
+    // Prints the comments in front of the closing `}` and the brace itself
+    fn close_block(&mut self, span: Span) {
+        self.separate(span.end - 1, 0, 1);
+        self.indent -= 1;
+        self.line("}");
+        self.last_end = span.end;
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        self.out.push_str(&INDENT.repeat(self.indent));
+        self.statement_rest(statement);
+    }
+
+    // Prints a statement starting at the current position of the line
+    fn statement_rest(&mut self, statement: &Statement) {
+        match statement {
+            Statement::AssignStatement(s) => {
+                let target = self.variable(&s.target);
+                let value = self.expression(&s.value, 0);
+                let _ = writeln!(self.out, "{target} := {value};");
+            }
+            Statement::CallStatement(s) => {
+                let call = self.call(s);
+                let _ = writeln!(self.out, "{call};");
+            }
+            Statement::ReturnStatement(s) => match &s.value {
+                Some(value) => {
+                    let value = self.expression(value, 0);
+                    let _ = writeln!(self.out, "return {value};");
+                }
+                None => self.out.push_str("return;\n"),
+            },
+            Statement::EmptyStatement(_) => self.out.push_str(";\n"),
+            Statement::BreakStatement(_) => self.out.push_str("break;\n"),
+            Statement::ContinueStatement(_) => self.out.push_str("continue;\n"),
+            Statement::CompoundStatement(statements, span) => self.block(statements, *span),
+            Statement::IfStatement(s) => {
+                let condition = self.expression(&s.condition, 0);
+                let _ = write!(self.out, "if ({condition})");
+                self.body(&s.then_branch);
+
+                if let Some(else_branch) = &s.else_branch {
+                    let keyword = self.find_token(s.then_branch.span().end, "else");
+                    self.continue_after(&s.then_branch, keyword);
+                    self.out.push_str("else");
+                    if let Statement::IfStatement(_) = else_branch {
+                        self.out.push(' ');
+                        self.statement_rest(else_branch);
+                    } else {
+                        self.body(else_branch);
+                    }
+                }
+            }
+            Statement::WhileStatement(s) => {
+                let condition = self.expression(&s.condition, 0);
+                let _ = write!(self.out, "while ({condition})");
+                self.body(&s.body);
+            }
+            Statement::ForStatement(s) => {
+                let variable = self.variable(&s.variable);
+                let start = self.expression(&s.start, 0);
+                let end = self.expression(&s.end, 0);
+                let _ = write!(self.out, "for {variable} := {start} to {end}");
+                if s.step != 1 {
+                    let _ = write!(self.out, " step {}", s.step);
+                }
+                self.out.push_str(" do");
+                self.body(&s.body);
+            }
+            Statement::RepeatStatement(s) => {
+                self.out.push_str("repeat");
+                self.body(&s.body);
+                let keyword = self.find_token(s.body.span().end, "until");
+                self.continue_after(&s.body, keyword);
+                let condition = self.expression(&s.condition, 0);
+                let _ = writeln!(self.out, "until ({condition});");
+            }
+            Statement::CaseStatement(s) => {
+                let selector = self.expression(&s.selector, 0);
+                let _ = writeln!(self.out, "case {selector} of");
+                self.last_end = s.selector.span().end;
+                self.indent += 1;
+                for branch in &s.branches {
+                    self.separate(branch.span.start, 0, 1);
+                    let labels = self.list(
+                        branch
+                            .labels
+                            .iter()
+                            .map(|label| (self.expression(label, 0), label.span().end)),
+                    );
+                    let _ = write!(self.out, "{}{labels}:", INDENT.repeat(self.indent));
+                    self.branch(&branch.statement);
+                }
+                if let Some(default) = &s.default {
+                    self.separate(default.span().start, 0, 1);
+                    self.out.push_str(&INDENT.repeat(self.indent));
+                    self.out.push_str("else");
+                    self.branch(default);
+                }
+                // The comments in front of `end`
+                self.separate(s.span.end - 3, 0, 1);
+                self.indent -= 1;
+                self.line("end");
+            }
+        }
+        self.last_end = statement.span().end;
+    }
+
+    // A branch of a case statement stays on the line of its labels
+    fn branch(&mut self, statement: &Statement) {
+        self.out.push(' ');
+        if let Statement::CompoundStatement(statements, span) = statement {
+            self.block(statements, *span);
+        } else {
+            self.statement_rest(statement);
+        }
+    }
+
+    // Continues a statement with the keyword at `keyword` behind its body.
+    // The keyword follows a compound body on the line of its `}`, as in
+    // `} else`, unless a comment is in between.
+    fn continue_after(&mut self, body: &Statement, keyword: usize) {
+        let commented = self
+            .comments
+            .get(self.next_comment.get())
+            .is_some_and(|c| c.span.start < keyword);
+        if matches!(body, Statement::CompoundStatement(..)) && !commented {
+            self.out.pop();
+            self.out.push(' ');
+        } else {
+            self.separate(keyword, 0, 0);
+            self.out.push_str(&INDENT.repeat(self.indent));
+        }
+    }
+
+    // A compound body stays on the line of the `if` or loop,
+    // any other statement is indented on the next line
+    fn body(&mut self, statement: &Statement) {
+        if let Statement::CompoundStatement(statements, span) = statement {
+            self.out.push(' ');
+            self.block(statements, *span);
+        } else {
+            self.out.push('\n');
+            self.indent += 1;
+            self.separate(statement.span().start, 0, 0);
+            self.statement(statement);
+            self.indent -= 1;
+        }
+    }
+
+    fn block(&mut self, statements: &LinkedList<Box<Statement>>, span: Span) {
+        self.out.push_str("{\n");
+        self.last_end = span.start + 1;
+
+        self.indent += 1;
+        for statement in statements {
+            self.separate(statement.span().start, 0, 1);
+            self.statement(statement);
+        }
+        self.close_block(span);
+    }
+
+    // Prints the comments before `pos` followed by the blank lines in front of
+    // the next item. Blank lines are kept from the source within the bounds,
+    // `min_blank` only applies to the first thing printed.
+    fn separate(&mut self, pos: usize, mut min_blank: usize, max_blank: usize) {
+        let mut previous_end = self.last_end;
+        while let Some(comment) = self
+            .comments
+            .get(self.next_comment.get())
+            .filter(|c| c.span.start < pos)
+        {
+            self.next_comment.set(self.next_comment.get() + 1);
+            let gap = &self.source[previous_end.min(comment.span.start)..comment.span.start];
+            if !gap.contains('\n') && self.out.ends_with('\n') {
+                // A comment behind code stays on its line
+                self.out.pop();
+                self.out.push(' ');
+                self.out.push_str(&comment.text);
+                self.out.push('\n');
+            } else {
+                self.blank_lines(comment.span.start, min_blank, max_blank);
+                self.line(&comment.text);
+                min_blank = 0;
+            }
+            // A comment moved behind later code must not add its line breaks
+            previous_end = comment.span.end;
+            self.last_end = self.last_end.max(previous_end);
+        }
+        self.blank_lines(pos, min_blank, max_blank);
+    }
+
+    fn blank_lines(&mut self, pos: usize, min_blank: usize, max_blank: usize) {
+        if self.out.is_empty() {
+            return;
+        }
+        let gap = &self.source[self.last_end.min(pos)..pos];
+        let blank = gap.matches('\n').count().saturating_sub(1);
+        self.out
+            .push_str(&"\n".repeat(blank.clamp(min_blank, max_blank.max(min_blank))));
+    }
+
+    fn line(&mut self, text: &str) {
+        self.out.push_str(&INDENT.repeat(self.indent));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    // Joins the items of a list with their end positions. A comment behind
+    // an item stays there and the list continues on the next line,
+    // indented twice to set it apart from a body.
+    fn list(&self, items: impl Iterator<Item = (String, usize)>) -> String {
+        let mut text = String::new();
+        let mut items = items.peekable();
+        while let Some((item, end)) = items.next() {
+            text.push_str(&item);
+            let last = items.peek().is_none();
+            if !last {
+                text.push(',');
+            }
+
+            let comments = self.comments_after(end);
+            let continuation = format!("\n{}", INDENT.repeat(self.indent + 2));
+            for (own_line, comment) in &comments {
+                text.push_str(if *own_line { &continuation } else { " " });
+                text.push_str(&comment.text);
+            }
+            if !comments.is_empty() {
+                text.push_str(&continuation);
+            } else if !last {
+                text.push(' ');
+            }
+        }
+        text
+    }
+
+    // Takes the comments directly behind `end`, only separated by white
+    // space and the comma of a list, and whether they are on their own line
+    fn comments_after(&self, mut end: usize) -> Vec<(bool, &Comment)> {
+        let mut comments = vec![];
+        while let Some(comment) = self.comments.get(self.next_comment.get()).filter(|c| {
+            c.span.start >= end
+                && self.source[end..c.span.start]
+                    .chars()
+                    .all(|c| c.is_whitespace() || c == ',')
+        }) {
+            self.next_comment.set(self.next_comment.get() + 1);
+            comments.push((self.source[end..comment.span.start].contains('\n'), comment));
+            end = comment.span.end;
+        }
+        comments
+    }
+
+    // Position of the next `token` behind `from` outside of comments
+    fn find_token(&self, from: usize, token: &str) -> usize {
+        let mut pos = from;
+        loop {
+            let found = pos + self.source[pos..].find(token).unwrap();
+            match self
+                .comments
+                .iter()
+                .find(|c| c.span.start <= found && found < c.span.end)
+            {
+                Some(comment) => pos = comment.span.end,
+                None => return found,
+            }
+        }
+    }
+
+    fn parameter(&self, parameter: &ParameterDefinition) -> String {
+        format!(
+            "{}{}: {}",
+            if parameter.is_reference { "ref " } else { "" },
+            parameter.name,
+            self.type_expression(&parameter.type_expression)
+        )
+    }
+
+    fn type_expression(&self, type_expression: &TypeExpression) -> String {
+        self.indented_type_expression(type_expression, self.indent)
+    }
+
+    // Records have one field per line, indented one level deeper than `end`
+    fn indented_type_expression(&self, type_expression: &TypeExpression, indent: usize) -> String {
+        match type_expression {
+            TypeExpression::NamedTypeExpression(name, _) => name.clone(),
+            TypeExpression::ArrayTypeExpression(a) => format!(
+                "array [{}] of {}",
+                self.expression(&a.array_size, 0),
+                self.indented_type_expression(&a.base_type, indent)
+            ),
+            TypeExpression::RecordTypeExpression(r) => {
+                let mut text = String::from("record\n");
+                for field in &r.fields {
+                    let _ = writeln!(
+                        text,
+                        "{}{}: {};",
+                        INDENT.repeat(indent + 1),
+                        field.name,
+                        self.indented_type_expression(&field.type_expression, indent + 1)
+                    );
+                }
+                text.push_str(&INDENT.repeat(indent));
+                text.push_str("end");
+                text
+            }
+        }
+    }
+
+    // `min_precedence` is the lowest precedence which may be printed
+    // without parentheses at this position
+    fn expression(&self, expression: &Expression, min_precedence: u8) -> String {
+        let text = match expression {
+            Expression::BinaryExpression(e) => {
+                let precedence = precedence(expression);
+                // All binary operators are left associative
+                format!(
+                    "{} {} {}",
+                    self.expression(&e.left, precedence),
+                    e.operator,
+                    self.expression(&e.right, precedence + 1)
+                )
+            }
+            Expression::UnaryExpression(e) => {
+                format!(
+                    "{}{}",
+                    e.operator,
+                    self.expression(&e.operand, UNARY_PRECEDENCE)
+                )
+            }
+            // Literals keep their notation, e.g. `0x1F` or escape sequences
+            Expression::IntLiteral(_, span) | Expression::StringLiteral(_, span) => {
+                self.source[span.start..span.end].to_string()
+            }
+            Expression::BoolLiteral(value, _) => value.to_string(),
+            Expression::VariableExpression(var) => self.variable(var),
+            Expression::CallExpression(call) => self.call(call),
+        };
+
+        if precedence(expression) < min_precedence {
+            format!("({text})")
+        } else {
+            text
+        }
+    }
+
+    fn call(&self, call: &CallStatement) -> String {
+        let arguments = self.list(
+            call.arguments
+                .iter()
+                .map(|arg| (self.expression(arg, 0), arg.span().end)),
+        );
+        format!("{}({arguments})", call.name)
+    }
+
+    fn variable(&self, variable: &Variable) -> String {
+        match variable {
+            Variable::NamedVariable(name, _) => name.clone(),
+            Variable::ArrayAccess(a) => {
+                format!(
+                    "{}[{}]",
+                    self.variable(&a.array),
+                    self.expression(&a.index, 0)
+                )
+            }
+            Variable::FieldAccess(a) => format!("{}.{}", self.variable(&a.record), a.field),
+        }
+    }
+}
+
+// The precedence levels of the grammar, from `||` to
+// `expression5` (literals, calls, variables and parentheses)
+const fn precedence(expression: &Expression) -> u8 {
+    match expression {
+        Expression::BinaryExpression(e) => match e.operator {
+            Operator::Or => 0,
+            Operator::And => 1,
+            Operator::Equ
+            | Operator::Neq
+            | Operator::Lst
+            | Operator::Lse
+            | Operator::Grt
+            | Operator::Gre => 2,
+            Operator::Add | Operator::Sub | Operator::BitOr | Operator::BitXor => 3,
+            Operator::Mul
+            | Operator::Div
+            | Operator::Mod
+            | Operator::BitAnd
+            | Operator::Shl
+            | Operator::Shr => 4,
+        },
+        Expression::UnaryExpression(_) => UNARY_PRECEDENCE,
+        Expression::IntLiteral(..)
+        | Expression::BoolLiteral(..)
+        | Expression::StringLiteral(..)
+        | Expression::VariableExpression(_)
+        | Expression::CallExpression(_) => 6,
+    }
+}
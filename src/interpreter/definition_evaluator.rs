@@ -5,13 +5,14 @@ use crate::{
         absyn::{Definition, Program},
         call_statement::CallStatement,
     },
+    diagnostics::Span,
     interpreter::{
         environment::Environment,
         statement_evaluator::eval_call_statement,
         value::{Value, ValueFunction},
     },
     spl_builtins,
-    table::{entry::Entry, symbol_table::SymbolTable},
+    table::{entry::Entry, symbol_table::SymbolTable, types::Type},
 };
 
 use super::value::ValueRef;
@@ -26,7 +27,9 @@ pub fn eval_program<'a>(
         .iter()
         .filter_map(|def| match def.as_ref() {
             Definition::ProcedureDefinition(proc_def) => Some(proc_def),
-            Definition::TypeDefinition(_) => None,
+            Definition::TypeDefinition(_)
+            | Definition::ConstDefinition(_)
+            | Definition::ImportDefinition(_) => None,
         })
         .map(|proc_def| {
             let Some(Entry::ProcedureEntry(proc_entry)) = symbol_table.lookup(&proc_def.name)
@@ -34,15 +37,37 @@ pub fn eval_program<'a>(
                 unreachable!();
             };
             (
-                proc_def.name.to_string(),
+                proc_def.name.clone(),
                 Value::new_refcell(Value::Function(ValueFunction::Spl(
-                    proc_entry,
+                    Rc::new(proc_entry),
                     &proc_def.body,
                 ))),
             )
         });
 
-    Environment::new_global(procs, symbol_table)
+    // Constants are evaluated during the semantic analysis already
+    let consts = program
+        .definitions
+        .iter()
+        .filter_map(|def| match def.as_ref() {
+            Definition::ConstDefinition(const_def) => Some(const_def),
+            Definition::ProcedureDefinition(_)
+            | Definition::TypeDefinition(_)
+            | Definition::ImportDefinition(_) => None,
+        })
+        .map(|const_def| {
+            let Some(Entry::ConstEntry(const_entry)) = symbol_table.lookup(&const_def.name) else {
+                unreachable!();
+            };
+            let value = if const_entry.typ == Type::BOOL {
+                Value::Bool(const_entry.value != 0)
+            } else {
+                Value::Int(const_entry.value)
+            };
+            (const_def.name.clone(), Value::new_refcell(value))
+        });
+
+    Environment::new_global(procs.chain(consts), symbol_table)
 }
 
 // Starts the main procedure of the given program, initializing the environment and executing it.
@@ -53,13 +78,14 @@ pub fn start_main(program: &Program, table: &SymbolTable) {
     let call_stmt = CallStatement {
         name: "main".to_string(),
         arguments: LinkedList::new(),
+        span: Span::default(),
     };
 
     //Initialize program start for built-in time function
     spl_builtins::init_start_time();
 
     //Evaluate the call statement for the main procedure
-    eval_call_statement(&call_stmt, table, &env);
+    eval_call_statement(&call_stmt, &env);
 }
 
 // Evaluates a local variable by looking it up in the symbol table and returning its name and default value.
@@ -12,7 +12,7 @@ use super::value::ValueRef;
 // Represents an environment for variable storage
 #[derive(Clone, Debug)]
 pub struct Environment<'a, 'b> {
-    pub parent: Option<Rc<Environment<'a, 'b>>>,
+    pub parent: Option<Rc<Self>>,
     pub vars: RefCell<HashMap<String, ValueRef<'a>>>,
     symbol_table: &'b SymbolTable,
 }
@@ -96,7 +96,7 @@ impl<'a, 'b> Environment<'a, 'b> {
 pub fn get_builtins<'a>() -> impl Iterator<Item = (String, ValueRef<'a>)> {
     PROCEDURES.iter().filter_map(|&(name, params, body)| {
         body.map(|body| {
-            let params = params.iter().map(|p| (p.name.to_string(), p.is_reference));
+            let params = params.iter().map(|p| (p.name.clone(), p.is_reference));
             (
                 name.to_string(),
                 Value::new_refcell(Value::new_builtin_proc(params, body)),
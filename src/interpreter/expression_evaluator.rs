@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{cell::Cell, rc::Rc};
 
 use crate::{
     absyn::{
@@ -6,25 +6,43 @@ use crate::{
         binary_expression::{self, BinaryExpression},
         unary_expression::{self, UnaryExpression},
     },
-    interpreter::{environment::Environment, value::Value},
+    diagnostics::Span,
+    interpreter::{
+        environment::Environment, statement_evaluator::eval_call_statement, value::Value,
+    },
 };
 
 use super::value::ValueRef;
 
+thread_local! {
+    // Span of the expression that is being evaluated, reported if the program panics
+    static CURRENT_SPAN: Cell<Option<Span>> = const { Cell::new(None) };
+}
+
+// Returns the span of the expression that is currently evaluated, if it has one.
+pub fn current_span() -> Option<Span> {
+    CURRENT_SPAN.get()
+}
+
 // Evaluates any expression and returns its value.
 pub fn eval_expression<'a>(expression: &Expression, env: Rc<Environment<'a, '_>>) -> Value<'a> {
     match expression {
         Expression::BinaryExpression(binary_expression) => eval_binary(binary_expression, env),
         Expression::UnaryExpression(unary_expression) => eval_unary(unary_expression, env),
-        Expression::IntLiteral(i) => Value::Int(*i),
+        Expression::IntLiteral(i, _) => Value::Int(*i),
+        Expression::BoolLiteral(b, _) => Value::Bool(*b),
+        Expression::StringLiteral(s, _) => Value::String(s.as_str().into()),
         Expression::VariableExpression(variable) => eval_var(variable, &env).borrow().clone(),
+        Expression::CallExpression(call) => {
+            eval_call_statement(call, &env).expect("functions always return a value")
+        }
     }
 }
 
 // Evaluates a variable expression and returns its value.
 pub fn eval_var<'a>(variable: &Variable, env: &Rc<Environment<'a, '_>>) -> ValueRef<'a> {
     match variable {
-        Variable::NamedVariable(v) => env.get(v).unwrap(),
+        Variable::NamedVariable(v, _) => env.get(v).unwrap(),
         Variable::ArrayAccess(array_access) => {
             // Evaluate the index. Make sure it is an integer.
             let Value::Int(index) = eval_expression(&array_access.index, env.clone()) else {
@@ -37,11 +55,27 @@ pub fn eval_var<'a>(variable: &Variable, env: &Rc<Environment<'a, '_>>) -> Value
             };
 
             // Ensure the index is within bounds.
+            CURRENT_SPAN.set(Some(array_access.span));
             let index = eval_array_index(index, array.len());
 
             // Return the value at the specified index.
             array[index].clone()
         }
+        Variable::FieldAccess(field_access) => {
+            // The position of the field is given by the record type.
+            let (index, _) = field_access
+                .typ
+                .as_ref()
+                .unwrap()
+                .field(&field_access.field)
+                .unwrap();
+            let record = eval_var(&field_access.record, env);
+            let Value::Record(ref fields) = *record.borrow() else {
+                unreachable!()
+            };
+
+            fields[index].clone()
+        }
     }
 }
 
@@ -60,7 +94,19 @@ pub fn eval_binary<'a>(
 ) -> Value<'a> {
     // Evaluate the left and right operands of the binary expression.
     let op1 = eval_expression(&binary_expression.left, env.clone());
+
+    // Logical operators only evaluate the right operand if it decides the result.
+    match (binary_expression.operator, &op1) {
+        (binary_expression::Operator::And, Value::Bool(false)) => return Value::Bool(false),
+        (binary_expression::Operator::Or, Value::Bool(true)) => return Value::Bool(true),
+        (binary_expression::Operator::And | binary_expression::Operator::Or, _) => {
+            return eval_expression(&binary_expression.right, env);
+        }
+        _ => {}
+    }
+
     let op2 = eval_expression(&binary_expression.right, env);
+    CURRENT_SPAN.set(Some(binary_expression.span));
 
     // Calculate the result based on the operator.
     match binary_expression.operator {
@@ -68,12 +114,19 @@ pub fn eval_binary<'a>(
         binary_expression::Operator::Sub => op1 - op2,
         binary_expression::Operator::Mul => op1 * op2,
         binary_expression::Operator::Div => op1 / op2,
+        binary_expression::Operator::Mod => op1 % op2,
+        binary_expression::Operator::BitAnd => op1 & op2,
+        binary_expression::Operator::BitOr => op1 | op2,
+        binary_expression::Operator::BitXor => op1 ^ op2,
+        binary_expression::Operator::Shl => op1 << op2,
+        binary_expression::Operator::Shr => op1 >> op2,
         binary_expression::Operator::Equ => Value::Bool(op1 == op2),
         binary_expression::Operator::Neq => Value::Bool(op1 != op2),
         binary_expression::Operator::Lst => Value::Bool(op1 < op2),
         binary_expression::Operator::Lse => Value::Bool(op1 <= op2),
         binary_expression::Operator::Grt => Value::Bool(op1 > op2),
         binary_expression::Operator::Gre => Value::Bool(op1 >= op2),
+        binary_expression::Operator::And | binary_expression::Operator::Or => unreachable!(),
     }
 }
 
@@ -83,5 +136,6 @@ pub fn eval_unary<'a>(unary: &UnaryExpression, env: Rc<Environment<'a, '_>>) ->
 
     match unary.operator {
         unary_expression::UnaryOperator::Minus => -op,
+        unary_expression::UnaryOperator::Not => !op,
     }
 }
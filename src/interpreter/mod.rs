@@ -9,143 +9,288 @@ pub mod value;
 mod test {
     use std::{
         collections::HashMap,
-        fs,
+        fs, panic,
         path::{Path, PathBuf},
+        thread,
     };
 
+    use dialoguer::theme::ColorfulTheme;
     use rstest::rstest;
 
     use crate::{
         base_blocks::BlockGraph,
+        cli,
         code_gen::Tac,
-        interpreter::tac_interpreter::eval_tac,
-        parser::parse_everything_else::parse,
-        semant::{build_symbol_table::build_symbol_table, check_def_global},
+        diagnostics::SourceMap,
+        interpreter::{
+            expression_evaluator,
+            tac_interpreter::{self, eval_tac},
+        },
+        optimizations::{
+            call_graph::CallGraph,
+            pass_manager::{self, PassManager, Pipeline},
+        },
+        parser::loader::load,
+        semant::check_program,
+        spl_builtins,
     };
 
     use super::definition_evaluator::start_main;
 
     #[rstest]
-    fn ast(
+    fn run(
         #[files("spl-testfiles/runtime_tests/*.spl")]
         #[exclude("reftest.spl")]
         #[exclude("test8.spl")]
         #[exclude("test9.spl")]
         #[exclude("drawTest.spl")] // requires graphics
-        #[exclude("gol.spl")] // requires user input
-        #[exclude("lambda.spl")] // interactive
+        #[exclude("lambda.spl")] // interactive, exits the process
         #[exclude("sierpinski.spl")] // requires graphics
+        #[exclude("time.spl")] // prints the time
         path: PathBuf,
-    ) -> anyhow::Result<()> {
-        test_file_ast(&path)
-    }
-
-    fn test_file_ast(path: &Path) -> anyhow::Result<()> {
-        let code = fs::read_to_string(path).unwrap();
-
-        let mut absyn = parse(code.leak())?;
-
-        let table = build_symbol_table(&absyn)?;
-
-        absyn
-            .definitions
-            .iter_mut()
-            .try_for_each(|def| check_def_global(def, &table))?;
-
-        let t = table.borrow();
-        start_main(&absyn, &t);
+        #[values("ast", "tac", "-O2")] interpreter: &'static str,
+    ) {
+        with_large_stack(move || {
+            // Both interpreters print the same with and without optimizations
+            let output = match interpreter {
+                "ast" => run_ast(&path)?,
+                "tac" => run_tac(&path, &Pipeline::default())?,
+                _ => run_tac(&path, &Pipeline::level("2")?)?,
+            };
+            assert_eq!(output, fs::read_to_string(path.with_extension("expected"))?);
 
-        Ok(())
+            Ok(())
+        });
     }
 
     #[test]
     #[should_panic(expected = "index out of bounds for array length 3: -1")]
     fn ast_runtime_err_8() {
-        test_file_ast(Path::new("spl-testfiles/runtime_tests/test8.spl")).unwrap();
+        run_ast(Path::new("spl-testfiles/runtime_tests/test8.spl")).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "index out of bounds for array length 3: 3")]
     fn ast_runtime_err_9() {
-        test_file_ast(Path::new("spl-testfiles/runtime_tests/test9.spl")).unwrap();
+        run_ast(Path::new("spl-testfiles/runtime_tests/test9.spl")).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "not implemented: SPL-builtin `clearAll()`")]
     fn ast_unimplemented_drawtest() {
-        test_file_ast(Path::new("spl-testfiles/runtime_tests/drawTest.spl")).unwrap();
+        run_ast(Path::new("spl-testfiles/runtime_tests/drawTest.spl")).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "not implemented: SPL-builtin `clearAll()`")]
     fn ast_unimplemented_sierpinski() {
-        test_file_ast(Path::new("spl-testfiles/runtime_tests/sierpinski.spl")).unwrap();
-    }
-
-    #[rstest]
-    fn tac(
-        #[files("spl-testfiles/runtime_tests/*.spl")]
-        #[exclude("reftest.spl")]
-        #[exclude("test8.spl")]
-        #[exclude("test9.spl")]
-        #[exclude("acker.spl")] // TODO: fatal runtime error: stack overflow, aborting
-        #[exclude("drawTest.spl")] // requires graphics
-        #[exclude("gol.spl")] // requires user input
-        #[exclude("lambda.spl")] // interactive
-        #[exclude("sierpinski.spl")] // requires graphics
-        path: PathBuf,
-    ) -> anyhow::Result<()> {
-        test_file_tac(&path)
-    }
-
-    fn test_file_tac(path: &Path) -> anyhow::Result<()> {
-        let code = fs::read_to_string(path).unwrap();
-
-        let mut absyn = parse(code.leak())?;
-
-        let table = build_symbol_table(&absyn)?;
-
-        absyn
-            .definitions
-            .iter_mut()
-            .try_for_each(|def| check_def_global(def, &table))?;
-
-        let mut tac = Tac::new(table.clone());
-        tac.code_generation(&absyn);
-
-        let proc_graphs = tac
-            .proc_table
-            .into_iter()
-            .map(|(proc_name, quads)| (proc_name, BlockGraph::from_tac(&quads)))
-            .collect::<HashMap<_, _>>();
-
-        let t = table.borrow();
-        eval_tac(&proc_graphs, &t);
-
-        Ok(())
+        run_ast(Path::new("spl-testfiles/runtime_tests/sierpinski.spl")).unwrap();
     }
 
     #[test]
     #[should_panic(expected = "index out of bounds for array length 3: -1")]
     fn tac_runtime_err_8() {
-        test_file_tac(Path::new("spl-testfiles/runtime_tests/test8.spl")).unwrap();
+        run_tac(
+            Path::new("spl-testfiles/runtime_tests/test8.spl"),
+            &Pipeline::default(),
+        )
+        .unwrap();
     }
 
     #[test]
     #[should_panic(expected = "index out of bounds for array length 3: 3")]
     fn tac_runtime_err_9() {
-        test_file_tac(Path::new("spl-testfiles/runtime_tests/test9.spl")).unwrap();
+        run_tac(
+            Path::new("spl-testfiles/runtime_tests/test9.spl"),
+            &Pipeline::default(),
+        )
+        .unwrap();
     }
 
     #[test]
     #[should_panic(expected = "not implemented: SPL-builtin `clearAll()`")]
     fn tac_unimplemented_drawtest() {
-        test_file_tac(Path::new("spl-testfiles/runtime_tests/drawTest.spl")).unwrap();
+        run_tac(
+            Path::new("spl-testfiles/runtime_tests/drawTest.spl"),
+            &Pipeline::default(),
+        )
+        .unwrap();
     }
 
     #[test]
     #[should_panic(expected = "not implemented: SPL-builtin `clearAll()`")]
     fn tac_unimplemented_sierpinski() {
-        test_file_tac(Path::new("spl-testfiles/runtime_tests/sierpinski.spl")).unwrap();
+        run_tac(
+            Path::new("spl-testfiles/runtime_tests/sierpinski.spl"),
+            &Pipeline::default(),
+        )
+        .unwrap();
+    }
+
+    #[rstest]
+    #[case("7 / x", "attempt to divide by zero")]
+    #[case("7 % x", "attempt to calculate the remainder with a divisor of zero")]
+    #[case("y / -1", "attempt to divide with overflow")]
+    #[case("y % -1", "attempt to calculate the remainder with overflow")]
+    fn division_error(
+        #[case] expression: &str,
+        #[case] message: &str,
+        #[values("ast", "tac")] interpreter: &str,
+    ) {
+        let code = format!(
+            "proc main() {{ var x: int; var y: int; y := -2147483647 - 1; printi({expression}); }}"
+        );
+        let code: &'static str = code.leak();
+
+        // Both interpreters report the error at the expression
+        let (err, span) = if interpreter == "ast" {
+            let err = panic::catch_unwind(|| {
+                let mut absyn = load("test.spl", code, &mut SourceMap::default()).unwrap();
+                let table = check_program(&mut absyn).unwrap();
+                start_main(&absyn, &table.borrow());
+            })
+            .unwrap_err();
+            (err, expression_evaluator::current_span())
+        } else {
+            let err = panic::catch_unwind(|| {
+                run_program("test.spl", code, "", &Pipeline::default()).unwrap();
+            })
+            .unwrap_err();
+            (err, tac_interpreter::current_span())
+        };
+        assert_eq!(err.downcast_ref::<&str>(), Some(&message));
+        let span = span.unwrap();
+        assert_eq!(&code[span.start..span.end], expression);
+    }
+
+    #[rstest]
+    // Array stores only overwrite a single element, the first store is still live
+    #[case(
+        "dead",
+        "proc main() { var a: array [2] of int; var i: int; a[0] := 1; readi(i); \
+            if (i = 0) a[1] := 2; else a[1] := 3; printi(a[0]); }",
+        "0\n",
+        "1"
+    )]
+    // The caller reads a reference parameter after the procedure returns
+    #[case(
+        "dead",
+        "proc p(ref x: int) { x := 1; } proc main() { var y: int; p(y); printi(y); }",
+        "",
+        "1"
+    )]
+    // A procedure may write its reference arguments, they are not constant after the call
+    #[case(
+        "cf",
+        "proc main() { var x: int; x := 1; readi(x); printi(x); }",
+        "5\n",
+        "5"
+    )]
+    // The interpreter only allocates the temporaries from the local table
+    #[case(
+        "cse",
+        "proc main() { var x: int; readi(x); printi((x + 1) * (x + 1)); }",
+        "2\n",
+        "9"
+    )]
+    // Expressions with the same arguments but different operators are not common
+    #[case(
+        "cse",
+        "proc main() { var x: int; readi(x); printi(x + 3 - x * 3); }",
+        "3\n",
+        "-3"
+    )]
+    // Constants wrap around on overflow, even in code that never runs
+    #[case(
+        "cf",
+        "proc main() { var t: int; readi(t); \
+            if (t = 12345) { t := 2147483647; t := t + 1; } printi(t); }",
+        "1\n",
+        "1"
+    )]
+    fn optimized_program(
+        #[case] passes: &str,
+        #[case] code: &'static str,
+        #[case] input: &str,
+        #[case] expected: &str,
+    ) -> anyhow::Result<()> {
+        let pipeline = Pipeline::parse(passes.split(','))?;
+        assert_eq!(run_program("test.spl", code, input, &pipeline)?, expected);
+
+        Ok(())
+    }
+
+    // The TAC interpreter recurses deeply for acker.spl, so the tests run it in a
+    // thread with a larger stack.
+    fn with_large_stack(run: impl FnOnce() -> anyhow::Result<()> + Send + 'static) {
+        let run = thread::Builder::new()
+            .stack_size(256 << 20)
+            .spawn(run)
+            .unwrap();
+
+        match run.join() {
+            Ok(result) => result.unwrap(),
+            Err(err) => panic::resume_unwind(err),
+        }
+    }
+
+    // Runs the program in the AST interpreter and returns what it prints,
+    // the input is read from the `.input` file next to it.
+    fn run_ast(path: &Path) -> anyhow::Result<String> {
+        let code = fs::read_to_string(path).unwrap();
+        let input = fs::read_to_string(path.with_extension("input")).unwrap_or_default();
+
+        let name = path.to_string_lossy();
+        let mut absyn = load(&name, code.leak(), &mut SourceMap::default())?;
+
+        let table = check_program(&mut absyn)?;
+
+        let t = table.borrow();
+        Ok(spl_builtins::redirect_io(&input, || {
+            start_main(&absyn, &t);
+        }))
+    }
+
+    // Runs the program in the TAC interpreter after applying the pipeline,
+    // the input is read from the `.input` file next to it.
+    fn run_tac(path: &Path, pipeline: &Pipeline) -> anyhow::Result<String> {
+        let code = fs::read_to_string(path).unwrap();
+        let input = fs::read_to_string(path.with_extension("input")).unwrap_or_default();
+
+        run_program(&path.to_string_lossy(), code.leak(), &input, pipeline)
+    }
+
+    // Runs the program in the TAC interpreter after applying the pipeline
+    // and returns what it printed.
+    fn run_program(
+        name: &str,
+        code: &'static str,
+        input: &str,
+        pipeline: &Pipeline,
+    ) -> anyhow::Result<String> {
+        let mut absyn = load(name, code, &mut SourceMap::default())?;
+
+        let table = check_program(&mut absyn)?;
+
+        let mut tac = Tac::new(table.clone());
+        tac.code_generation(&absyn);
+
+        let call_graph = CallGraph::new(&tac.proc_table, &table.borrow());
+        call_graph.annotate(&mut table.borrow_mut());
+        pass_manager::run_program_passes(pipeline, &mut tac, &table)?;
+
+        let matches = cli::load_program_data().try_get_matches_from(["spl", name])?;
+        let theme = ColorfulTheme::default();
+        let mut proc_graphs = HashMap::new();
+        for (proc_name, quads) in &tac.proc_table {
+            let mut graph = BlockGraph::from_tac(quads);
+            PassManager::new(&mut graph, &table, proc_name, &matches, &theme).run(pipeline)?;
+            proc_graphs.insert(proc_name.clone(), graph);
+        }
+
+        let t = table.borrow();
+        Ok(spl_builtins::redirect_io(input, || {
+            eval_tac(&proc_graphs, &t);
+        }))
     }
 }
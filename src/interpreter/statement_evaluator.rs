@@ -5,50 +5,82 @@ use crate::{
         absyn::{Expression, Statement},
         assign_statement::AssignStatement,
         call_statement::CallStatement,
+        case_statement::CaseStatement,
+        for_statement::ForStatement,
         if_statement::IfStatement,
+        repeat_statement::RepeatStatement,
+        return_statement::ReturnStatement,
         while_statement::WhileStatement,
     },
     interpreter::{
         definition_evaluator::eval_local_var,
         environment::Environment,
         expression_evaluator::{eval_expression, eval_var},
-        value::{Value, ValueFunction},
+        value::{Value, ValueFunction, ValueRef},
     },
-    table::{entry::Entry, symbol_table::SymbolTable},
+    table::entry::ProcedureEntry,
 };
 
+// How the execution goes on after a statement.
+pub enum Flow<'a> {
+    // With the next statement
+    Normal,
+    // Back in the caller, with the value of a function
+    Return(Option<Value<'a>>),
+    // After the innermost loop
+    Break,
+    // With the next iteration of the innermost loop
+    Continue,
+}
+
 // Evaluates any statement and executes it in the given environment.
 pub fn eval_statement<'a, 'b: 'a>(
     statement: &'b Statement,
-    table: &SymbolTable,
     env: Rc<Environment<'b, '_>>,
-) {
+) -> Flow<'b> {
     match statement {
         Statement::AssignStatement(assign_statement) => {
             eval_assign_statement(assign_statement, &env);
         }
-        Statement::IfStatement(if_statement) => eval_if_statement(if_statement, table, env),
+        Statement::IfStatement(if_statement) => return eval_if_statement(if_statement, env),
         Statement::WhileStatement(while_statement) => {
-            eval_while_statement(while_statement, table, &env);
+            return eval_while_statement(while_statement, &env);
+        }
+        Statement::ForStatement(for_statement) => {
+            return eval_for_statement(for_statement, &env);
+        }
+        Statement::RepeatStatement(repeat_statement) => {
+            return eval_repeat_statement(repeat_statement, &env);
+        }
+        Statement::CaseStatement(case_statement) => {
+            return eval_case_statement(case_statement, env);
         }
+        Statement::BreakStatement(_) => return Flow::Break,
+        Statement::ContinueStatement(_) => return Flow::Continue,
         Statement::CallStatement(call_statement) => {
-            eval_call_statement(call_statement, table, &env);
+            eval_call_statement(call_statement, &env);
         }
-        Statement::EmptyStatement => (),
-        Statement::CompoundStatement(statements) => {
+        Statement::ReturnStatement(return_statement) => {
+            return eval_return_statement(return_statement, env);
+        }
+        Statement::EmptyStatement(_) => (),
+        Statement::CompoundStatement(statements, _) => {
             for s in statements {
-                eval_statement(s, table, env.clone());
+                let flow = eval_statement(s, env.clone());
+                if !matches!(flow, Flow::Normal) {
+                    return flow;
+                }
             }
         }
     }
+    Flow::Normal
 }
 
 // Executes an if statement.
 pub fn eval_if_statement<'a, 'b: 'a>(
     statement: &'b IfStatement,
-    table: &SymbolTable,
     env: Rc<Environment<'b, '_>>,
-) {
+) -> Flow<'b> {
     // Evaluate the condition of the if statement.
     let cond = eval_expression(&statement.condition, env.clone());
 
@@ -56,9 +88,11 @@ pub fn eval_if_statement<'a, 'b: 'a>(
     match cond {
         Value::Bool(b) => {
             if b {
-                eval_statement(&statement.then_branch, table, env);
+                eval_statement(&statement.then_branch, env)
             } else if let Some(ref s) = statement.else_branch {
-                eval_statement(s, table, env);
+                eval_statement(s, env)
+            } else {
+                Flow::Normal
             }
         }
         _ => unreachable!(),
@@ -80,22 +114,110 @@ pub fn eval_assign_statement<'a, 'b: 'a>(
 // Executes a while statement.
 pub fn eval_while_statement<'a, 'b: 'a>(
     statement: &'b WhileStatement,
-    table: &SymbolTable,
     env: &Rc<Environment<'b, '_>>,
-) {
+) -> Flow<'b> {
     // Evaluate the condition of the while statement. Execute the body as long as the condition is true.
     while eval_expression(&statement.condition, env.clone()) == Value::Bool(true) {
-        // Execute the body of the while statement.
-        eval_statement(&statement.body, table, env.clone());
+        // Execute the body of the while statement, a return or break leaves the loop.
+        match eval_statement(&statement.body, env.clone()) {
+            flow @ Flow::Return(_) => return flow,
+            Flow::Break => break,
+            Flow::Normal | Flow::Continue => (),
+        }
     }
+    Flow::Normal
+}
+
+// Executes a for statement. The end is evaluated once, the variable is
+// compared with it before and advanced by the step after each iteration.
+pub fn eval_for_statement<'a, 'b: 'a>(
+    statement: &'b ForStatement,
+    env: &Rc<Environment<'b, '_>>,
+) -> Flow<'b> {
+    let start = eval_expression(&statement.start, env.clone());
+    *eval_var(&statement.variable, env).borrow_mut() = start;
+    let Value::Int(end) = eval_expression(&statement.end, env.clone()) else {
+        unreachable!()
+    };
+
+    loop {
+        let Value::Int(i) = *eval_var(&statement.variable, env).borrow() else {
+            unreachable!()
+        };
+        if (statement.step > 0 && i > end) || (statement.step < 0 && i < end) {
+            break;
+        }
+
+        match eval_statement(&statement.body, env.clone()) {
+            flow @ Flow::Return(_) => return flow,
+            Flow::Break => break,
+            Flow::Normal | Flow::Continue => (),
+        }
+
+        let var = eval_var(&statement.variable, env);
+        let Value::Int(i) = *var.borrow() else {
+            unreachable!()
+        };
+        *var.borrow_mut() = Value::Int(i + statement.step);
+    }
+    Flow::Normal
+}
+
+// Executes a repeat statement, the body runs at least once.
+pub fn eval_repeat_statement<'a, 'b: 'a>(
+    statement: &'b RepeatStatement,
+    env: &Rc<Environment<'b, '_>>,
+) -> Flow<'b> {
+    loop {
+        // A continue goes on with the condition
+        match eval_statement(&statement.body, env.clone()) {
+            flow @ Flow::Return(_) => return flow,
+            Flow::Break => break,
+            Flow::Normal | Flow::Continue => (),
+        }
+        if eval_expression(&statement.condition, env.clone()) == Value::Bool(true) {
+            break;
+        }
+    }
+    Flow::Normal
+}
+
+// Executes a case statement, the branch with the value of the selector or the
+// `else` branch. A `break` or `continue` leaves the enclosing loop.
+pub fn eval_case_statement<'a, 'b: 'a>(
+    statement: &'b CaseStatement,
+    env: Rc<Environment<'b, '_>>,
+) -> Flow<'b> {
+    let Value::Int(selector) = eval_expression(&statement.selector, env.clone()) else {
+        unreachable!()
+    };
+    statement
+        .branches
+        .iter()
+        .find(|b| b.values.contains(&selector))
+        .map(|b| &b.statement)
+        .or(statement.default.as_ref())
+        .map_or(Flow::Normal, |s| eval_statement(s, env))
+}
+
+// Executes a return statement, evaluating the value of a function.
+pub fn eval_return_statement<'a, 'b: 'a>(
+    statement: &'b ReturnStatement,
+    env: Rc<Environment<'b, '_>>,
+) -> Flow<'b> {
+    let value = statement
+        .value
+        .as_ref()
+        .map(|value| eval_expression(value, env));
+    Flow::Return(value)
 }
 
 // Calls a procedure defined in the symbol table, passing the evaluated arguments.
-pub fn eval_call_statement<'a, 'b: 'a>(
-    statement: &'b CallStatement,
-    table: &SymbolTable,
+// Returns the value of a function, procedures return `None`.
+pub fn eval_call_statement<'a>(
+    statement: &CallStatement,
     env: &Rc<Environment<'a, '_>>,
-) {
+) -> Option<Value<'a>> {
     // Look up the procedure in the symbol table.
     let Some(Value::Function(proc)) = env.get(&statement.name).map(|v| v.borrow().clone()) else {
         unimplemented!("SPL-builtin `{}()`", statement.name);
@@ -121,42 +243,54 @@ pub fn eval_call_statement<'a, 'b: 'a>(
     // Match the procedure type and execute it accordingly.
     match proc {
         ValueFunction::Spl(proc_entry, proc_body) => {
-            // Get the local symbol table for the procedure.
-            let local_table = match table.lookup(&statement.name).unwrap() {
-                Entry::ProcedureEntry(procedure_entry) => procedure_entry.local_table,
-                _ => unreachable!(),
-            };
-
-            // get the parameters for the procedure call and create a new environment with the parameters and local variables.
-            let vars_param = proc_entry
-                .parameters
-                .iter()
-                .zip(args)
-                .map(|(var, arg)| (var.name.clone(), arg));
-            let vars_param_names = vars_param.clone().map(|(n, _)| n).collect::<Vec<_>>();
-
-            let vars_local = proc_entry
-                .local_table
-                .entries
-                .keys()
-                .filter(|&n| !vars_param_names.contains(n))
-                .map(|var_name| eval_local_var(var_name, &local_table));
-
-            let new_env = Rc::new(Environment::new(
-                env.clone(),
-                vars_param.chain(vars_local),
-                &local_table,
-            ));
-
-            // Execute the procedure body in the new environment.
+            let new_env = eval_proc_env(&proc_entry, args, env);
+
+            // Execute the procedure body in the new environment up to a return.
             for s in proc_body {
-                eval_statement(s, &local_table, new_env.clone());
+                if let Flow::Return(value) = eval_statement(s, new_env.clone()) {
+                    return value;
+                }
             }
+            None
         }
         ValueFunction::BuiltIn(_, f) => {
             // Call the built-in function with the evaluated arguments.
             f.call(&args);
+            None
         }
         ValueFunction::Tac(_, _) => unreachable!(),
     }
 }
+
+// Creates the environment of a procedure call, with the parameters bound to the
+// arguments and the local variables. Kept apart from `eval_call_statement` to
+// keep its stack frame small in deep recursions.
+fn eval_proc_env<'a, 'b>(
+    proc_entry: &'b ProcedureEntry,
+    args: Vec<ValueRef<'a>>,
+    env: &Rc<Environment<'a, 'b>>,
+) -> Rc<Environment<'a, 'b>> {
+    // Get the local symbol table for the procedure.
+    let local_table = &proc_entry.local_table;
+
+    // get the parameters for the procedure call and create a new environment with the parameters and local variables.
+    let vars_param = proc_entry
+        .parameters
+        .iter()
+        .zip(args)
+        .map(|(var, arg)| (var.name.clone(), arg));
+    let vars_param_names = vars_param.clone().map(|(n, _)| n).collect::<Vec<_>>();
+
+    let vars_local = proc_entry
+        .local_table
+        .entries
+        .keys()
+        .filter(|&n| !vars_param_names.contains(n))
+        .map(|var_name| eval_local_var(var_name, local_table));
+
+    Rc::new(Environment::new(
+        env.clone(),
+        vars_param.chain(vars_local),
+        local_table,
+    ))
+}
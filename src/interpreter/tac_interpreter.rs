@@ -1,8 +1,14 @@
-use std::{cell::RefCell, collections::HashMap, mem, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    mem,
+    rc::Rc,
+};
 
 use crate::{
     base_blocks::{BlockContent, BlockGraph},
     code_gen::quadrupel::{Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar},
+    diagnostics::Span,
     interpreter::{
         environment::Environment,
         expression_evaluator,
@@ -12,6 +18,16 @@ use crate::{
     table::{entry::Entry, symbol_table::SymbolTable},
 };
 
+thread_local! {
+    // Span of the quad that is being evaluated, reported if the program panics
+    static CURRENT_SPAN: Cell<Option<Span>> = const { Cell::new(None) };
+}
+
+// Returns the span of the quad that is currently evaluated, if it has one.
+pub fn current_span() -> Option<Span> {
+    CURRENT_SPAN.get()
+}
+
 // Evaluates the graphs of all procedure in a SPL program and initializes the global environment with procedures.
 pub fn eval_tac(proc_graphs: &HashMap<String, BlockGraph>, symbol_table: &SymbolTable) {
     // Initialize the global environment with SPL procedures.
@@ -20,8 +36,11 @@ pub fn eval_tac(proc_graphs: &HashMap<String, BlockGraph>, symbol_table: &Symbol
             unreachable!("function not found: {name}");
         };
         (
-            name.to_string(),
-            Value::new_refcell(Value::Function(ValueFunction::Tac(proc_entry, graph))),
+            name.clone(),
+            Value::new_refcell(Value::Function(ValueFunction::Tac(
+                Rc::new(proc_entry),
+                graph,
+            ))),
         )
     });
     let global_env = Rc::new(Environment::new_global(procs, symbol_table));
@@ -33,11 +52,12 @@ pub fn eval_tac(proc_graphs: &HashMap<String, BlockGraph>, symbol_table: &Symbol
     eval_function("main", &mut Vec::new(), global_env);
 }
 
+// Calls a procedure with the given arguments and returns the value of a function.
 pub fn eval_function<'a>(
     fun: &str,
     args: &mut Vec<ValueRef<'a>>,
     parent_env: Rc<Environment<'a, '_>>,
-) {
+) -> Option<Value<'a>> {
     // Look up the function in the parent environment.
     let Some(Value::Function(proc)) = parent_env.get(fun).map(|v| v.borrow().clone()) else {
         unimplemented!("SPL-builtin `{fun}()`");
@@ -50,7 +70,7 @@ pub fn eval_function<'a>(
                 .drain(..)
                 .zip(&proc.entry().parameters)
                 .map(|(arg, param)| {
-                    let param_name = param.name.to_string();
+                    let param_name = param.name.clone();
                     if param.is_reference {
                         (param_name, arg.clone())
                     } else {
@@ -77,7 +97,7 @@ pub fn eval_function<'a>(
                     };
                     let default =
                         Rc::new(RefCell::new(var_entry.typ.default_value().flatten_value()));
-                    Some((name.to_string(), default))
+                    Some((name.clone(), default))
                 });
 
             let env = Rc::new(Environment::new(
@@ -89,6 +109,7 @@ pub fn eval_function<'a>(
             // Execute the procedure body in the new environment. Start with the first block.
             let blocks = &proc_graph.blocks;
             let mut next_block = 0;
+            let mut return_value = None;
 
             // Iterate through the blocks of the procedure graph.
             while next_block < blocks.len() {
@@ -100,6 +121,10 @@ pub fn eval_function<'a>(
                     BlockContent::Code(quads) => {
                         // If the block contains code, evaluate each quadrupel in the block.
                         for quad in quads {
+                            // The value of a function is kept until the procedure ends.
+                            if quad.op == QuadrupelOp::Return {
+                                return_value = Some(parse_arg(&quad.arg1, &env));
+                            }
                             // Evaluate the quadrupel and check if it returns a label for the next block.
                             if let Some(l) = &eval_quad(args, quad, env.clone()) {
                                 next_block = *proc_graph.label_to_id.get(l).unwrap() - 1;
@@ -113,10 +138,12 @@ pub fn eval_function<'a>(
                     }
                 }
             }
+            return_value
         }
         ValueFunction::BuiltIn(_, f) => {
             // If the function is a built-in, call it with the provided arguments.
             f.call(args);
+            None
         }
         ValueFunction::Spl(_, _) => unreachable!(),
     }
@@ -129,6 +156,8 @@ pub fn eval_quad<'a>(
     quad: &Quadrupel,
     env: Rc<Environment<'a, '_>>,
 ) -> Option<String> {
+    CURRENT_SPAN.set(quad.span);
+
     match quad.op {
         QuadrupelOp::Add => {
             // Evaluate operands and perform addition.
@@ -164,6 +193,27 @@ pub fn eval_quad<'a>(
             *env.get(&res).unwrap().borrow_mut() = i / j;
             None
         }
+        QuadrupelOp::Mod
+        | QuadrupelOp::And
+        | QuadrupelOp::Or
+        | QuadrupelOp::Xor
+        | QuadrupelOp::Shl
+        | QuadrupelOp::Shr => {
+            // Evaluate operands and perform the remainder or bitwise operation.
+            let i = parse_arg(&quad.arg1, &env);
+            let j = parse_arg(&quad.arg2, &env);
+            let res = parse_result(&quad.result);
+            *env.get(&res).unwrap().borrow_mut() = match quad.op {
+                QuadrupelOp::Mod => i % j,
+                QuadrupelOp::And => i & j,
+                QuadrupelOp::Or => i | j,
+                QuadrupelOp::Xor => i ^ j,
+                QuadrupelOp::Shl => i << j,
+                QuadrupelOp::Shr => i >> j,
+                _ => unreachable!(),
+            };
+            None
+        }
         QuadrupelOp::Neg => {
             // Evaluate the argument and negate it.
             let i = parse_arg(&quad.arg1, &env);
@@ -305,12 +355,33 @@ pub fn eval_quad<'a>(
 
             None
         }
+        QuadrupelOp::BoundsCheck => {
+            // Check that the index is within the size of the array dimension.
+            let Value::Int(index) = parse_arg(&quad.arg1, &env) else {
+                unreachable!();
+            };
+            let QuadrupelArg::Const(size) = quad.arg2 else {
+                unreachable!();
+            };
+            expression_evaluator::eval_array_index(index, usize::try_from(size).unwrap());
+            None
+        }
         QuadrupelOp::Goto => {
             // Unconditional jump to a label.
             // Parse the label from the result and return it.
             let label = parse_result(&quad.result);
             Some(label)
         }
+        QuadrupelOp::JumpTable => {
+            // Jump to the label at the index, which is checked before.
+            let Value::Int(index) = parse_arg(&quad.arg1, &env) else {
+                unreachable!();
+            };
+            let QuadrupelResult::Table(labels) = &quad.result else {
+                unreachable!();
+            };
+            Some(labels[usize::try_from(index).unwrap()].clone())
+        }
         QuadrupelOp::Param => {
             // Add a parameter to the argument list for a function call.
             // Parse the argument and add it to the list of arguments.
@@ -322,12 +393,19 @@ pub fn eval_quad<'a>(
             // Call a function with the provided arguments.
             // Parse the function name and prepare to call it.
             let fun = parse_fun(&quad.arg1);
-            eval_function(&fun, args, env);
+            // The variable receiving the value of a function, if it is used.
+            let target = matches!(quad.result, QuadrupelResult::Var(_))
+                .then(|| env.get(&parse_result(&quad.result)).unwrap());
+            let value = eval_function(&fun, args, env);
             // Clear the arguments after the function call.
             args.clear();
+            if let Some(target) = target {
+                *target.borrow_mut() = value.unwrap();
+            }
             None
         }
-        QuadrupelOp::Default => None,
+        // The value of `Return` is taken by `eval_function`, the jump to the end follows.
+        QuadrupelOp::Return | QuadrupelOp::Default => None,
     }
 }
 
@@ -369,7 +447,7 @@ pub fn parse_fun(arg: &QuadrupelArg) -> String {
 pub fn parse_result(res: &QuadrupelResult) -> String {
     match &res {
         QuadrupelResult::Var(quadrupel_var) => quadrupel_var.to_identifier(),
-        QuadrupelResult::Label(l) => l.to_string(),
-        QuadrupelResult::Empty => unreachable!(),
+        QuadrupelResult::Label(l) => l.clone(),
+        QuadrupelResult::Table(_) | QuadrupelResult::Empty => unreachable!(),
     }
 }
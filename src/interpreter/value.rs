@@ -3,7 +3,7 @@ use std::{
     cmp::Ordering,
     collections::LinkedList,
     fmt::Debug,
-    ops::{Add, Div, Mul, Neg, Sub},
+    ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub},
     rc::Rc,
 };
 
@@ -20,12 +20,16 @@ use crate::{
 // A reference to a value in the interpreter, allowing for shared ownership and mutable access.
 pub type ValueRef<'a> = Rc<RefCell<Value<'a>>>;
 
-// Represents a value in the interpreter, which can be an integer, boolean, array, or function.
+// Represents a value in the interpreter, which can be an integer, boolean, array, record, or function.
 #[derive(Clone, Debug)]
 pub enum Value<'a> {
     Int(i32),
     Bool(bool),
+    // Only string literals, which are passed to builtins
+    String(Rc<str>),
     Array(Vec<ValueRef<'a>>),
+    // The values of the fields in the order of the record type
+    Record(Vec<ValueRef<'a>>),
     Function(ValueFunction<'a>),
 }
 
@@ -33,13 +37,13 @@ pub enum Value<'a> {
 #[derive(Clone, Debug)]
 pub enum ValueFunction<'a> {
     #[expect(clippy::linkedlist)]
-    Spl(ProcedureEntry, &'a LinkedList<Statement>),
-    Tac(ProcedureEntry, &'a BlockGraph),
-    BuiltIn(ProcedureEntry, BuiltInProc),
+    Spl(Rc<ProcedureEntry>, &'a LinkedList<Statement>),
+    Tac(Rc<ProcedureEntry>, &'a BlockGraph),
+    BuiltIn(Rc<ProcedureEntry>, BuiltInProc),
 }
 impl ValueFunction<'_> {
     // Returns the procedure entry associated with the function, regardless of its type.
-    pub const fn entry(&self) -> &ProcedureEntry {
+    pub fn entry(&self) -> &ProcedureEntry {
         match self {
             ValueFunction::Tac(proc, _)
             | ValueFunction::Spl(proc, _)
@@ -80,21 +84,21 @@ impl Debug for BuiltInProc {
 
 impl Value<'_> {
     // Flattens an array value by recursively flattening any nested arrays within it. We need this to ensure that arrays can be treated as flat arrays of values in the TAC interpreter.
+    // Records become flat arrays of their fields, booleans become 0 or 1, like in the three address code.
     pub fn flatten_value(&self) -> Self {
-        if let Value::Array(ref_cells) = self {
-            let mut arr = vec![];
-            for v in ref_cells {
-                if let Value::Array(a) = v.borrow().flatten_value() {
-                    for x in a {
-                        arr.push(x);
+        match self {
+            Value::Array(ref_cells) | Value::Record(ref_cells) => {
+                let mut arr = vec![];
+                for v in ref_cells {
+                    match v.borrow().flatten_value() {
+                        Value::Array(a) => arr.extend(a),
+                        value => arr.push(Value::new_refcell(value)),
                     }
-                } else {
-                    arr.push(v.clone());
                 }
+                Value::Array(arr)
             }
-            Value::Array(arr)
-        } else {
-            self.clone()
+            Value::Bool(b) => Value::Int(i32::from(*b)),
+            _ => self.clone(),
         }
     }
     // Creates a new ValueRef containing a RefCell with the given value.
@@ -108,7 +112,7 @@ impl Value<'_> {
         f: impl Fn(&[ValueRef<'_>]) + 'static,
     ) -> Self {
         Value::Function(ValueFunction::BuiltIn(
-            ProcedureEntry {
+            Rc::new(ProcedureEntry {
                 local_table: SymbolTable::new(),
                 parameters: params
                     .map(|(name, is_reference)| Parameter {
@@ -117,7 +121,10 @@ impl Value<'_> {
                         is_reference,
                     })
                     .collect(),
-            },
+                return_type: None,
+                side_effects: None,
+                is_eliminated: false,
+            }),
             BuiltInProc {
                 implementation: Rc::new(f),
             },
@@ -182,7 +189,108 @@ impl Div for Value<'_> {
             unreachable!("{rhs:?} is not Int");
         };
 
-        Self::Int(i / j)
+        // Runtime errors of the SPL program, `i32::MIN / -1` overflows
+        match i.checked_div(j) {
+            Some(quotient) => Self::Int(quotient),
+            None if j == 0 => panic!("attempt to divide by zero"),
+            None => panic!("attempt to divide with overflow"),
+        }
+    }
+}
+
+impl Rem for Value<'_> {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        let Self::Int(i) = self else {
+            unreachable!("{self:?} is not Int");
+        };
+        let Self::Int(j) = rhs else {
+            unreachable!("{rhs:?} is not Int");
+        };
+
+        // Runtime errors of the SPL program, `i32::MIN % -1` overflows
+        match i.checked_rem(j) {
+            Some(remainder) => Self::Int(remainder),
+            None if j == 0 => panic!("attempt to calculate the remainder with a divisor of zero"),
+            None => panic!("attempt to calculate the remainder with overflow"),
+        }
+    }
+}
+
+// Implements the bitwise operations for Value, shifts only use the lower five bits of the amount.
+impl BitAnd for Value<'_> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        let Self::Int(i) = self else {
+            unreachable!("{self:?} is not Int");
+        };
+        let Self::Int(j) = rhs else {
+            unreachable!("{rhs:?} is not Int");
+        };
+
+        Self::Int(i & j)
+    }
+}
+
+impl BitOr for Value<'_> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        let Self::Int(i) = self else {
+            unreachable!("{self:?} is not Int");
+        };
+        let Self::Int(j) = rhs else {
+            unreachable!("{rhs:?} is not Int");
+        };
+
+        Self::Int(i | j)
+    }
+}
+
+impl BitXor for Value<'_> {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        let Self::Int(i) = self else {
+            unreachable!("{self:?} is not Int");
+        };
+        let Self::Int(j) = rhs else {
+            unreachable!("{rhs:?} is not Int");
+        };
+
+        Self::Int(i ^ j)
+    }
+}
+
+impl Shl for Value<'_> {
+    type Output = Self;
+
+    fn shl(self, rhs: Self) -> Self::Output {
+        let Self::Int(i) = self else {
+            unreachable!("{self:?} is not Int");
+        };
+        let Self::Int(j) = rhs else {
+            unreachable!("{rhs:?} is not Int");
+        };
+
+        Self::Int(i << (j & 31))
+    }
+}
+
+impl Shr for Value<'_> {
+    type Output = Self;
+
+    fn shr(self, rhs: Self) -> Self::Output {
+        let Self::Int(i) = self else {
+            unreachable!("{self:?} is not Int");
+        };
+        let Self::Int(j) = rhs else {
+            unreachable!("{rhs:?} is not Int");
+        };
+
+        Self::Int(i >> (j & 31))
     }
 }
 
@@ -233,3 +341,14 @@ impl Neg for Value<'_> {
         Self::Int(-i)
     }
 }
+
+// Implements the Not trait for Value, allowing negation of boolean values.
+impl Not for Value<'_> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        let Self::Bool(b) = self else { unreachable!() };
+
+        Self::Bool(!b)
+    }
+}
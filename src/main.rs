@@ -1,37 +1,68 @@
+use std::process;
+
 use cli::process_matches;
 
 mod absyn; // Abstract Syntax Tree structures
 mod base_blocks; // Control flow graph and basic block handling
 mod cli; // CLI parsing and argument handling
 mod code_gen; // Code generation (e.g. TAC)
+mod diagnostics; // Source spans and rustc-style error messages
+mod formatter; // Pretty-printer for SPL source code
 mod interpreter;
 mod optimizations; // Compiler optimizations
 mod parser; // SPL parser implementation
 mod semant; // Semantic checks and symbol table generation
 mod spl_builtins;
 mod table; // Symbol table and entry types
+mod warnings; // Lints reported as compiler warnings
 
-fn main() -> anyhow::Result<()> {
+fn main() {
     // Entry point: parse CLI arguments and start processing
-    process_matches(&cli::load_program_data().get_matches())
+    if let Err(err) = process_matches(&cli::load_program_data().get_matches()) {
+        // Same output as returning the error from `main`, but with the exit code of the error
+        eprintln!("Error: {err:?}");
+        process::exit(cli::exit_code(&err));
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use bitvec::vec::BitVec;
     use rstest::rstest;
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::fmt::Write as _;
     use std::fs;
     use std::path::{Path, PathBuf};
+    use std::rc::Rc;
 
     // Import necessary modules for testing the full compilation pipeline
-    use crate::base_blocks::BlockGraph;
+    use crate::absyn::absyn::Program;
+    use crate::base_blocks::{BlockContent, BlockGraph};
+    use crate::cli;
     use crate::code_gen::Tac;
+    use crate::code_gen::quadrupel::{
+        Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar, quad,
+    };
+    use crate::diagnostics::{Diagnostic, Source, SourceMap};
+    use crate::formatter::format_source;
+    use crate::optimizations::array_bounds::{BoundsCheck, Safety};
+    use crate::optimizations::call_graph::CallGraph;
     use crate::optimizations::constant_propagation::ConstantPropagation;
     use crate::optimizations::live_variables::LiveVariables;
+    use crate::optimizations::pass_manager::Pipeline;
+    use crate::optimizations::range_analysis::{Interval, RangeAnalysis};
     use crate::optimizations::reaching_expressions::ReachingDefinitions;
+    use crate::optimizations::uninitialized_variables::{Uninitialized, UninitializedVariables};
     use crate::optimizations::worklist::Worklist;
+    use crate::parser::lexer::tokenize;
+    use crate::parser::loader::load;
     use crate::parser::parse_everything_else::parse;
-    use crate::semant::{build_symbol_table::build_symbol_table, check_def_global};
+    use crate::parser::tokens::Tokens;
+    use crate::semant::{SemanticErrorKind, check_program};
     use crate::table::entry::Entry;
+    use crate::table::symbol_table::SymbolTable;
+    use crate::warnings::{self, Level, Lint, LintLevels};
 
     #[rstest]
     fn optimizations(
@@ -51,25 +82,710 @@ mod test {
 
     #[rstest]
     fn syntax_errors(#[files("spl-testfiles/syntax_errors/*.spl")] path: PathBuf) {
-        let code = fs::read_to_string(path).unwrap();
+        let code = fs::read_to_string(&path).unwrap();
+        // Every error is listed as `line:col: message` next to the test file
+        let expected = fs::read_to_string(path.with_extension("expected")).unwrap();
+
         // Parsing should fail (on purpose)
-        parse(&code).expect_err("Parsing should fail");
+        let errors = parse(&code).expect_err("Parsing should fail");
+        let found = errors.0.iter().fold(String::new(), |mut out, err| {
+            let _ = writeln!(out, "{}:{}: {err}", err.span.line, err.span.col);
+            out
+        });
+        assert_eq!(found, expected);
+    }
+
+    #[rstest]
+    fn formatter(#[files("spl-testfiles/formatter/*.spl")] path: PathBuf) {
+        let code = fs::read_to_string(&path).unwrap();
+        let expected = fs::read_to_string(path.with_extension("expected")).unwrap();
+
+        assert_eq!(format_source(&code).unwrap(), expected);
+        assert_eq!(format_source(&expected).unwrap(), expected);
+    }
+
+    #[rstest]
+    fn formatter_round_trip(
+        #[files("spl-testfiles/*/*.spl")]
+        #[exclude("errors")]
+        #[exclude("reftest.spl")]
+        path: PathBuf,
+    ) {
+        let code = fs::read_to_string(&path).unwrap();
+        let formatted = format_source(&code).unwrap();
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+
+        // Formatting must not change the meaning of the program
+        let tac = |code: String| {
+            let name = path.to_string_lossy();
+            let mut absyn = load(&name, code.leak(), &mut SourceMap::default()).unwrap();
+            let table = check_program(&mut absyn).unwrap();
+            let mut address_code = Tac::new(table);
+            address_code.code_generation(&absyn);
+            address_code
+                .proc_table
+                .into_iter()
+                .map(|(name, code)| {
+                    let code = code.iter().map(ToString::to_string).collect::<Vec<_>>();
+                    (name, code)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        assert_eq!(tac(formatted), tac(code));
+    }
+
+    #[rstest]
+    fn semantic_errors(#[files("spl-testfiles/semantic_errors/*.spl")] path: PathBuf) {
+        let code = fs::read_to_string(&path).unwrap();
+        // Every error is listed as `line:col: message` next to the test file
+        let expected = fs::read_to_string(path.with_extension("expected")).unwrap();
+
+        let mut absyn = parse(&code).unwrap();
+        let errors = check_program(&mut absyn).expect_err("Semantic analysis should fail");
+        // Errors without a position, like a missing `main`, are listed without one
+        let found = errors.0.iter().fold(String::new(), |mut out, err| {
+            let _ = match err.span {
+                Some(span) => writeln!(out, "{}:{}: {err}", span.line, span.col),
+                None => writeln!(out, "{err}"),
+            };
+            out
+        });
+        assert_eq!(found, expected);
+    }
+
+    #[rstest]
+    #[case("proc main() {\n  var x: int;\n  x := 1 + ;\n}\n", (3, 12), "found `;`")]
+    #[case("proc main() {\n  var x: int;\n  x := 1\n", (4, 1), "found end of file")]
+    #[case("proc main() {\n  case 1 of\n  end\n}\n", (3, 3), "found `end`")]
+    fn syntax_error_spans(
+        #[case] code: &'static str,
+        #[case] (line, col): (usize, usize),
+        #[case] message: &str,
+    ) {
+        let errors = parse(code).expect_err("Parsing should fail");
+        let [err] = errors.0.as_slice() else {
+            panic!("expected a single error: {errors}");
+        };
+        assert_eq!((err.span.line, err.span.col), (line, col));
+        assert!(err.to_string().contains(message));
+    }
+
+    #[rstest]
+    #[case(
+        "cycle.spl",
+        "cycle_b.spl:1:1: cyclic import of `spl-testfiles/import_errors/cycle.spl`"
+    )]
+    #[case(
+        "missing.spl",
+        "missing.spl:1:1: cannot import `spl-testfiles/import_errors/nothing.spl`: No such file or directory (os error 2)"
+    )]
+    // Errors in imported files are shown with their own position
+    #[case("syntax.spl", "broken.spl:3:1: expected `;`, found `}`")]
+    fn import_errors(#[case] file: &str, #[case] expected: &str) {
+        let path = Path::new("spl-testfiles/import_errors").join(file);
+        let code = fs::read_to_string(&path).unwrap();
+        let mut sources = SourceMap::default();
+        let errors = load(&path.to_string_lossy(), code.leak(), &mut sources)
+            .expect_err("Loading should fail");
+        let [err] = errors.0.as_slice() else {
+            panic!("expected a single error: {errors}");
+        };
+        assert_eq!(err.exit_code(), 100);
+
+        let (source, span) = sources.lookup(err.span);
+        let name = Path::new(&source.name)
+            .file_name()
+            .unwrap()
+            .to_string_lossy();
+        assert_eq!(
+            format!("{name}:{}:{}: {err}", span.line, span.col),
+            expected
+        );
+    }
+
+    #[test]
+    fn import_redeclaration() -> anyhow::Result<()> {
+        let path = "spl-testfiles/import_errors/duplicate.spl";
+        let code = fs::read_to_string(path)?;
+        let mut sources = SourceMap::default();
+        let mut absyn = load(path, code.leak(), &mut sources)?;
+        let errors = check_program(&mut absyn).expect_err("`show` is imported");
+        let [err] = errors.0.as_slice() else {
+            panic!("expected a single error: {errors}");
+        };
+        assert_eq!(errors.exit_code(), 105);
+
+        // The redeclaration is in the importing file, after the imported definitions
+        let (source, span) = sources.lookup(err.span.unwrap());
+        assert_eq!(source.name, path);
+        assert_eq!((span.line, span.col), (3, 1));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("42", Tokens::Intlit(42))]
+    #[case("0x1F", Tokens::Intlit(31))]
+    #[case("0xFFFFFFFF", Tokens::Intlit(-1))]
+    #[case("'a'", Tokens::Intlit(97))]
+    #[case("'\\n'", Tokens::Intlit(10))]
+    #[case("while", Tokens::While)]
+    #[case("whilst", Tokens::Ident("whilst".to_string()))]
+    #[case("until", Tokens::Until)]
+    #[case("const", Tokens::Const)]
+    #[case("record", Tokens::Record)]
+    #[case("end", Tokens::End)]
+    #[case("case", Tokens::Case)]
+    #[case(".", Tokens::Dot)]
+    #[case("import", Tokens::Import)]
+    #[case(r#""a\tb\n""#, Tokens::Strlit("a\tb\n".to_string()))]
+    #[case(r#""\"\\""#, Tokens::Strlit("\"\\".to_string()))]
+    #[case("\"\"", Tokens::Strlit(String::new()))]
+    #[case("%", Tokens::Percent)]
+    #[case("&", Tokens::Amp)]
+    #[case("||", Tokens::Or)]
+    #[case("^", Tokens::Caret)]
+    #[case("<<", Tokens::Shl)]
+    #[case(">>", Tokens::Shr)]
+    #[case("\"lib/io.spl\"", Tokens::Strlit("lib/io.spl".to_string()))]
+    #[case(":=", Tokens::Asgn)]
+    #[case("<=", Tokens::Le)]
+    fn tokens(#[case] code: &str, #[case] expected: Tokens) {
+        let (tokens, errors) = tokenize(code);
+        assert!(errors.is_empty());
+        let kinds = tokens.into_iter().map(|t| t.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, [expected, Tokens::Eof]);
+    }
+
+    #[test]
+    fn token_stream() {
+        let (tokens, _) = tokenize("proc main() {\n  x := 'x'; // comment\n}\n");
+        let lines = tokens.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            lines[5..],
+            [
+                "TOKEN = IDENT, name = x, line = 2",
+                "TOKEN = ASGN, line = 2",
+                "TOKEN = INTLIT, value = 120, line = 2",
+                "TOKEN = SEMIC, line = 2",
+                "TOKEN = RCURL, line = 3",
+                "TOKEN = -- EOF --, line = 4",
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("proc main() { x := 1; }", 120)]
+    #[case("proc main() { var a: array [2] of int; a := 1; }", 108)]
+    #[case("proc main() { var a: array [2] of int; a := a; }", 109)]
+    #[case("proc main() { var x: int; x := 1 + (1 < 2); }", 117)]
+    #[case("proc main() { if ((1 < 2) < (1 < 2)) ; }", 118)]
+    #[case("proc main() { var x: int; x := -(1 < 2); }", 119)]
+    #[case("proc main() { printi(1, 2); }", 116)]
+    #[case("proc main() { if (1 && 2) ; }", 128)]
+    #[case("proc main() { while (!1) ; }", 128)]
+    #[case("proc main() { var b: bool; b := 1; }", 108)]
+    #[case("proc main() { var b: array [2] of bool; b := b; }", 109)]
+    #[case("proc main() { printi(true); }", 114)]
+    #[case("proc main() { var b: bool; b := -false; }", 119)]
+    #[case("proc p() {} type p = int;", 103)]
+    #[case("proc p(a: array [2] of int) {} proc main() {}", 104)]
+    #[case("proc p(a: int, a: int) {} proc main() {}", 106)]
+    #[case("proc p() {}", 125)]
+    #[case("type main = int;", 126)]
+    #[case("proc main(x: int) {}", 127)]
+    #[case("proc main(): int { return 0; }", 126)]
+    #[case("proc f(): array [2] of int { return 0; } proc main() {}", 129)]
+    #[case("proc main() { return 1; }", 130)]
+    #[case("proc f(): int { return; } proc main() {}", 131)]
+    #[case("proc f(): int { return true; } proc main() {}", 132)]
+    #[case("proc f(): int { while (true) return 1; } proc main() {}", 133)]
+    #[case("proc f() {} proc main() { var x: int; x := f(); }", 134)]
+    #[case("proc main() { repeat ; until (1); }", 135)]
+    #[case("proc main() { var b: bool; for b := 1 to 2 do ; }", 136)]
+    #[case("proc main() { var i: int; for i := 1 to true do ; }", 136)]
+    #[case("proc main() { var i: int; for i := 1 to 2 step 0 do ; }", 137)]
+    #[case("proc main() { break; }", 138)]
+    #[case("proc f() { continue; } proc main() { while (true) f(); }", 139)]
+    #[case("const N = 1; proc main() { N := 2; }", 121)]
+    #[case("const N = 1; proc main() { readi(N); }", 115)]
+    #[case("const N = 1; const N = 2; proc main() {}", 140)]
+    #[case("proc N() {} const N = 1; proc main() {}", 140)]
+    #[case("const N = M; proc main() {}", 120)]
+    #[case("const N = f(); proc f(): int { return 1; } proc main() {}", 141)]
+    #[case("proc main() { var n: int; var a: array [n] of int; }", 141)]
+    #[case("const N = 1 / (1 - 1); proc main() {}", 142)]
+    #[case("const N = 2147483647 + 1; proc main() {}", 143)]
+    #[case("const N = 1 % 0; proc main() {}", 142)]
+    #[case("const N = (-2147483647 - 1) % -1; proc main() {}", 143)]
+    #[case("proc main() { var b: bool; b := true & false; }", 119)]
+    #[case("proc main() { prints(1); }", 114)]
+    #[case("proc main() { printc(\"a\"); }", 114)]
+    #[case("proc main() { var x: int; x := \"a\"; }", 108)]
+    #[case("proc main() { var s: string; }", 101)]
+    #[case("const S = \"a\"; proc main() {}", 141)]
+    #[case("type t = array [-1] of int; proc main() {}", 144)]
+    #[case("type t = array [1 < 2] of int; proc main() {}", 144)]
+    #[case("const N = 1 + true; proc main() { var a: array [N] of int; }", 117)]
+    #[case("type r = record x: int; end; proc main() { var a: r; a := a; }", 109)]
+    #[case("type r = record x: int; end; proc f(a: r) {} proc main() {}", 104)]
+    #[case(
+        "type r = record x: int; end; proc f(): r { return 0; } proc main() {}",
+        129
+    )]
+    #[case(
+        "type r = record x: int; y: bool; end; proc main() { var a: r; a.y := 1; }",
+        108
+    )]
+    #[case("type r = record x: int; x: bool; end; proc main() {}", 145)]
+    #[case("proc main() { var a: record x: int; end; a.y := 1; }", 146)]
+    #[case("proc main() { var a: array [2] of int; a.x := 1; }", 147)]
+    #[case("proc main() { var a: record x: int; end; a[0] := 1; }", 122)]
+    #[case("proc main() { var a: record x: undefined; end; a.x := 1; }", 101)]
+    #[case("proc main() { case true of 1: ; end }", 148)]
+    #[case("proc main() { case 1 of true: ; end }", 149)]
+    #[case("proc main() { case 1 of 1, 2: ; 2: ; end }", 150)]
+    #[case("const N = 2; proc main() { case 1 of N: ; 1 + 1: ; end }", 150)]
+    #[case("proc main() { var x: int; case 1 of x: ; end }", 141)]
+    #[case("proc main() { case 1 of 1: break; end }", 138)]
+    #[case(
+        "proc f(x: int): int { case x of 1: return 1; end } proc main() {}",
+        133
+    )]
+    // The first error decides the exit code
+    #[case("proc main() { x := y; }\nproc main() {}", 120)]
+    fn semantic_exit_codes(#[case] code: &str, #[case] exit_code: i32) {
+        let mut absyn = parse(code).unwrap();
+        let errors = check_program(&mut absyn).expect_err("Semantic analysis should fail");
+        assert_eq!(errors.exit_code(), exit_code, "{errors}");
+    }
+
+    #[test]
+    fn semantic_error_diagnostic() -> anyhow::Result<()> {
+        let code = "proc main() {\n  var x: int;\n\tx := y + 1;\n}\n";
+        let mut absyn = parse(code)?;
+        let errors = check_program(&mut absyn).expect_err("`y` is not defined");
+        let [err] = errors.0.as_slice() else {
+            panic!("expected a single error: {errors}");
+        };
+        assert_eq!(
+            err.kind,
+            SemanticErrorKind::UndefinedVariable("y".to_string())
+        );
+
+        let source = Source {
+            name: "test.spl".to_string(),
+            text: code,
+        };
+        let rendered = source.render(&Diagnostic::error(err.to_string()).with_span(err.span));
+        assert!(rendered.contains("test.spl:3:7"));
+        // Tabs are shown as four spaces
+        assert!(rendered.contains("    x := y + 1;"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(quad!((%), (x()), 1 => res()), QuadrupelOp::Assign, QuadrupelArg::Const(0))]
+    #[case(quad!((&), (x()), 0 => res()), QuadrupelOp::Assign, QuadrupelArg::Const(0))]
+    #[case(quad!((&), 0, (x()) => res()), QuadrupelOp::Assign, QuadrupelArg::Const(0))]
+    #[case(quad!((^), (x()), (x()) => res()), QuadrupelOp::Assign, QuadrupelArg::Const(0))]
+    #[case(quad!((<<), (x()), 0 => res()), QuadrupelOp::Assign, x())]
+    #[case(quad!((>>), (x()), 0 => res()), QuadrupelOp::Assign, x())]
+    #[case(quad!((|), 0, (x()) => res()), QuadrupelOp::Assign, x())]
+    #[case(quad!((%), (=(-7)), 3 => res()), QuadrupelOp::Assign, QuadrupelArg::Const(-1))]
+    #[case(quad!((<<), 1, 33 => res()), QuadrupelOp::Assign, QuadrupelArg::Const(2))]
+    #[case(quad!((>>), (=(-16)), 2 => res()), QuadrupelOp::Assign, QuadrupelArg::Const(-4))]
+    // A division by zero is left to the runtime
+    #[case(quad!((%), 7, 0 => res()), QuadrupelOp::Mod, QuadrupelArg::Const(7))]
+    fn identities(#[case] quad: Quadrupel, #[case] op: QuadrupelOp, #[case] arg1: QuadrupelArg) {
+        let simplified = quad.simplify().unwrap();
+        assert_eq!((simplified.op, simplified.arg1), (op, arg1));
+    }
+
+    fn x() -> QuadrupelArg {
+        QuadrupelArg::Var(QuadrupelVar::Spl("x".to_string()))
+    }
+
+    fn res() -> QuadrupelResult {
+        QuadrupelResult::Var(QuadrupelVar::Spl("r".to_string()))
+    }
+
+    #[test]
+    fn pipeline_levels() -> anyhow::Result<()> {
+        let steps = |pipeline: Pipeline| {
+            pipeline
+                .steps
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(Pipeline::level("0")?.steps.is_empty());
+        assert_eq!(Pipeline::level("1")?.program_passes[0].name, "dpe");
+        assert_eq!(steps(Pipeline::level("1")?), ["cse", "cf", "dead", "bce"]);
+        assert_eq!(
+            steps(Pipeline::parse(["2", "dot"])?),
+            ["cse", "cf:dead+", "licm+", "cse", "cf:dead+", "bce", "dot"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn pipeline_unknown_pass() {
+        let err = Pipeline::parse(["cse", "foo"]).expect_err("`foo` is not a pass");
+        assert!(err.to_string().contains("foo"));
+    }
+
+    #[test]
+    fn loop_nesting_after_licm() -> anyhow::Result<()> {
+        let code = "proc main() { var i: int; var j: int; var x: int; \
+            while (i < 2) { j := 0; while (j < 2) { x := 5 * 3; j := j + 1; } i := i + 1; } \
+            while (x < 100) x := x + 1; printi(x); }";
+        let program = compile(code)?;
+        let Some(Entry::ProcedureEntry(proc_entry)) = program.table.borrow().lookup("main") else {
+            unreachable!()
+        };
+
+        // The preheader of the inner loop is appended to the end of the graph
+        let mut bg = BlockGraph::from_tac(&program.tac.proc_table["main"]);
+        assert!(bg.loop_optimization(&proc_entry.local_table));
+
+        // Loops are only nested in the loops that contain them
+        let sccs = bg.tarjan();
+        assert_eq!(sccs.len(), 3);
+        for scc in sccs {
+            if let Some(parent) = scc.parent_idx {
+                assert!(scc.nodes.iter().all(|n| sccs[parent].nodes.contains(n)));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn call_graph() -> anyhow::Result<()> {
+        let Compiled {
+            table,
+            mut tac,
+            call_graph,
+            ..
+        } = compile_file("runtime_tests/bigtest.spl")?;
+
+        assert!(call_graph.is_recursive("callRecursive"));
+        assert!(!call_graph.is_recursive("check"));
+        assert_eq!(call_graph.unreachable_from("main"), ["hide"]);
+
+        // `callbyval` only writes its value parameters
+        assert!(call_graph.summaries["callbyval"].modified.not_any());
+        assert!(call_graph.summaries["callbyref"].modified.all());
+        assert!(call_graph.summaries["error"].performs_io);
+        assert!(!call_graph.summaries["callArray"].performs_io);
+
+        let removed = tac.dead_procedure_elimination(&call_graph, &mut table.borrow_mut());
+        assert_eq!(removed, [("hide".to_string(), 1)]);
+        assert!(!tac.proc_table.contains_key("hide"));
+        assert!(matches!(
+            table.borrow().lookup("hide"),
+            Some(Entry::ProcedureEntry(entry)) if entry.is_eliminated
+        ));
+        assert!(
+            cli::show_call_graph(&call_graph, &table.borrow()).ends_with(">>> Eliminated:\nhide")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn call_graph_summaries() -> anyhow::Result<()> {
+        let code = "type arr = array [2] of int; \
+            proc init(ref x: int) { x := 0; } \
+            proc inc(ref x: int) { x := x + 1; } \
+            proc maybe(ref x: int, c: int) { if (c = 0) x := 1; printi(x); } \
+            proc viaCall(ref x: int) { init(x); inc(x); } \
+            proc elem(ref a: arr) { a[0] := 1; } \
+            proc main() { var x: int; var a: arr; init(x); inc(x); maybe(x, 0); viaCall(x); elem(a); }";
+        let summaries = compile(code)?.call_graph.summaries;
+        let bits = |bits: &BitVec| bits.iter().map(|b| *b).collect::<Vec<_>>();
+
+        // Only a read before the assignment sees the value of the caller
+        assert_eq!(bits(&summaries["init"].assigned), [true]);
+        assert_eq!(bits(&summaries["init"].referenced), [false]);
+        assert_eq!(bits(&summaries["inc"].referenced), [true]);
+
+        // `x` is not assigned on every path
+        assert_eq!(bits(&summaries["maybe"].assigned), [false, false]);
+        assert_eq!(bits(&summaries["maybe"].referenced), [true, true]);
+
+        // `init` assigns `x` before `inc` reads it
+        assert_eq!(bits(&summaries["viaCall"].assigned), [true]);
+        assert_eq!(bits(&summaries["viaCall"].referenced), [false]);
+
+        // Storing an element modifies the array, but does not assign all of it
+        assert_eq!(bits(&summaries["elem"].modified), [true]);
+        assert_eq!(bits(&summaries["elem"].assigned), [false]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("runtime_tests/test8.spl", (3, 1, 0))]
+    #[case("runtime_tests/test9.spl", (3, 1, 0))]
+    #[case("optimizations/ranges.spl", (1, 0, 0))]
+    #[case("runtime_tests/queens.spl", (7, 0, 8))]
+    #[case("optimizations/nested_bounds.spl", (5, 1, 0))]
+    fn bounds_check(
+        #[case] path: &str,
+        #[case] expected: (usize, usize, usize),
+    ) -> anyhow::Result<()> {
+        let program = compile_file(path)?;
+
+        let accesses = BoundsCheck::check_program(&program.tac, &program.table)
+            .into_iter()
+            .flat_map(|(_, check)| check.accesses)
+            .collect::<Vec<_>>();
+        let count = |safety| accesses.iter().filter(|a| a.safety == safety).count();
+        assert_eq!(
+            (
+                count(Safety::Safe),
+                count(Safety::Unsafe),
+                count(Safety::Unknown)
+            ),
+            expected
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("runtime_tests/test9.spl", (4, 1))]
+    #[case("optimizations/ranges.spl", (1, 0))]
+    #[case("runtime_tests/twodim.spl", (6, 2))]
+    #[case("optimizations/wraparound.spl", (1, 1))]
+    fn bounds_check_elimination(
+        #[case] path: &str,
+        #[case] expected: (usize, usize),
+    ) -> anyhow::Result<()> {
+        let program = compile_file(path)?;
+
+        let mut bg = BlockGraph::from_tac(&program.tac.proc_table["main"]);
+        let Some(Entry::ProcedureEntry(proc_entry)) = program.table.borrow().lookup("main") else {
+            unreachable!()
+        };
+        let before = bg.bounds_checks();
+        let ranges = RangeAnalysis::run(&bg, &proc_entry.local_table);
+        let removed = bg.bounds_check_elimination(&ranges);
+
+        assert_eq!((before, bg.bounds_checks()), expected);
+        assert_eq!(removed, before - bg.bounds_checks());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("optimizations/uninitialized.spl", "main", &[("a", Uninitialized::Always), ("a", Uninitialized::Always), ("b", Uninitialized::Maybe), ("d", Uninitialized::Maybe)])]
+    #[case("runtime_tests/licm.spl", "p", &[("j", Uninitialized::Maybe), ("j", Uninitialized::Maybe), ("i", Uninitialized::Maybe)])]
+    #[case("runtime_tests/fak.spl", "main", &[])]
+    fn uninitialized_variables(
+        #[case] path: &str,
+        #[case] proc_name: &str,
+        #[case] expected: &[(&str, Uninitialized)],
+    ) -> anyhow::Result<()> {
+        let Compiled { table, tac, .. } = compile_file(path)?;
+
+        let uses = UninitializedVariables::check_program(&tac, &table)
+            .into_iter()
+            .find(|(name, _)| name == proc_name)
+            .unwrap()
+            .1
+            .uses
+            .into_iter()
+            .map(|u| (u.var, u.kind))
+            .collect::<Vec<_>>();
+        let expected = expected
+            .iter()
+            .map(|(var, kind)| ((*var).to_string(), *kind))
+            .collect::<Vec<_>>();
+        assert_eq!(uses, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("optimizations/warnings.spl", &[("main", Lint::UnusedVariable), ("main", Lint::UnusedVariable), ("main", Lint::DeadStore), ("main", Lint::ConstantCondition), ("main", Lint::UnreachableCode), ("never", Lint::UnusedProcedure), ("output", Lint::UnusedParameter)])]
+    #[case("runtime_tests/test8.spl", &[("main", Lint::UnusedVariable), ("main", Lint::OutOfBounds)])]
+    #[case("optimizations/nested_bounds.spl", &[("main", Lint::OutOfBounds)])]
+    #[case("runtime_tests/fak.spl", &[])]
+    fn lints(#[case] path: &str, #[case] expected: &[(&str, Lint)]) -> anyhow::Result<()> {
+        let Compiled {
+            absyn,
+            table,
+            tac,
+            call_graph,
+        } = compile_file(path)?;
+
+        let warnings = warnings::check_program(&absyn, &tac, &table, &call_graph)
+            .into_iter()
+            .map(|w| (w.proc_name, w.lint))
+            .collect::<Vec<_>>();
+        let expected = expected
+            .iter()
+            .map(|(proc_name, lint)| ((*proc_name).to_string(), *lint))
+            .collect::<Vec<_>>();
+        assert_eq!(warnings, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn constant_condition_messages() -> anyhow::Result<()> {
+        // Short-circuit code also jumps if a condition holds
+        let code = "proc main() { var b: bool; var x: int; x := 0; \
+            if (x < 1 || 2 < 1) b := 1 < 2; if (!(1 = 1)) ; }";
+        let mut absyn = parse(code)?;
+        let table = check_program(&mut absyn)?;
+        let mut address_code = Tac::new(table.clone());
+        address_code.code_generation(&absyn);
+        let call_graph = CallGraph::new(&address_code.proc_table, &table.borrow());
+        call_graph.annotate(&mut table.borrow_mut());
+
+        let mut messages = warnings::check_program(&absyn, &address_code, &table, &call_graph)
+            .into_iter()
+            .filter(|w| w.lint == Lint::ConstantCondition)
+            .map(|w| {
+                let span = w.span.unwrap();
+                format!("{}: {}", &code[span.start..span.end], w.message)
+            })
+            .collect::<Vec<_>>();
+        messages.sort();
+        assert_eq!(
+            messages,
+            [
+                "1 < 2: condition is always true",
+                "1 = 1: condition is always true",
+                "2 < 1: condition is always false",
+                "x < 1: condition is always true",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn lint_levels() -> anyhow::Result<()> {
+        let levels = |args: &[&str]| {
+            let matches = cli::load_program_data()
+                .try_get_matches_from([&["spl", "test.spl"], args].concat())?;
+            LintLevels::from_matches(&matches)
+        };
+
+        assert_eq!(levels(&[])?.level(Lint::DeadStore), Level::Warn);
+
+        // Later flags override earlier ones
+        let custom = levels(&["-A", "all", "-W", "dead-store,W0006", "--deny-warnings"])?;
+        assert_eq!(custom.level(Lint::DeadStore), Level::Deny);
+        assert_eq!(custom.level(Lint::UnusedProcedure), Level::Deny);
+        assert_eq!(custom.level(Lint::UnusedVariable), Level::Allow);
+        assert_eq!(
+            levels(&["-W", "all", "-A", "out-of-bounds"])?.level(Lint::OutOfBounds),
+            Level::Allow
+        );
+
+        levels(&["-W", "foo"]).expect_err("`foo` is not a lint");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn deny_warnings(#[values("-s", "-i", "-3", "-c", "-I", "-j")] phase: &str) {
+        let matches = cli::load_program_data().get_matches_from([
+            "spl",
+            "spl-testfiles/optimizations/warnings.spl",
+            phase,
+            "--deny-warnings",
+        ]);
+        cli::process_matches(&matches).expect_err("the warnings are denied in every phase");
+    }
+
+    #[test]
+    fn range_analysis() -> anyhow::Result<()> {
+        let program = compile_file("optimizations/ranges.spl")?;
+
+        let bg = BlockGraph::from_tac(&program.tac.proc_table["main"]);
+        let Some(Entry::ProcedureEntry(proc_entry)) = program.table.borrow().lookup("main") else {
+            unreachable!()
+        };
+        let ranges = RangeAnalysis::run(&bg, &proc_entry.local_table);
+        let i = ranges
+            .vars
+            .iter()
+            .position(|v| v.to_identifier() == "i")
+            .unwrap();
+
+        let block_with = |op| {
+            bg.blocks
+                .iter()
+                .position(
+                    |b| matches!(&b.content, BlockContent::Code(q) if q.iter().any(|q| q.op == op)),
+                )
+                .unwrap()
+        };
+
+        // Inside the loop body
+        assert_eq!(
+            ranges.r#in[block_with(QuadrupelOp::ArrayStore)][i],
+            Interval::new(0, 7)
+        );
+        // After the loop
+        assert_eq!(
+            ranges.r#in[block_with(QuadrupelOp::Call)][i],
+            Interval::new(8, 8)
+        );
+
+        Ok(())
+    }
+
+    // A checked program with its three address code, the symbol table is annotated
+    // with the side-effect summaries from the call graph.
+    struct Compiled {
+        absyn: Program,
+        table: Rc<RefCell<SymbolTable>>,
+        tac: Tac,
+        call_graph: CallGraph,
+    }
+
+    // Runs the same phases as the command line before any analysis or optimization.
+    fn compile(code: &'static str) -> anyhow::Result<Compiled> {
+        let mut absyn = parse(code)?;
+        let table = check_program(&mut absyn)?;
+        let mut tac = Tac::new(table.clone());
+        tac.code_generation(&absyn);
+        let call_graph = CallGraph::new(&tac.proc_table, &table.borrow());
+        call_graph.annotate(&mut table.borrow_mut());
+
+        Ok(Compiled {
+            absyn,
+            table,
+            tac,
+            call_graph,
+        })
+    }
+
+    fn compile_file(path: &str) -> anyhow::Result<Compiled> {
+        let code = fs::read_to_string(format!("spl-testfiles/{path}"))?;
+        compile(code.leak())
     }
 
     fn file(path: &Path) -> anyhow::Result<()> {
         let code = fs::read_to_string(path).unwrap();
 
-        // Parse into abstract syntax tree
-        let mut absyn = parse(code.leak())?;
-
-        // Build the global symbol table
-        let table = build_symbol_table(&absyn)?;
+        // Parse into abstract syntax tree, together with the imported files
+        let name = path.to_string_lossy();
+        let mut absyn = load(&name, code.leak(), &mut SourceMap::default())?;
 
-        // Perform semantic checks on all definitions
-        absyn
-            .definitions
-            .iter_mut()
-            .try_for_each(|def| check_def_global(def, &table))?;
+        // Build the global symbol table and perform semantic checks on all definitions
+        let table = check_program(&mut absyn)?;
 
         let mut address_code = Tac::new(table.clone());
         address_code.code_generation(&absyn);
@@ -77,26 +793,32 @@ mod test {
         // Ensure that a "main" function exists
         assert!(address_code.proc_table.contains_key("main"));
 
+        // Interprocedural summaries are used by the analyses below
+        let call_graph = CallGraph::new(&address_code.proc_table, &table.borrow());
+        call_graph.annotate(&mut table.borrow_mut());
+
         // Process each procedure independently
         for (proc_name, code) in &address_code.proc_table {
-            let Some(Entry::ProcedureEntry(proc_entry)) = table.borrow().lookup(proc_name) else {
+            let Some(Entry::ProcedureEntry(mut proc_entry)) = table.borrow().lookup(proc_name)
+            else {
                 unreachable!()
             };
             let mut bg = BlockGraph::from_tac(code);
 
-            bg.common_subexpression_elimination(&proc_entry.local_table);
+            bg.common_subexpression_elimination(&mut proc_entry.local_table);
 
             match table.borrow_mut().entries.get_mut(proc_name) {
                 Some(Entry::ProcedureEntry(pe)) => pe.local_table = proc_entry.local_table,
                 _ => unreachable!(),
             }
-            let Some(Entry::ProcedureEntry(proc_entry)) = table.borrow().lookup(proc_name) else {
+            let Some(Entry::ProcedureEntry(mut proc_entry)) = table.borrow().lookup(proc_name)
+            else {
                 unreachable!()
             };
 
             let mut bg = BlockGraph::from_tac(code);
 
-            bg.common_subexpression_elimination(&table.borrow());
+            bg.common_subexpression_elimination(&mut proc_entry.local_table);
 
             match table.borrow_mut().entries.get_mut(proc_name) {
                 Some(Entry::ProcedureEntry(pe)) => pe.local_table = proc_entry.local_table,
@@ -116,6 +838,9 @@ mod test {
             // Eliminate dead code based on liveness information
             bg.dead_code_elimination(&live_variables);
 
+            // Compute the ranges of all variables
+            RangeAnalysis::run(&bg, local_table);
+
             // Run constant propagation analysis
             let mut const_prop = ConstantPropagation::run(&bg, local_table);
             while { bg.constant_folding(&mut const_prop, &table.borrow()) }.is_continue() {}
@@ -16,15 +16,28 @@ impl AEBEntry {
     }
 
     pub fn cmp(&self, other: &Quadrupel) -> bool {
+        // e.g. `a + b` and `a * b` use the same arguments
+        if self.quad.op != other.op {
+            return false;
+        }
         match self.quad.op {
-            QuadrupelOp::Add | QuadrupelOp::Mul | QuadrupelOp::Neg => {
+            QuadrupelOp::Add
+            | QuadrupelOp::Mul
+            | QuadrupelOp::And
+            | QuadrupelOp::Or
+            | QuadrupelOp::Xor
+            | QuadrupelOp::Neg => {
                 (self.quad.arg1 == other.arg1 && self.quad.arg2 == other.arg2)
                     || (self.quad.arg1 == other.arg2 && self.quad.arg2 == other.arg1)
             }
-            QuadrupelOp::Sub | QuadrupelOp::Div => {
+            QuadrupelOp::Sub
+            | QuadrupelOp::Div
+            | QuadrupelOp::Mod
+            | QuadrupelOp::Shl
+            | QuadrupelOp::Shr
+            | QuadrupelOp::ArrayLoad => {
                 self.quad.arg1 == other.arg1 && self.quad.arg2 == other.arg2
             }
-            QuadrupelOp::ArrayLoad => self.quad.arg1 == other.arg1 && self.quad.arg2 == other.arg2,
             _ => false,
         }
     }
@@ -0,0 +1,190 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Display},
+    rc::Rc,
+};
+
+use colored::Colorize;
+
+use crate::{
+    base_blocks::{BlockContent, BlockGraph, BlockId},
+    code_gen::{
+        Tac,
+        quadrupel::{Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar},
+    },
+    diagnostics::Span,
+    optimizations::{
+        range_analysis::{Interval, NEG_INF, POS_INF, RangeAnalysis},
+        worklist::Worklist,
+    },
+    table::{entry::Entry, symbol_table::SymbolTable},
+};
+
+/// Result of the static bounds check of a single array access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Safety {
+    /// The index is always within the bounds of the array
+    Safe,
+    /// The index is never within the bounds of the array
+    Unsafe,
+    /// The index may or may not be within the bounds of the array
+    Unknown,
+}
+
+impl Display for Safety {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Safe => write!(f, "{}", "safe".green()),
+            Self::Unsafe => write!(f, "{}", "unsafe".red()),
+            Self::Unknown => write!(f, "{}", "unknown".yellow()),
+        }
+    }
+}
+
+/// The bounds check of a single array dimension together with the range of its index.
+#[derive(Debug, Clone)]
+pub struct ArrayAccess {
+    pub block_id: BlockId,
+    /// Position of the `BoundsCheck` quad in its block.
+    pub quad_idx: usize,
+    pub array: String,
+    /// Number of elements of the checked dimension.
+    pub len: i64,
+    /// Range of the index into the checked dimension.
+    pub index: Interval,
+    pub safety: Safety,
+    pub span: Option<Span>,
+}
+
+impl ArrayAccess {
+    /// The index range without colors, e.g. `-1` or `0..7`.
+    pub fn fmt_index(&self) -> String {
+        let bound = |b| match b {
+            NEG_INF => "-∞".to_string(),
+            POS_INF => "∞".to_string(),
+            b => b.to_string(),
+        };
+        match self.index {
+            Interval::Range(lo, hi) if lo == hi => bound(lo),
+            Interval::Range(lo, hi) => format!("{}..{}", bound(lo), bound(hi)),
+            Interval::Empty => "-".to_string(),
+        }
+    }
+}
+
+/// Classification of all reachable array accesses of a procedure.
+#[derive(Debug, Clone, Default)]
+pub struct BoundsCheck {
+    pub accesses: Vec<ArrayAccess>,
+}
+
+impl BoundsCheck {
+    /// Compares the ranges of the indices of all `BoundsCheck` quads with the sizes
+    /// of their dimensions, every dimension of an array is checked on its own.
+    pub fn new(graph: &BlockGraph, ranges: &RangeAnalysis) -> Self {
+        let mut accesses = Vec::new();
+
+        for (block_id, block) in graph.blocks.iter().enumerate() {
+            let BlockContent::Code(quads) = &block.content else {
+                continue;
+            };
+            let states = ranges.ranges_before(graph, block_id);
+
+            for (quad_idx, (quad, state)) in quads.iter().zip(&states).enumerate() {
+                // Accesses in unreachable code are never executed
+                if quad.op != QuadrupelOp::BoundsCheck || state.contains(&Interval::Empty) {
+                    continue;
+                }
+                let QuadrupelArg::Const(len) = quad.arg2 else {
+                    unreachable!()
+                };
+                let len = i64::from(len);
+
+                // The checks of all dimensions precede the access to the array
+                let Some(array) = quads[quad_idx..].iter().find_map(Self::array_access) else {
+                    continue;
+                };
+
+                let index = ranges.eval_arg(&quad.arg1, state);
+                let safety = match index {
+                    Interval::Range(lo, hi) if lo >= 0 && hi < len => Safety::Safe,
+                    Interval::Range(lo, hi) if hi < 0 || lo >= len => Safety::Unsafe,
+                    Interval::Range(..) => Safety::Unknown,
+                    Interval::Empty => continue,
+                };
+
+                accesses.push(ArrayAccess {
+                    block_id,
+                    quad_idx,
+                    array: array.to_identifier(),
+                    len,
+                    index,
+                    safety,
+                    span: quad.span,
+                });
+            }
+        }
+
+        Self { accesses }
+    }
+
+    /// The array of an array access.
+    const fn array_access(quad: &Quadrupel) -> Option<&QuadrupelVar> {
+        match (&quad.op, &quad.arg1, &quad.result) {
+            (QuadrupelOp::ArrayLoad, QuadrupelArg::Var(array), _)
+            | (QuadrupelOp::ArrayStore, _, QuadrupelResult::Var(array)) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Accesses that are out of bounds whenever they are executed.
+    pub fn unsafe_accesses(&self) -> impl Iterator<Item = &ArrayAccess> {
+        self.accesses.iter().filter(|a| a.safety == Safety::Unsafe)
+    }
+
+    /// Checks all procedures of the program, sorted by name.
+    pub fn check_program(
+        tac: &Tac,
+        symbol_table: &Rc<RefCell<SymbolTable>>,
+    ) -> Vec<(String, Self)> {
+        let mut procs = tac.proc_table.iter().collect::<Vec<_>>();
+        procs.sort_by_key(|(name, _)| *name);
+
+        procs
+            .into_iter()
+            .map(|(name, quads)| {
+                let Some(Entry::ProcedureEntry(proc_entry)) = symbol_table.borrow().lookup(name)
+                else {
+                    unreachable!()
+                };
+                let graph = BlockGraph::from_tac(quads);
+                let ranges = RangeAnalysis::run(&graph, &proc_entry.local_table);
+                let check = Self::new(&graph, &ranges);
+                (name.clone(), check)
+            })
+            .collect()
+    }
+}
+
+impl Display for BoundsCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5} {:>5} {:<10} {:>6} {:<16} Safety",
+            "Block", "Quad", "Array", "Length", "Index"
+        )?;
+        for access in &self.accesses {
+            writeln!(
+                f,
+                "{:>5} {:>5} {:<10} {:>6} {:<16} {}",
+                access.block_id,
+                access.quad_idx,
+                access.array,
+                access.len,
+                access.fmt_index(),
+                access.safety,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{
+    base_blocks::{BlockContent, BlockGraph},
+    code_gen::quadrupel::{Quadrupel, QuadrupelArg, QuadrupelOp},
+    optimizations::{
+        range_analysis::{Interval, RangeAnalysis},
+        worklist::Lattice,
+    },
+};
+
+impl BlockGraph {
+    /// Removes bounds checks that can never fail.
+    /// A check is redundant if the range of its index lies within the bounds,
+    /// or if the same index was already checked earlier in the block and not changed since.
+    /// Returns the number of removed checks.
+    pub fn bounds_check_elimination(&mut self, ranges: &RangeAnalysis) -> usize {
+        let states = (0..self.blocks.len())
+            .map(|block_id| ranges.ranges_before(self, block_id))
+            .collect::<Vec<_>>();
+        let mut removed = 0;
+
+        for (block, states) in self.blocks.iter_mut().zip(states) {
+            let BlockContent::Code(quads) = &mut block.content else {
+                continue;
+            };
+
+            // Checks that already passed in this block
+            let mut checked: Vec<Quadrupel> = Vec::new();
+
+            for (quad, state) in quads.iter_mut().zip(states) {
+                if quad.op != QuadrupelOp::BoundsCheck {
+                    // Arguments might be passed by reference and changed by the callee
+                    checked.retain(|c| {
+                        quad.result != c.arg1
+                            && !(quad.op == QuadrupelOp::Param && quad.arg1 == c.arg1)
+                    });
+                    continue;
+                }
+
+                let QuadrupelArg::Const(size) = quad.arg2 else {
+                    unreachable!()
+                };
+                let index = ranges.eval_arg(&quad.arg1, &state);
+                let in_bounds = index.meet(&Interval::new(0, i64::from(size) - 1)) == index;
+
+                if in_bounds || checked.contains(quad) {
+                    *quad = Quadrupel::EMPTY;
+                    removed += 1;
+                } else {
+                    checked.push(quad.clone());
+                }
+            }
+
+            Quadrupel::filter_empty(quads);
+        }
+
+        removed
+    }
+
+    /// Number of bounds checks in the whole graph.
+    pub fn bounds_checks(&self) -> usize {
+        self.quads()
+            .filter(|q| q.op == QuadrupelOp::BoundsCheck)
+            .count()
+    }
+}
@@ -0,0 +1,564 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{self, Display};
+use std::rc::Rc;
+
+use bitvec::vec::BitVec;
+
+use crate::base_blocks::{BlockContent, BlockGraph};
+use crate::code_gen::quadrupel::{
+    Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar,
+};
+use crate::spl_builtins::PROCEDURES;
+use crate::table::entry::{Entry, Parameter, ProcedureEntry, VariableEntry};
+use crate::table::symbol_table::SymbolTable;
+
+/// Interprocedural side effects of a procedure, as seen by its callers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SideEffects {
+    /// Parameters the procedure may write to (only possible for reference parameters).
+    pub modified: BitVec,
+    /// Parameters whose value the procedure may read before assigning them.
+    pub referenced: BitVec,
+    /// Reference parameters the procedure assigns on every path.
+    pub assigned: BitVec,
+    /// Whether the procedure (or one of its callees) calls a builtin procedure.
+    pub performs_io: bool,
+}
+
+impl SideEffects {
+    /// A procedure that neither reads nor writes its parameters and performs no I/O.
+    fn none(params: usize) -> Self {
+        Self {
+            modified: BitVec::repeat(false, params),
+            referenced: BitVec::repeat(false, params),
+            assigned: BitVec::repeat(false, params),
+            performs_io: false,
+        }
+    }
+
+    /// Worst-case assumption for a procedure without a summary.
+    pub fn worst_case(parameters: &[Parameter]) -> Self {
+        Self {
+            modified: parameters.iter().map(|p| p.is_reference).collect(),
+            referenced: BitVec::repeat(true, parameters.len()),
+            assigned: BitVec::repeat(false, parameters.len()),
+            performs_io: true,
+        }
+    }
+
+    /// Builtins write their reference parameters without reading them and all perform I/O.
+    fn builtin(parameters: &[Parameter]) -> Self {
+        Self {
+            modified: parameters.iter().map(|p| p.is_reference).collect(),
+            referenced: parameters.iter().map(|p| !p.is_reference).collect(),
+            assigned: parameters.iter().map(|p| p.is_reference).collect(),
+            performs_io: true,
+        }
+    }
+}
+
+/// How a called procedure may access the argument of a `param` quad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamAccess {
+    pub is_reference: bool,
+    /// The callee may write the argument (reference parameters only).
+    pub may_write: bool,
+    /// The callee may read the argument.
+    pub may_read: bool,
+}
+
+impl Quadrupel {
+    /// Given the index of a `param` quad, determines how the called procedure may access
+    /// the argument. Without a summary, reference arguments may be read and written.
+    pub fn find_param_access(
+        quads: &[Self],
+        quad_index_param: usize,
+        symbol_table: &SymbolTable,
+    ) -> ParamAccess {
+        let (callee, j) = Self::find_param_call(quads, quad_index_param, symbol_table);
+        let is_reference = callee.parameters[j].is_reference;
+        let effects = callee
+            .side_effects
+            .unwrap_or_else(|| Rc::new(SideEffects::worst_case(&callee.parameters)));
+
+        ParamAccess {
+            is_reference,
+            may_write: is_reference && effects.modified[j],
+            may_read: !is_reference || effects.referenced[j],
+        }
+    }
+}
+
+/// Parameters that are assigned on every path from the procedure entry to the start of each block.
+fn assigned_at_entry(graph: &BlockGraph, assigned_by_block: &[BitVec]) -> Vec<BitVec> {
+    let params = assigned_by_block.first().map_or(0, BitVec::len);
+    let preds = graph.edges_prev();
+    let mut r#in = graph
+        .blocks
+        .iter()
+        .map(|b| BitVec::repeat(!matches!(b.content, BlockContent::Start), params))
+        .collect::<Vec<_>>();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for block_id in 0..graph.blocks.len() {
+            let Some(new) = preds[block_id]
+                .iter()
+                .map(|&p| r#in[p].clone() | assigned_by_block[p].clone())
+                .reduce(|a, b| a & b)
+            else {
+                continue;
+            };
+            if new != r#in[block_id] {
+                r#in[block_id] = new;
+                changed = true;
+            }
+        }
+    }
+
+    r#in
+}
+
+/// Call graph over all procedures of a program together with their side-effect summaries.
+pub struct CallGraph {
+    /// Procedures (including builtins) called by each user-defined procedure.
+    pub callees: BTreeMap<String, BTreeSet<String>>,
+    /// Groups of mutually recursive procedures, callees before their callers.
+    pub sccs: Vec<Vec<String>>,
+    /// Side-effect summaries of all procedures, including builtins.
+    pub summaries: BTreeMap<String, SideEffects>,
+    /// Parameter names of the user-defined procedures.
+    param_names: BTreeMap<String, Vec<String>>,
+}
+
+impl CallGraph {
+    /// Builds the call graph of the given procedures and computes their summaries.
+    pub fn new(proc_table: &HashMap<String, Vec<Quadrupel>>, symbol_table: &SymbolTable) -> Self {
+        let callees = proc_table
+            .iter()
+            .map(|(name, quads)| {
+                let called = quads
+                    .iter()
+                    .filter_map(|q| match q {
+                        Quadrupel {
+                            op: QuadrupelOp::Call,
+                            arg1: QuadrupelArg::Var(QuadrupelVar::Spl(callee)),
+                            ..
+                        } => Some(callee.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                (name.clone(), called)
+            })
+            .collect();
+
+        let param_names = proc_table
+            .keys()
+            .map(|name| {
+                let Some(Entry::ProcedureEntry(entry)) = symbol_table.lookup(name) else {
+                    unreachable!()
+                };
+                (
+                    name.clone(),
+                    entry.parameters.into_iter().map(|p| p.name).collect(),
+                )
+            })
+            .collect();
+
+        let mut call_graph = Self {
+            callees,
+            param_names,
+            sccs: Vec::new(),
+            summaries: PROCEDURES
+                .iter()
+                .map(|(name, params, _)| ((*name).to_string(), SideEffects::builtin(params)))
+                .collect(),
+        };
+        call_graph.sccs = call_graph.tarjan();
+
+        // Callees are summarized before their callers, only recursive procedures need iteration.
+        for scc in call_graph.sccs.clone() {
+            for name in &scc {
+                let Some(Entry::ProcedureEntry(entry)) = symbol_table.lookup(name) else {
+                    unreachable!()
+                };
+                call_graph
+                    .summaries
+                    .insert(name.clone(), SideEffects::none(entry.parameters.len()));
+            }
+
+            // Referenced parameters depend on the assignments, so they are computed once
+            // all other effects are stable. Both phases only ever add effects.
+            call_graph.summarize_scc(&scc, proc_table, symbol_table, |a, b| {
+                a.modified == b.modified
+                    && a.assigned == b.assigned
+                    && a.performs_io == b.performs_io
+            });
+            for name in &scc {
+                call_graph
+                    .summaries
+                    .get_mut(name)
+                    .unwrap()
+                    .referenced
+                    .fill(false);
+            }
+            call_graph.summarize_scc(&scc, proc_table, symbol_table, |a, b| a == b);
+        }
+
+        call_graph
+    }
+
+    /// Updates the summaries of a group of mutually recursive procedures until they are stable.
+    fn summarize_scc(
+        &mut self,
+        scc: &[String],
+        proc_table: &HashMap<String, Vec<Quadrupel>>,
+        symbol_table: &SymbolTable,
+        is_stable: impl Fn(&SideEffects, &SideEffects) -> bool,
+    ) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for name in scc {
+                let summary = self.summarize(&proc_table[name], name, symbol_table);
+                if !is_stable(&self.summaries[name], &summary) {
+                    changed = true;
+                }
+                self.summaries.insert(name.clone(), summary);
+            }
+        }
+    }
+
+    /// Computes the side effects of a single procedure based on the current summaries of its callees.
+    /// A parameter is only referenced if its value may be read before the procedure assigns it.
+    fn summarize(
+        &self,
+        quads: &[Quadrupel],
+        proc_name: &str,
+        symbol_table: &SymbolTable,
+    ) -> SideEffects {
+        let Some(Entry::ProcedureEntry(entry)) = symbol_table.lookup(proc_name) else {
+            unreachable!()
+        };
+        let params = entry.parameters.len();
+
+        let graph = BlockGraph::from_tac(quads);
+        let blocks = graph
+            .blocks
+            .iter()
+            .map(|b| match &b.content {
+                BlockContent::Code(quads) => quads.as_slice(),
+                BlockContent::Start | BlockContent::Stop => &[],
+            })
+            .collect::<Vec<_>>();
+
+        // Parameters that each block assigns on its own
+        let assigned_by_block = blocks
+            .iter()
+            .map(|quads| {
+                let mut assigned = BitVec::repeat(false, params);
+                self.summarize_block(
+                    quads,
+                    &entry,
+                    symbol_table,
+                    &mut assigned,
+                    &mut SideEffects::none(params),
+                );
+                assigned
+            })
+            .collect::<Vec<_>>();
+
+        let assigned_at_entry = assigned_at_entry(&graph, &assigned_by_block);
+        let stop = graph
+            .blocks
+            .iter()
+            .position(|b| matches!(b.content, BlockContent::Stop))
+            .unwrap();
+
+        let mut effects = SideEffects::none(params);
+        effects.assigned.clone_from(&assigned_at_entry[stop]);
+        for (quads, mut assigned) in blocks.into_iter().zip(assigned_at_entry) {
+            self.summarize_block(quads, &entry, symbol_table, &mut assigned, &mut effects);
+        }
+
+        // Writes to value parameters are not visible to the caller.
+        for (k, param) in entry.parameters.iter().enumerate() {
+            if !param.is_reference {
+                effects.modified.set(k, false);
+                effects.assigned.set(k, false);
+            }
+        }
+
+        effects
+    }
+
+    /// Adds the side effects of the quads of a block, given the parameters that are
+    /// definitely assigned at the start of the block.
+    fn summarize_block(
+        &self,
+        quads: &[Quadrupel],
+        entry: &ProcedureEntry,
+        symbol_table: &SymbolTable,
+        assigned: &mut BitVec,
+        effects: &mut SideEffects,
+    ) {
+        let param_idx = |var: &QuadrupelVar| {
+            entry
+                .parameters
+                .iter()
+                .position(|p| matches!(var, QuadrupelVar::Spl(name) if *name == p.name))
+        };
+
+        // Temporaries holding a reference to an array element, e.g. for `readi(a[i])`.
+        let mut aliases = HashMap::new();
+        let mut args = Vec::new();
+
+        for quad in quads {
+            match quad.op {
+                QuadrupelOp::Param => args.push(&quad.arg1),
+                QuadrupelOp::Call => {
+                    let QuadrupelArg::Var(QuadrupelVar::Spl(callee)) = &quad.arg1 else {
+                        unreachable!()
+                    };
+                    let Some(Entry::ProcedureEntry(callee_entry)) = symbol_table.lookup(callee)
+                    else {
+                        unreachable!()
+                    };
+                    let worst_case = SideEffects::worst_case(&callee_entry.parameters);
+                    let callee_effects = self.summaries.get(callee).unwrap_or(&worst_case);
+
+                    for (j, arg) in std::mem::take(&mut args).into_iter().enumerate() {
+                        let QuadrupelArg::Var(var) = arg else {
+                            continue;
+                        };
+                        let alias = aliases.get(var);
+                        let Some(k) = param_idx(alias.unwrap_or(var)) else {
+                            continue;
+                        };
+
+                        if callee_entry.parameters[j].is_reference {
+                            let modified = effects.modified[k] | callee_effects.modified[j];
+                            let referenced = effects.referenced[k]
+                                || (callee_effects.referenced[j] && !assigned[k]);
+                            effects.modified.set(k, modified);
+                            effects.referenced.set(k, referenced);
+
+                            if callee_effects.assigned[j] && alias.is_none() {
+                                assigned.set(k, true);
+                            }
+                        } else if !assigned[k] {
+                            effects.referenced.set(k, true);
+                        }
+                    }
+                    effects.performs_io |= callee_effects.performs_io;
+
+                    // The value of a function assigns its result
+                    if let QuadrupelResult::Var(var) = &quad.result
+                        && let Some(k) = param_idx(var)
+                    {
+                        effects.modified.set(k, true);
+                        assigned.set(k, true);
+                    }
+                }
+                _ => {
+                    for arg in [&quad.arg1, &quad.arg2] {
+                        if let QuadrupelArg::Var(var) = arg
+                            && let Some(k) = param_idx(var)
+                            && !assigned[k]
+                        {
+                            effects.referenced.set(k, true);
+                        }
+                    }
+                    if let QuadrupelResult::Var(var) = &quad.result {
+                        if let Some(k) = param_idx(var) {
+                            effects.modified.set(k, true);
+                            // Storing a single element does not assign the whole array
+                            if quad.op != QuadrupelOp::ArrayStore {
+                                assigned.set(k, true);
+                            }
+                        }
+                        if quad.op == QuadrupelOp::ArrayLoad
+                            && let QuadrupelArg::Var(array) = &quad.arg1
+                            && matches!(
+                                entry.local_table.entries.get(&var.to_identifier()),
+                                Some(Entry::VariableEntry(VariableEntry {
+                                    is_reference: true,
+                                    ..
+                                }))
+                            )
+                        {
+                            aliases.insert(var.clone(), array.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Tarjan's algorithm on the call graph, yields the SCCs in reverse topological order.
+    fn tarjan(&self) -> Vec<Vec<String>> {
+        struct State<'a> {
+            index: HashMap<&'a str, usize>,
+            lowlink: HashMap<&'a str, usize>,
+            stack: Vec<&'a str>,
+            sccs: Vec<Vec<String>>,
+        }
+
+        fn strong_connect<'a>(graph: &'a CallGraph, state: &mut State<'a>, v: &'a str) {
+            let idx = state.index.len();
+            state.index.insert(v, idx);
+            state.lowlink.insert(v, idx);
+            state.stack.push(v);
+
+            for w in graph.callees[v]
+                .iter()
+                .filter(|w| graph.callees.contains_key(*w))
+            {
+                if !state.index.contains_key(w.as_str()) {
+                    strong_connect(graph, state, w);
+                    state
+                        .lowlink
+                        .insert(v, state.lowlink[v].min(state.lowlink[w.as_str()]));
+                } else if state.stack.contains(&w.as_str()) {
+                    state
+                        .lowlink
+                        .insert(v, state.lowlink[v].min(state.index[w.as_str()]));
+                }
+            }
+
+            if state.lowlink[v] == state.index[v] {
+                let pos = state.stack.iter().position(|w| *w == v).unwrap();
+                let mut scc = state
+                    .stack
+                    .split_off(pos)
+                    .into_iter()
+                    .map(String::from)
+                    .collect::<Vec<_>>();
+                scc.sort();
+                state.sccs.push(scc);
+            }
+        }
+
+        let mut state = State {
+            index: HashMap::new(),
+            lowlink: HashMap::new(),
+            stack: Vec::new(),
+            sccs: Vec::new(),
+        };
+        for name in self.callees.keys() {
+            if !state.index.contains_key(name.as_str()) {
+                strong_connect(self, &mut state, name);
+            }
+        }
+        state.sccs
+    }
+
+    /// Checks whether a procedure can (directly or indirectly) call itself.
+    pub fn is_recursive(&self, proc_name: &str) -> bool {
+        self.callees[proc_name].contains(proc_name)
+            || self
+                .sccs
+                .iter()
+                .any(|scc| scc.len() > 1 && scc.iter().any(|p| p == proc_name))
+    }
+
+    /// All user-defined procedures that can be called starting from `root` (including `root`).
+    pub fn reachable_from(&self, root: &str) -> BTreeSet<&str> {
+        let mut reachable = BTreeSet::new();
+        let mut worklist = vec![root];
+
+        while let Some(name) = worklist.pop() {
+            if let Some((name, callees)) = self.callees.get_key_value(name)
+                && reachable.insert(name.as_str())
+            {
+                worklist.extend(callees.iter().map(String::as_str));
+            }
+        }
+
+        reachable
+    }
+
+    /// All user-defined procedures that are never called starting from `root`.
+    pub fn unreachable_from(&self, root: &str) -> Vec<&str> {
+        let reachable = self.reachable_from(root);
+        self.callees
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !reachable.contains(name))
+            .collect()
+    }
+
+    /// Stores the summaries in the procedure entries, where the data-flow analyses pick them up.
+    pub fn annotate(&self, symbol_table: &mut SymbolTable) {
+        for (name, summary) in &self.summaries {
+            if let Some(Entry::ProcedureEntry(entry)) = symbol_table.entries.get_mut(name) {
+                entry.side_effects = Some(Rc::new(summary.clone()));
+            }
+        }
+    }
+}
+
+impl Display for CallGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |names: &mut dyn Iterator<Item = &str>| {
+            let names = names.collect::<Vec<_>>();
+            if names.is_empty() {
+                "-".to_string()
+            } else {
+                names.join(", ")
+            }
+        };
+
+        writeln!(f, ">>> Call Graph:")?;
+        for (name, callees) in &self.callees {
+            writeln!(
+                f,
+                "{name} -> {}",
+                join(&mut callees.iter().map(String::as_str))
+            )?;
+        }
+
+        writeln!(f, "\n>>> Recursion:")?;
+        for scc in &self.sccs {
+            if scc.len() > 1 {
+                writeln!(f, "{{{}}} (mutually recursive)", scc.join(", "))?;
+            } else if self.is_recursive(&scc[0]) {
+                writeln!(f, "{} (recursive)", scc[0])?;
+            }
+        }
+
+        writeln!(f, "\n>>> Unreachable from main:")?;
+        writeln!(
+            f,
+            "{}",
+            join(&mut self.unreachable_from("main").into_iter())
+        )?;
+
+        writeln!(f, "\n>>> Side Effects:")?;
+        writeln!(
+            f,
+            "{:<15} {:<20} {:<20} {:<20} I/O",
+            "Procedure", "Modifies", "Assigns", "References"
+        )?;
+        for name in self.callees.keys() {
+            let summary = &self.summaries[name];
+            let names = &self.param_names[name];
+            let params = |bits: &BitVec| {
+                bits.iter_ones()
+                    .map(|i| names[i].as_str())
+                    .collect::<Vec<_>>()
+            };
+            writeln!(
+                f,
+                "{name:<15} {:<20} {:<20} {:<20} {}",
+                join(&mut params(&summary.modified).into_iter()),
+                join(&mut params(&summary.assigned).into_iter()),
+                join(&mut params(&summary.referenced).into_iter()),
+                if summary.performs_io { "yes" } else { "no" },
+            )?;
+        }
+
+        Ok(())
+    }
+}
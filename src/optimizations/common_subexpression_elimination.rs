@@ -5,12 +5,18 @@ use crate::{
     code_gen::quadrupel::{
         Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar, quad,
     },
-    table::symbol_table::SymbolTable,
+    table::{
+        entry::{Entry, VariableEntry},
+        symbol_table::SymbolTable,
+        types::Type,
+    },
 };
 
 impl BlockGraph {
     // Applies Common Subexpression Elimination (CSE) to all basic blocks in the graph.
-    pub fn common_subexpression_elimination(&mut self, symbol_table: &SymbolTable) {
+    // New temporaries are entered into the local symbol table, as the TAC interpreter
+    // only allocates the variables listed there.
+    pub fn common_subexpression_elimination(&mut self, local_table: &mut SymbolTable) {
         // Find the highest temporary variable number used so far.
         let mut tmp_last_num = self
             .blocks
@@ -23,19 +29,41 @@ impl BlockGraph {
                 _ => None,
             })
             .flatten()
+            .chain(
+                local_table
+                    .entries
+                    .keys()
+                    .filter_map(|name| name.strip_prefix('T')?.parse().ok()),
+            )
             .max()
             .unwrap_or(0);
 
+        let mut new_tmps = Vec::new();
+
         // Function to generate new unique temporary variable numbers.
         let mut tmp_next_num = || -> usize {
             tmp_last_num += 1;
+            new_tmps.push(tmp_last_num);
             tmp_last_num
         };
 
         // Apply the optimization to each block individually.
         self.blocks
             .iter_mut()
-            .for_each(|b| optimize_block(b, &mut tmp_next_num, symbol_table));
+            .for_each(|b| optimize_block(b, &mut tmp_next_num, local_table));
+
+        // All expressions are integer valued, so are the temporaries holding them.
+        for n in new_tmps {
+            local_table
+                .enter(
+                    QuadrupelVar::Tmp(n).to_identifier(),
+                    Entry::VariableEntry(VariableEntry {
+                        typ: Type::INT,
+                        is_reference: false,
+                    }),
+                )
+                .unwrap();
+        }
     }
 }
 
@@ -67,6 +95,12 @@ fn optimize_block(
             | QuadrupelOp::Sub
             | QuadrupelOp::Mul
             | QuadrupelOp::Div
+            | QuadrupelOp::Mod
+            | QuadrupelOp::And
+            | QuadrupelOp::Or
+            | QuadrupelOp::Xor
+            | QuadrupelOp::Shl
+            | QuadrupelOp::Shr
             | QuadrupelOp::Neg => {
                 // Try to find a matching expression in the AEB (Available Expression Buffer).
                 if let Some(entry) = aeb.iter_mut().find(|e| e.cmp(&quad)) {
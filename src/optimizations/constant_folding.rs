@@ -1,7 +1,5 @@
 use std::ops::ControlFlow;
 
-use bitvec::vec::BitVec;
-
 use crate::{
     base_blocks::{BlockContent, BlockGraph},
     code_gen::quadrupel::{Quadrupel, QuadrupelArg, QuadrupelOp},
@@ -28,24 +26,43 @@ impl BlockGraph {
                 let mut const_state = const_prop.r#in[block_id].clone();
 
                 // Detect which PARAM instructions refer to reference parameters
-                let ref_params = quads
+                let param_access = quads
                     .iter()
                     .enumerate()
                     .map(|(i, q)| {
-                        q.op == QuadrupelOp::Param
-                            && Quadrupel::find_param_declaration(quads, i, symbol_table)
-                                .is_reference
+                        (q.op == QuadrupelOp::Param)
+                            .then(|| Quadrupel::find_param_access(quads, i, symbol_table))
                     })
-                    .collect::<BitVec>();
+                    .collect::<Vec<_>>();
+                let is_reference =
+                    |quad_idx: usize| param_access[quad_idx].is_some_and(|a| a.is_reference);
+                let may_write =
+                    |quad_idx: usize| param_access[quad_idx].is_some_and(|a| a.may_write);
 
                 // Iterate over each instruction in the block
                 for (quad_idx, quad) in quads.iter_mut().enumerate() {
                     match &quad.op {
-                        // Skip CALL instructions (side effects, no simplification)
-                        QuadrupelOp::Call => {}
+                        // CALL instructions are not simplified (side effects), but the
+                        // value of a function is not constant
+                        QuadrupelOp::Call => {
+                            Constness::from_quad(
+                                quad,
+                                &mut const_state,
+                                |var| const_prop.get_var_idx(var).unwrap(),
+                                || false,
+                            );
+                        }
 
-                        // Skip PARAM instructions that are reference arguments
-                        QuadrupelOp::Param if ref_params[quad_idx] => {}
+                        // Keep reference arguments, but forget their constant value if the
+                        // called procedure may write them
+                        QuadrupelOp::Param if is_reference(quad_idx) => {
+                            Constness::from_quad(
+                                quad,
+                                &mut const_state,
+                                |var| const_prop.get_var_idx(var).unwrap(),
+                                || may_write(quad_idx),
+                            );
+                        }
 
                         // All other instructions may be simplified
                         _ => {
@@ -61,7 +78,7 @@ impl BlockGraph {
                                 quad,
                                 &mut const_state,
                                 |var| const_prop.get_var_idx(var).unwrap(),
-                                || ref_params[quad_idx],
+                                || may_write(quad_idx),
                             );
                         }
                     }
@@ -151,10 +151,10 @@ impl Block {
                 let mut gens = vec![Undefined; vars.len()];
 
                 for (i, quad) in quads.iter().enumerate() {
-                    // Check if we're referencing a parameter
-                    let is_reference =
-                        || Quadrupel::find_param_declaration(quads, i, &symbol_table).is_reference;
-                    Constness::from_quad(quad, &mut gens, var_idx, is_reference);
+                    // Check if the called procedure may write a reference parameter
+                    let may_write =
+                        || Quadrupel::find_param_access(quads, i, &symbol_table).may_write;
+                    Constness::from_quad(quad, &mut gens, var_idx, may_write);
                 }
 
                 gens
@@ -195,9 +195,13 @@ impl Constness {
         quad: &'a Quadrupel,
         gens: &mut [Self],
         var_idx: impl Fn(&'b QuadrupelVar) -> usize,
-        is_reference: impl FnOnce() -> bool,
+        may_write: impl FnOnce() -> bool,
     ) {
         match quad {
+            // The value of a function is unknown, its name is no variable
+            quad_match!((c), _, _ => QuadrupelResult::Var(var)) => {
+                gens[var_idx(var)] = Variable;
+            }
             // Handle assignment to a variable
             quad_match!(op, arg1, arg2 => res @ QuadrupelResult::Var(var)) => {
                 let var = var_idx(var);
@@ -208,20 +212,21 @@ impl Constness {
                     // Simple constant assignment
                     (quad!(@op :=), (Constant(c), _)) => Constant(c),
                     // Unary minus
-                    (quad!(@op ~ ), (Constant(c), _)) => Constant(-c),
-
-                    // Binary operations with constant values
-                    (op @ quad!(@op (+)(-)(*)(/)), (Constant(c1), Constant(c2))) => Constant(
-                        quad!(*op, (=c1), (=c2) => res.clone())
-                            .calc_const()
-                            .unwrap(),
-                    ),
+                    (quad!(@op ~ ), (Constant(c), _)) => Constant(c.wrapping_neg()),
+
+                    // Binary operations with constant values, a division by zero is left to the runtime
+                    (
+                        op @ quad!(@op (+)(-)(*)(/)(%)(&)(|)(^)(<<)(>>)),
+                        (Constant(c1), Constant(c2)),
+                    ) => quad!(*op, (=c1), (=c2) => res.clone())
+                        .calc_const()
+                        .map_or(Variable, Constant),
                     // Otherwise, result is not constant
                     _ => Variable,
                 };
             }
-            // Special case: calling procedure by reference
-            quad_match!((p), (~var), _ => _) if is_reference() => {
+            // Special case: argument may be written by the called procedure
+            quad_match!((p), (~var), _ => _) if may_write() => {
                 let var = var_idx(var);
                 // Cannot determine constness of references
                 gens[var] = Variable;
@@ -1,6 +1,6 @@
 use crate::{
     base_blocks::{BlockContent, BlockGraph},
-    code_gen::quadrupel::{Quadrupel, QuadrupelArg, QuadrupelResult, QuadrupelVar},
+    code_gen::quadrupel::{Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar},
     optimizations::{live_variables::LiveVariables, worklist::GetVarIdx},
 };
 
@@ -26,7 +26,10 @@ impl BlockGraph {
                         .and_then(|var| livar.get_var_idx(var))
                         .is_some_and(|idx| !liveout[idx]);
 
-                    if is_dead {
+                    if is_dead && quad.op == QuadrupelOp::Call {
+                        // The call may have side effects, only its value is dropped.
+                        quad.result = QuadrupelResult::Empty;
+                    } else if is_dead {
                         // Replace the instruction with an EMPTY placeholder.
                         *quad = Quadrupel::EMPTY;
                     } else {
@@ -0,0 +1,46 @@
+use crate::{
+    code_gen::Tac,
+    optimizations::call_graph::CallGraph,
+    table::{entry::Entry, symbol_table::SymbolTable},
+};
+
+impl Tac {
+    /// Removes all procedures that are never called (directly or indirectly) from `main`
+    /// and marks their symbol table entries as eliminated.
+    /// Returns the removed procedures together with their number of quadruples.
+    pub fn dead_procedure_elimination(
+        &mut self,
+        call_graph: &CallGraph,
+        symbol_table: &mut SymbolTable,
+    ) -> Vec<(String, usize)> {
+        call_graph
+            .unreachable_from("main")
+            .into_iter()
+            .filter_map(|name| {
+                let quads = self.proc_table.remove(name)?;
+
+                if let Some(Entry::ProcedureEntry(entry)) = symbol_table.entries.get_mut(name) {
+                    entry.is_eliminated = true;
+                }
+
+                Some((name.to_string(), quads.len()))
+            })
+            .collect()
+    }
+}
+
+impl SymbolTable {
+    /// Names of the procedures marked as eliminated, sorted alphabetically.
+    pub fn eliminated_procedures(&self) -> Vec<&str> {
+        let mut names = self
+            .entries
+            .iter()
+            .filter_map(|(name, entry)| match entry {
+                Entry::ProcedureEntry(entry) if entry.is_eliminated => Some(name.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+}
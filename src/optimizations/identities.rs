@@ -24,13 +24,18 @@ impl Quadrupel {
                 quad!((:=), (arg), _ => res)
             }
 
+            // x | 0 = x, x ^ 0 = x, x << 0 = x, x >> 0 = x
+            quad_match!((|)(^), 0, arg => res) | quad_match!((|)(^)(<<)(>>), arg, 0 => res) => {
+                quad!((:=), (arg), _ => res)
+            }
+
             // 0 - x = -x
             quad_match!((-), 0, arg => res) => {
                 quad!((~), (arg), _ => res)
             }
 
-            // x - x = 0
-            quad_match!((-), arg1, arg2 => res) if arg1 == arg2 => {
+            // x - x = 0, x ^ x = 0
+            quad_match!((-)(^), arg1, arg2 => res) if arg1 == arg2 => {
                 quad!((:=), 0, _ => res)
             }
 
@@ -39,8 +44,10 @@ impl Quadrupel {
                 quad!((+), (arg.clone()), (arg) => res)
             }
 
-            // x * 0 = 0, 0 * x = 0
-            quad_match!((*)(/), 0, _ => res) | quad_match!((*), _, 0 => res) => {
+            // x * 0 = 0, 0 * x = 0, x & 0 = 0, 0 & x = 0, x % 1 = 0
+            quad_match!((*)(/)(&), 0, _ => res)
+            | quad_match!((*)(&), _, 0 => res)
+            | quad_match!((%), _, 1 => res) => {
                 quad!((:=), 0, _ => res)
             }
 
@@ -50,6 +57,8 @@ impl Quadrupel {
     }
 
     /// Performs constant folding on arithmetic operations if both operands are constants.
+    /// Arithmetic wraps around on overflow like the target machine.
+    /// A division by zero is not folded, it fails at runtime.
     pub const fn calc_const(&self) -> Option<i32> {
         let QuadrupelArg::Const(arg1) = self.arg1 else {
             return None;
@@ -65,13 +74,20 @@ impl Quadrupel {
 
         // Perform constant arithmetic depending on the operator
         match self.op {
-            quad!(@op + ) => Some(arg1 + arg2.unwrap()),
-            quad!(@op - ) => Some(arg1 - arg2.unwrap()),
-            quad!(@op * ) => Some(arg1 * arg2.unwrap()),
-            quad!(@op / ) => Some(arg1 / arg2.unwrap()),
+            quad!(@op + ) => Some(arg1.wrapping_add(arg2.unwrap())),
+            quad!(@op - ) => Some(arg1.wrapping_sub(arg2.unwrap())),
+            quad!(@op * ) => Some(arg1.wrapping_mul(arg2.unwrap())),
+            quad!(@op / ) => arg1.checked_div(arg2.unwrap()),
+            quad!(@op % ) => arg1.checked_rem(arg2.unwrap()),
+            quad!(@op & ) => Some(arg1 & arg2.unwrap()),
+            quad!(@op | ) => Some(arg1 | arg2.unwrap()),
+            quad!(@op ^ ) => Some(arg1 ^ arg2.unwrap()),
+            // Only the lower five bits of the amount are used
+            quad!(@op <<) => Some(arg1 << (arg2.unwrap() & 31)),
+            quad!(@op >>) => Some(arg1 >> (arg2.unwrap() & 31)),
 
             // Unary negation
-            quad!(@op ~ ) => Some(-arg1),
+            quad!(@op ~ ) => Some(arg1.wrapping_neg()),
 
             _ => None,
         }
@@ -4,8 +4,9 @@ use std::ops::Not;
 use bitvec::vec::BitVec;
 
 use crate::base_blocks::{Block, BlockContent, BlockGraph};
-use crate::code_gen::quadrupel::{QuadrupelResult, QuadrupelVar};
+use crate::code_gen::quadrupel::{Quadrupel, QuadrupelOp, QuadrupelResult, QuadrupelVar};
 use crate::optimizations::worklist::Definition;
+use crate::table::entry::{Entry, VariableEntry};
 use crate::table::symbol_table::SymbolTable;
 
 use super::worklist::{self, GetVarIdx, Worklist};
@@ -44,16 +45,32 @@ impl Worklist for LiveVariables {
             .into_iter()
             .collect::<Vec<_>>();
 
+        // Array stores only overwrite a single element, so they do not kill the array.
+        // Calls may leave their reference arguments untouched, so they do not kill them either.
+        let killing_defs = defs_in_proc
+            .into_iter()
+            .filter(|d| {
+                !matches!(
+                    graph.def_op(d),
+                    Some(QuadrupelOp::ArrayStore | QuadrupelOp::Param)
+                )
+            })
+            .collect::<Vec<_>>();
+
         // Compute 'def' bit vectors for each block.
         let def = graph
             .blocks
             .iter()
             .enumerate()
-            .map(|(block_id, _)| Block::defs_in_block_2(block_id, &defs_in_proc, &vars))
+            .map(|(block_id, _)| Block::defs_in_block_2(block_id, &killing_defs, &vars))
             .collect();
 
         // Compute 'use' bit vectors for each block.
-        let r#use = graph.blocks.iter().map(|b| b.get_liv_use(&vars)).collect();
+        let r#use = graph
+            .blocks
+            .iter()
+            .map(|b| b.get_liv_use(&vars, local_table))
+            .collect();
 
         Self {
             def,
@@ -88,6 +105,16 @@ impl GetVarIdx<QuadrupelVar> for LiveVariables {
     }
 }
 
+impl BlockGraph {
+    /// Returns the operation of the quad a definition stems from.
+    fn def_op(&self, def: &Definition) -> Option<QuadrupelOp> {
+        match &self.blocks[def.block_id].content {
+            BlockContent::Code(quads) => Some(quads[def.quad_id].op),
+            BlockContent::Start | BlockContent::Stop => None,
+        }
+    }
+}
+
 impl Block {
     /// Computes a `BitVec` that indicates which variables from `unique_defs`
     /// are defined in the given block.
@@ -109,12 +136,14 @@ impl Block {
     }
 
     /// Returns a list of assignments (index, variable) inside this block.
+    /// Array stores are skipped, as they leave the rest of the array untouched.
     pub fn assignments_in_block(&self) -> Vec<(usize, QuadrupelVar)> {
         match self.content {
             BlockContent::Start | BlockContent::Stop => vec![],
             BlockContent::Code(ref quadrupels) => quadrupels
                 .iter()
                 .enumerate()
+                .filter(|(_, q)| q.op != QuadrupelOp::ArrayStore)
                 .filter_map(|(i, q)| match &q.result {
                     QuadrupelResult::Var(v) => Some((i, v.clone())),
                     _ => None,
@@ -125,15 +154,40 @@ impl Block {
 
     /// Computes the use bit vector for live variable analysis.
     /// A variable is considered "used" if it is read before being (re)assigned.
-    fn get_liv_use(&self, unique_defs: &[QuadrupelVar]) -> BitVec {
+    /// Reference parameters are used by the stop block, as the caller can read them.
+    /// Arguments are only used by a call if the called procedure may read them.
+    fn get_liv_use(&self, unique_defs: &[QuadrupelVar], local_table: &SymbolTable) -> BitVec {
         let assignment_in_block = self.assignments_in_block();
 
         // Collect all variables that are used before they are assigned.
         let used_vars = match &self.content {
-            BlockContent::Start | BlockContent::Stop => vec![],
+            BlockContent::Start => vec![],
+            BlockContent::Stop => {
+                return unique_defs
+                    .iter()
+                    .map(|v| {
+                        matches!(
+                            local_table.entries.get(&v.to_identifier()),
+                            Some(Entry::VariableEntry(VariableEntry {
+                                is_reference: true,
+                                ..
+                            }))
+                        )
+                    })
+                    .collect();
+            }
             BlockContent::Code(quadrupels) => quadrupels
                 .iter()
                 .enumerate()
+                .filter(|(i, q)| {
+                    q.op != QuadrupelOp::Param
+                        || Quadrupel::find_param_access(
+                            quadrupels,
+                            *i,
+                            &local_table.upper_level().borrow(),
+                        )
+                        .may_read
+                })
                 .flat_map(|(i, q)| {
                     vec![
                         match &q.arg1 {
@@ -152,9 +206,10 @@ impl Block {
                 })
                 .flatten()
                 .filter(|(i, v)| {
-                    // Exclude uses that come after the variable is assigned.
+                    // Exclude uses that come after the variable is assigned,
+                    // a quad reads its arguments before it assigns the result.
                     let assignment = assignment_in_block.iter().find(|(_, va)| v == &va);
-                    assignment.is_none() || assignment.unwrap().0 > *i
+                    assignment.is_none() || assignment.unwrap().0 >= *i
                 })
                 .collect::<Vec<_>>(),
         };
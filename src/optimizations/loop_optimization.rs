@@ -1,6 +1,8 @@
 use crate::{
     base_blocks::{Block, BlockContent, BlockGraph, BlockId},
-    code_gen::quadrupel::{QuadrupelArg, QuadrupelResult, QuadrupelVar, quad, quad_match},
+    code_gen::quadrupel::{
+        QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar, quad, quad_match,
+    },
     optimizations::{reaching_expressions::ReachingDefinitions, tarjan::Scc, worklist::Worklist},
     table::symbol_table::SymbolTable,
 };
@@ -34,8 +36,10 @@ impl BlockGraph {
                 // Only process blocks containing code (i.e., not entry/exit/other metadata blocks)
                 if let BlockContent::Code(quads) = &mut block.content {
                     for (line_number, quad) in quads.iter().enumerate() {
-                        // Check if both operands are loop-invariant
-                        if is_var_invariant(extract_var(&quad.arg1), &sccs[sccid], &reachdef)
+                        // Check if both operands are loop-invariant, calls are never
+                        // moved as they may have side effects
+                        if quad.op != QuadrupelOp::Call
+                            && is_var_invariant(extract_var(&quad.arg1), &sccs[sccid], &reachdef)
                             && is_var_invariant(extract_var(&quad.arg2), &sccs[sccid], &reachdef)
                             && is_movable(
                                 &quad.result,
@@ -69,8 +73,17 @@ impl BlockGraph {
                 };
                 block_counter += 1;
                 let new_id = self.add_block(new_block, None);
-                let new_label = QuadrupelResult::Label(format!("L{}", new_id));
-                self.label_to_id.insert(format!("L{new_id}"), new_id);
+                // The label must not be taken by the code generator yet, a duplicate would
+                // redirect unrelated jumps into the preheader
+                let label_num = self
+                    .label_to_id
+                    .keys()
+                    .filter_map(|l| l.strip_prefix('L')?.parse::<usize>().ok())
+                    .max()
+                    .map_or(0, |n| n + 1);
+                let label = format!("L{label_num}");
+                self.label_to_id.insert(label.clone(), new_id);
+                let new_label = QuadrupelResult::Label(label);
                 let loop_label = self.blocks[sccs[sccid].nodes[0]].label.clone().unwrap();
                 match &mut self.blocks[new_id].content {
                     BlockContent::Code(code) => {
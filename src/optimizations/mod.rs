@@ -1,12 +1,19 @@
 mod aeb;
 mod identities;
 
+pub mod array_bounds;
+pub mod bounds_check_elimination;
+pub mod call_graph;
 pub mod common_subexpression_elimination;
 pub mod constant_folding;
 pub mod constant_propagation;
 pub mod dead_code_elimination;
+pub mod dead_procedure_elimination;
 pub mod live_variables;
 pub mod loop_optimization;
+pub mod pass_manager;
+pub mod range_analysis;
 pub mod reaching_expressions;
 pub mod tarjan;
+pub mod uninitialized_variables;
 pub mod worklist;
@@ -0,0 +1,614 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use anyhow::bail;
+use colored::Colorize;
+use dialoguer::theme::Theme;
+
+use crate::{
+    base_blocks::BlockGraph,
+    cli::{fmt_bitvec, show_worklist_table},
+    code_gen::Tac,
+    optimizations::{
+        array_bounds::BoundsCheck, call_graph::CallGraph,
+        constant_propagation::ConstantPropagation, live_variables::LiveVariables,
+        range_analysis::RangeAnalysis, reaching_expressions::ReachingDefinitions,
+        uninitialized_variables::UninitializedVariables, worklist::Worklist,
+    },
+    table::{entry::Entry, symbol_table::SymbolTable},
+};
+
+/// Upper bound for the iterations of a fixpoint group, in case a pass keeps oscillating.
+const MAX_FIXPOINT_ITERATIONS: usize = 100;
+
+/// Data-flow analyses that passes can depend on.
+/// The pass manager caches their results until a pass invalidates them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Analysis {
+    ReachingDefinitions,
+    LiveVariables,
+    ConstantPropagation,
+    Ranges,
+}
+
+impl Analysis {
+    /// All analyses, used by passes that rewrite the code arbitrarily.
+    const ALL: &[Self] = &[
+        Self::ReachingDefinitions,
+        Self::LiveVariables,
+        Self::ConstantPropagation,
+        Self::Ranges,
+    ];
+}
+
+/// A named optimization or analysis pass on the block graph of a single procedure.
+#[derive(Debug)]
+pub struct Pass {
+    /// Name used on the command line.
+    pub name: &'static str,
+    /// Headline printed when the pass runs.
+    pub description: &'static str,
+    /// Analyses that are computed (or taken from the cache) before the pass runs.
+    pub requires: &'static [Analysis],
+    /// Analyses whose cached results are outdated after the pass ran.
+    pub invalidates: &'static [Analysis],
+    run: fn(&mut PassContext<'_>) -> anyhow::Result<()>,
+}
+
+/// All passes known to the pass manager.
+pub const PASSES: &[Pass] = &[
+    Pass {
+        name: "cse",
+        description: "Common Subexpression Elimination",
+        requires: &[],
+        invalidates: Analysis::ALL,
+        run: |ctx| {
+            let mut local_table = ctx.local_table();
+            ctx.graph.common_subexpression_elimination(&mut local_table);
+            match ctx.symbol_table.borrow_mut().entries.get_mut(ctx.proc_name) {
+                Some(Entry::ProcedureEntry(pe)) => pe.local_table = local_table,
+                _ => unreachable!(),
+            }
+            Ok(())
+        },
+    },
+    Pass {
+        name: "rch",
+        description: "Reaching Definitions:",
+        requires: &[Analysis::ReachingDefinitions],
+        invalidates: &[],
+        run: |ctx| {
+            let rch = ctx.rch.as_ref().unwrap();
+            show_worklist_table(
+                ("Definitions", &rch.defs),
+                (1, 1),
+                ("GEN", &rch.gen_bits),
+                ("PRSV", &rch.prsv),
+                ("RCHin", &rch.rchin),
+                ("RCHout", &rch.rchout),
+                fmt_bitvec,
+            )?;
+            Ok(())
+        },
+    },
+    Pass {
+        name: "uninit",
+        description: "Uninitialized Variables:",
+        requires: &[Analysis::ReachingDefinitions],
+        invalidates: &[],
+        run: |ctx| {
+            let Some(Entry::ProcedureEntry(proc_entry)) =
+                ctx.symbol_table.borrow().lookup(ctx.proc_name)
+            else {
+                unreachable!()
+            };
+            let uninit =
+                UninitializedVariables::new(ctx.graph, ctx.rch.as_ref().unwrap(), &proc_entry);
+            eprint!("{uninit}");
+            Ok(())
+        },
+    },
+    Pass {
+        name: "lv",
+        description: "Live Variables:",
+        requires: &[Analysis::LiveVariables],
+        invalidates: &[],
+        run: |ctx| {
+            let lv = ctx.lv.as_ref().unwrap();
+            show_worklist_table(
+                ("Variables", &lv.vars),
+                (1, 1),
+                ("DEF", &lv.def),
+                ("USE", &lv.use_bits),
+                ("LIVin", &lv.livin),
+                ("LIVout", &lv.livout),
+                fmt_bitvec,
+            )?;
+            Ok(())
+        },
+    },
+    Pass {
+        name: "dead",
+        description: "Dead Code Elimination",
+        requires: &[Analysis::LiveVariables],
+        invalidates: Analysis::ALL,
+        run: |ctx| {
+            ctx.graph.dead_code_elimination(ctx.lv.as_ref().unwrap());
+            Ok(())
+        },
+    },
+    Pass {
+        name: "gcp",
+        description: "Constant Propagation:",
+        requires: &[Analysis::ConstantPropagation],
+        invalidates: &[],
+        run: |ctx| {
+            let gcp = ctx.gcp.as_ref().unwrap();
+            show_worklist_table(
+                ("Variables", &gcp.vars),
+                (5, 14),
+                ("GEN", &gcp.gens),
+                ("PRSV", &gcp.prsv),
+                ("IN", &gcp.r#in),
+                ("OUT", &gcp.out),
+                |v| format!("{v:?}"),
+            )?;
+            Ok(())
+        },
+    },
+    Pass {
+        name: "range",
+        description: "Range Analysis:",
+        requires: &[Analysis::Ranges],
+        invalidates: &[],
+        run: |ctx| {
+            let ranges = ctx.ranges.as_ref().unwrap();
+            show_worklist_table(
+                ("Variables", &ranges.vars),
+                (8, 17),
+                ("GEN", &ranges.gens),
+                ("PRSV", &ranges.prsv),
+                ("IN", &ranges.r#in),
+                ("OUT", &ranges.out),
+                |v| format!("{v:?}"),
+            )?;
+            Ok(())
+        },
+    },
+    Pass {
+        name: "bounds",
+        description: "Array Bounds Check:",
+        requires: &[Analysis::Ranges],
+        invalidates: &[],
+        run: |ctx| {
+            let check = BoundsCheck::new(ctx.graph, ctx.ranges.as_ref().unwrap());
+            eprint!("{check}");
+            Ok(())
+        },
+    },
+    Pass {
+        name: "bce",
+        description: "Bounds Check Elimination",
+        requires: &[Analysis::Ranges],
+        invalidates: Analysis::ALL,
+        run: |ctx| {
+            let removed = ctx
+                .graph
+                .bounds_check_elimination(ctx.ranges.as_ref().unwrap());
+            eprintln!("    removed checks: {removed}");
+            Ok(())
+        },
+    },
+    Pass {
+        name: "cf",
+        description: "Constant Folding",
+        requires: &[Analysis::ConstantPropagation],
+        // Constant folding keeps the propagation state up to date by itself.
+        invalidates: &[
+            Analysis::ReachingDefinitions,
+            Analysis::LiveVariables,
+            Analysis::Ranges,
+        ],
+        run: |ctx| {
+            let symbol_table = ctx.symbol_table.borrow();
+            _ = ctx
+                .graph
+                .constant_folding(ctx.gcp.as_mut().unwrap(), &symbol_table);
+            Ok(())
+        },
+    },
+    Pass {
+        name: "scc",
+        description: "Strongly Connected Components:",
+        requires: &[],
+        invalidates: &[],
+        run: |ctx| {
+            let scc = ctx.graph.tarjan();
+            eprintln!("{scc:#?}");
+            Ok(())
+        },
+    },
+    Pass {
+        name: "licm",
+        description: "Loop Invariant Code Motion:",
+        requires: &[],
+        invalidates: Analysis::ALL,
+        run: |ctx| {
+            let local_table = ctx.local_table();
+            ctx.graph.loop_optimization(&local_table);
+            Ok(())
+        },
+    },
+    Pass {
+        name: "dot",
+        description: "Showing Dot Graph...",
+        requires: &[],
+        invalidates: &[],
+        run: |ctx| ctx.graph.show_dot(ctx.proc_name, ctx.matches, ctx.theme),
+    },
+];
+
+/// A named pass on the whole program, it runs before the passes on single procedures.
+#[derive(Debug)]
+pub struct ProgramPass {
+    /// Name used on the command line.
+    pub name: &'static str,
+    /// Headline printed when the pass runs.
+    pub description: &'static str,
+    run: fn(&mut Tac, &Rc<RefCell<SymbolTable>>) -> anyhow::Result<()>,
+}
+
+/// All whole-program passes known to the pass manager.
+pub const PROGRAM_PASSES: &[ProgramPass] = &[ProgramPass {
+    name: "dpe",
+    description: "Dead Procedure Elimination",
+    run: |tac, symbol_table| {
+        let call_graph = CallGraph::new(&tac.proc_table, &symbol_table.borrow());
+        let removed = tac.dead_procedure_elimination(&call_graph, &mut symbol_table.borrow_mut());
+
+        if removed.is_empty() {
+            eprintln!("    removed: -");
+        }
+        for (name, quads) in removed {
+            eprintln!("    removed: {name} ({quads} quads)");
+        }
+        Ok(())
+    },
+}];
+
+/// Predefined pipelines, selected with `-O0`, `-O1` and `-O2`.
+const PIPELINES: [(&str, &[&str]); 3] = [
+    ("0", &[]),
+    ("1", &["dpe", "cse", "cf", "dead", "bce"]),
+    (
+        "2",
+        &["dpe", "cse", "cf:dead+", "licm+", "cse", "cf:dead+", "bce"],
+    ),
+];
+
+/// Looks up a pass by its name.
+pub fn find_pass(name: &str) -> Option<&'static Pass> {
+    PASSES.iter().find(|p| p.name == name)
+}
+
+/// Looks up a whole-program pass by its name.
+pub fn find_program_pass(name: &str) -> Option<&'static ProgramPass> {
+    PROGRAM_PASSES.iter().find(|p| p.name == name)
+}
+
+/// A single step of a pipeline.
+#[derive(Debug, Clone)]
+pub enum Step {
+    /// Runs a pass once.
+    Pass(&'static Pass),
+    /// Runs a group of passes repeatedly until the code does not change anymore.
+    Fixpoint(Vec<&'static Pass>),
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pass(pass) => write!(f, "{}", pass.name),
+            Self::Fixpoint(passes) => {
+                let names = passes.iter().map(|p| p.name).collect::<Vec<_>>();
+                write!(f, "{}+", names.join(":"))
+            }
+        }
+    }
+}
+
+/// An ordered list of steps that is applied to every procedure,
+/// preceded by the passes on the whole program.
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    pub program_passes: Vec<&'static ProgramPass>,
+    pub steps: Vec<Step>,
+}
+
+impl Pipeline {
+    /// Parses a pipeline from the names given on the command line.
+    ///
+    /// Every name is either a pass, a predefined pipeline (`0`, `1`, `2`),
+    /// a pass followed by `+` (repeat until stable) or a group of passes
+    /// separated by `:` followed by `+` (repeat the whole group until stable).
+    pub fn parse<'a>(names: impl IntoIterator<Item = &'a str>) -> anyhow::Result<Self> {
+        let mut program_passes = Vec::new();
+        let mut steps = Vec::new();
+
+        for name in names {
+            if let Some((_, pipeline)) = PIPELINES.iter().find(|(level, _)| *level == name) {
+                let pipeline = Self::parse(pipeline.iter().copied())?;
+                program_passes.extend(pipeline.program_passes);
+                steps.extend(pipeline.steps);
+            } else if let Some(pass) = find_program_pass(name) {
+                program_passes.push(pass);
+            } else if let Some(group) = name.strip_suffix('+') {
+                let passes = group
+                    .split(':')
+                    .map(Self::parse_pass)
+                    .collect::<anyhow::Result<_>>()?;
+                steps.push(Step::Fixpoint(passes));
+            } else {
+                steps.push(Step::Pass(Self::parse_pass(name)?));
+            }
+        }
+
+        Ok(Self {
+            program_passes,
+            steps,
+        })
+    }
+
+    /// Returns the predefined pipeline for the given optimization level.
+    pub fn level(level: &str) -> anyhow::Result<Self> {
+        Self::parse([level])
+    }
+
+    fn parse_pass(name: &str) -> anyhow::Result<&'static Pass> {
+        if find_program_pass(name).is_some() {
+            bail!("`{name}` runs on the whole program and cannot be part of a `+` group");
+        }
+        let Some(pass) = find_pass(name) else {
+            bail!(
+                "Unknown optimization `{name}`\n  available passes: {}\n  predefined pipelines: {}\n  append `+` to repeat a pass (or `a:b` group) until stable",
+                available_passes(),
+                PIPELINES.map(|(level, _)| format!("-O{level}")).join(", "),
+            );
+        };
+        Ok(pass)
+    }
+}
+
+/// Comma separated list of all pass names.
+pub fn available_passes() -> String {
+    PASSES
+        .iter()
+        .map(|p| p.name)
+        .chain(PROGRAM_PASSES.iter().map(|p| p.name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Runs the whole-program passes of the pipeline.
+pub fn run_program_passes(
+    pipeline: &Pipeline,
+    tac: &mut Tac,
+    symbol_table: &Rc<RefCell<SymbolTable>>,
+) -> anyhow::Result<()> {
+    for pass in &pipeline.program_passes {
+        eprintln!("{}", format!(">>> {}", pass.description).green());
+        (pass.run)(tac, symbol_table)?;
+        eprintln!();
+    }
+    Ok(())
+}
+
+/// Everything a pass can access while it runs on a procedure.
+pub struct PassContext<'a> {
+    pub graph: &'a mut BlockGraph,
+    pub symbol_table: &'a Rc<RefCell<SymbolTable>>,
+    pub proc_name: &'a str,
+    pub matches: &'a clap::ArgMatches,
+    pub theme: &'a dyn Theme,
+    rch: Option<ReachingDefinitions>,
+    lv: Option<LiveVariables>,
+    gcp: Option<ConstantPropagation>,
+    ranges: Option<RangeAnalysis>,
+}
+
+impl PassContext<'_> {
+    /// Returns a copy of the local symbol table of the current procedure.
+    fn local_table(&self) -> SymbolTable {
+        let Some(Entry::ProcedureEntry(proc_entry)) =
+            self.symbol_table.borrow().lookup(self.proc_name)
+        else {
+            unreachable!()
+        };
+        proc_entry.local_table
+    }
+
+    /// Computes an analysis, unless a valid result is already cached.
+    fn ensure(&mut self, analysis: Analysis) {
+        let local_table = self.local_table();
+        match analysis {
+            Analysis::ReachingDefinitions if self.rch.is_none() => {
+                self.rch = Some(ReachingDefinitions::run(self.graph, &local_table));
+            }
+            Analysis::LiveVariables if self.lv.is_none() => {
+                self.lv = Some(LiveVariables::run(self.graph, &local_table));
+            }
+            Analysis::ConstantPropagation if self.gcp.is_none() => {
+                self.gcp = Some(ConstantPropagation::run(self.graph, &local_table));
+            }
+            Analysis::Ranges if self.ranges.is_none() => {
+                self.ranges = Some(RangeAnalysis::run(self.graph, &local_table));
+            }
+            _ => {}
+        }
+    }
+
+    /// Discards the cached result of an analysis.
+    fn invalidate(&mut self, analysis: Analysis) {
+        match analysis {
+            Analysis::ReachingDefinitions => self.rch = None,
+            Analysis::LiveVariables => self.lv = None,
+            Analysis::ConstantPropagation => self.gcp = None,
+            Analysis::Ranges => self.ranges = None,
+        }
+    }
+}
+
+/// Timing and code size of a single pipeline step.
+struct StepStats {
+    step: String,
+    iterations: usize,
+    time: Duration,
+    quads_before: usize,
+    quads_after: usize,
+    checks_before: usize,
+    checks_after: usize,
+}
+
+/// Runs pipelines on the block graph of a procedure and keeps statistics about them.
+pub struct PassManager<'a> {
+    ctx: PassContext<'a>,
+    stats: Vec<StepStats>,
+}
+
+impl<'a> PassManager<'a> {
+    pub fn new(
+        graph: &'a mut BlockGraph,
+        symbol_table: &'a Rc<RefCell<SymbolTable>>,
+        proc_name: &'a str,
+        matches: &'a clap::ArgMatches,
+        theme: &'a dyn Theme,
+    ) -> Self {
+        Self {
+            ctx: PassContext {
+                graph,
+                symbol_table,
+                proc_name,
+                matches,
+                theme,
+                rch: None,
+                lv: None,
+                gcp: None,
+                ranges: None,
+            },
+            stats: Vec::new(),
+        }
+    }
+
+    /// Runs all steps of the pipeline and prints the statistics afterwards.
+    pub fn run(mut self, pipeline: &Pipeline) -> anyhow::Result<()> {
+        for step in &pipeline.steps {
+            self.run_step(step)?;
+            eprintln!();
+        }
+
+        if !self.stats.is_empty() {
+            self.report();
+        }
+
+        Ok(())
+    }
+
+    fn run_step(&mut self, step: &Step) -> anyhow::Result<()> {
+        let quads_before = self.ctx.graph.quads().count();
+        let checks_before = self.ctx.graph.bounds_checks();
+        let start = Instant::now();
+        let mut iterations = 1;
+
+        match step {
+            Step::Pass(pass) => {
+                eprintln!("{}", format!(">>> {}", pass.description).green());
+                self.run_pass(pass)?;
+            }
+            Step::Fixpoint(passes) => {
+                let descriptions = passes.iter().map(|p| p.description).collect::<Vec<_>>();
+                eprintln!(
+                    "{}",
+                    format!(">>> {} (until stable)", descriptions.join(" + ")).green()
+                );
+                loop {
+                    let code_before = self.ctx.graph.quads().cloned().collect::<Vec<_>>();
+                    for pass in passes {
+                        self.run_pass(pass)?;
+                    }
+                    if code_before.iter().eq(self.ctx.graph.quads())
+                        || iterations == MAX_FIXPOINT_ITERATIONS
+                    {
+                        break;
+                    }
+                    iterations += 1;
+                }
+                eprintln!("    iterations: {iterations}");
+            }
+        }
+
+        self.stats.push(StepStats {
+            step: step.to_string(),
+            iterations,
+            time: start.elapsed(),
+            quads_before,
+            quads_after: self.ctx.graph.quads().count(),
+            checks_before,
+            checks_after: self.ctx.graph.bounds_checks(),
+        });
+
+        Ok(())
+    }
+
+    fn run_pass(&mut self, pass: &Pass) -> anyhow::Result<()> {
+        for &analysis in pass.requires {
+            self.ctx.ensure(analysis);
+        }
+
+        (pass.run)(&mut self.ctx)?;
+
+        for &analysis in pass.invalidates {
+            self.ctx.invalidate(analysis);
+        }
+
+        Ok(())
+    }
+
+    /// Prints per-step timing, the number of quadruples before and after each step
+    /// and how many bounds checks each step eliminated.
+    fn report(&self) {
+        eprintln!(
+            "{}",
+            format!(">>> Pass Statistics ({}):", self.ctx.proc_name).green()
+        );
+        eprintln!(
+            "{:<16} {:>10} {:>12} {:>8} {:>8} {:>8}",
+            "Pass", "Iterations", "Time", "Quads", "Delta", "Checks"
+        );
+        for s in &self.stats {
+            #[expect(clippy::cast_possible_wrap)]
+            let delta = s.quads_after as isize - s.quads_before as isize;
+            #[expect(clippy::cast_possible_wrap)]
+            let checks = s.checks_after as isize - s.checks_before as isize;
+            eprintln!(
+                "{:<16} {:>10} {:>12} {:>8} {:>+8} {:>+8}",
+                s.step,
+                s.iterations,
+                format!("{:.2?}", s.time),
+                s.quads_after,
+                delta,
+                checks,
+            );
+        }
+        let (first, last) = (&self.stats[0], &self.stats[self.stats.len() - 1]);
+        eprintln!(
+            "bounds checks eliminated: {} of {}",
+            first.checks_before.saturating_sub(last.checks_after),
+            first.checks_before,
+        );
+        eprintln!();
+    }
+}
@@ -0,0 +1,509 @@
+use std::fmt::{self, Debug};
+
+use bitvec::vec::BitVec;
+use colored::Colorize;
+
+use crate::{
+    base_blocks::{BlockContent, BlockGraph, BlockId},
+    code_gen::quadrupel::{Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar},
+    optimizations::worklist::{self, GetVarIdx, Lattice, LatticeJoinAssignCopy, Worklist},
+    table::symbol_table::SymbolTable,
+};
+
+use self::Interval::{Empty, Range};
+
+/// Bound representing negative infinity.
+pub const NEG_INF: i64 = i64::MIN;
+/// Bound representing positive infinity.
+pub const POS_INF: i64 = i64::MAX;
+
+/// Range of values an integer variable can have at a program point.
+/// Bounds outside of `i32` are treated as infinite. Arithmetic wraps around on
+/// overflow like the target machine, so a result that may overflow is `TOP`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    /// No value: not yet reached or unreachable
+    Empty,
+    /// All values between both bounds (inclusive)
+    Range(i64, i64),
+}
+
+impl Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound = |b: i64| match b {
+            NEG_INF => "-∞".to_string(),
+            POS_INF => "∞".to_string(),
+            b => b.to_string(),
+        };
+        match self {
+            Empty => write!(f, "{}", "⊥".red()),
+            Range(NEG_INF, POS_INF) => write!(f, "{}", "⊤".blue()),
+            Range(lo, hi) if lo == hi => write!(f, "{}", format!("[{lo}]").green()),
+            Range(lo, hi) => write!(f, "{}", format!("[{},{}]", bound(*lo), bound(*hi)).green()),
+        }
+    }
+}
+
+impl Lattice for Interval {
+    fn init(_: usize) -> Self {
+        Empty
+    }
+
+    // Intersection of both ranges
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Empty, _) | (_, Empty) => Empty,
+            (Range(a, b), Range(c, d)) => Self::new(*a.max(c), *b.min(d)),
+        }
+    }
+
+    // Smallest range containing both ranges
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Empty, i) | (i, Empty) => *i,
+            (Range(a, b), Range(c, d)) => Self::new(*a.min(c), *b.max(d)),
+        }
+    }
+
+    // Bounds that are still growing are moved to infinity
+    fn widen(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Empty, i) | (i, Empty) => *i,
+            (Range(a, b), Range(c, d)) => Range(
+                if c < a { NEG_INF } else { *a },
+                if d > b { POS_INF } else { *b },
+            ),
+        }
+    }
+
+    // Only infinite bounds are refined again
+    fn narrow(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Empty, _) | (_, Empty) => Empty,
+            (Range(a, b), Range(c, d)) => Self::new(
+                if *a == NEG_INF { *c } else { *a },
+                if *b == POS_INF { *d } else { *b },
+            ),
+        }
+    }
+}
+
+impl LatticeJoinAssignCopy for Interval {}
+
+impl Interval {
+    /// The range of all integers.
+    pub const TOP: Self = Range(NEG_INF, POS_INF);
+
+    /// Creates a range, bounds outside of `i32` become infinite.
+    pub fn new(lo: i64, hi: i64) -> Self {
+        let clamp = |b: i64| match b {
+            b if b < i64::from(i32::MIN) => NEG_INF,
+            b if b > i64::from(i32::MAX) => POS_INF,
+            b => b,
+        };
+
+        if lo > hi {
+            Empty
+        } else {
+            Range(clamp(lo), clamp(hi))
+        }
+    }
+
+    /// A range containing a single value.
+    pub fn constant(c: i32) -> Self {
+        Range(i64::from(c), i64::from(c))
+    }
+
+    /// Checks whether the range contains the given value.
+    pub const fn contains(self, value: i64) -> bool {
+        matches!(self, Range(lo, hi) if lo <= value && value <= hi)
+    }
+
+    fn add(self, other: Self) -> Self {
+        self.combine(other, |a, b| match (a, b) {
+            (NEG_INF, _) | (_, NEG_INF) => NEG_INF,
+            (POS_INF, _) | (_, POS_INF) => POS_INF,
+            (a, b) => a + b,
+        })
+    }
+
+    fn neg(self) -> Self {
+        let neg = |b: i64| match b {
+            NEG_INF => POS_INF,
+            POS_INF => NEG_INF,
+            b => -b,
+        };
+        match self {
+            Empty => Empty,
+            // The smallest `i32` is its own negation
+            Range(lo, _) if to_i32_bound(lo) == i64::from(i32::MIN) => Self::TOP,
+            Range(lo, hi) => Self::new(neg(hi), neg(lo)),
+        }
+    }
+
+    fn mul(self, other: Self) -> Self {
+        self.combine(other, |a, b| match (a, b) {
+            (0, _) | (_, 0) => 0,
+            (NEG_INF | POS_INF, _) | (_, NEG_INF | POS_INF) => {
+                if (a < 0) == (b < 0) {
+                    POS_INF
+                } else {
+                    NEG_INF
+                }
+            }
+            (a, b) => a * b,
+        })
+    }
+
+    fn div(self, other: Self) -> Self {
+        // Division by zero is possible, the result is unknown
+        if other.contains(0) {
+            return if matches!(self, Empty) || matches!(other, Empty) {
+                Empty
+            } else {
+                Self::TOP
+            };
+        }
+        self.combine(other, |a, b| match (a, b) {
+            (NEG_INF | POS_INF, _) => {
+                if (a < 0) == (b < 0) {
+                    POS_INF
+                } else {
+                    NEG_INF
+                }
+            }
+            (_, NEG_INF | POS_INF) => 0,
+            (a, b) => a / b,
+        })
+    }
+
+    // The remainder is smaller than the divisor and has the sign of the dividend
+    fn rem(self, other: Self) -> Self {
+        let (Range(a, b), Range(c, d)) = (self, other) else {
+            return Empty;
+        };
+        let max = i64::try_from(c.unsigned_abs().max(d.unsigned_abs())).map_or(POS_INF, |m| m - 1);
+        Self::new(
+            if a < 0 { a.max(-max) } else { 0 },
+            if b > 0 { b.min(max) } else { 0 },
+        )
+    }
+
+    /// Applies a monotone operation to all combinations of bounds.
+    /// The result is `TOP` if the operation overflows for any values of the ranges,
+    /// which is the case if it overflows for their limits as `i32`.
+    fn combine(self, other: Self, op: impl Fn(i64, i64) -> i64) -> Self {
+        match (self, other) {
+            (Empty, _) | (_, Empty) => Empty,
+            (Range(a, b), Range(c, d)) => {
+                let [a32, b32, c32, d32] = [a, b, c, d].map(to_i32_bound);
+                let limits = [op(a32, c32), op(a32, d32), op(b32, c32), op(b32, d32)];
+                if limits
+                    .iter()
+                    .any(|&l| l < i64::from(i32::MIN) || l > i64::from(i32::MAX))
+                {
+                    return Self::TOP;
+                }
+
+                let corners = [op(a, c), op(a, d), op(b, c), op(b, d)];
+                Self::new(
+                    *corners.iter().min().unwrap(),
+                    *corners.iter().max().unwrap(),
+                )
+            }
+        }
+    }
+
+    /// Restricts both ranges to the values for which `self op other` holds.
+    fn refine(self, op: QuadrupelOp, other: Self) -> (Self, Self) {
+        let (Range(a, b), Range(c, d)) = (self, other) else {
+            return (Empty, Empty);
+        };
+        let dec = |b: i64| {
+            if b == NEG_INF || b == POS_INF {
+                b
+            } else {
+                b - 1
+            }
+        };
+        let inc = |b: i64| {
+            if b == NEG_INF || b == POS_INF {
+                b
+            } else {
+                b + 1
+            }
+        };
+
+        match op {
+            QuadrupelOp::Lst => (
+                self.meet(&Range(NEG_INF, dec(d))),
+                other.meet(&Range(inc(a), POS_INF)),
+            ),
+            QuadrupelOp::Lse => (
+                self.meet(&Range(NEG_INF, d)),
+                other.meet(&Range(a, POS_INF)),
+            ),
+            QuadrupelOp::Grt => {
+                let (other, this) = other.refine(QuadrupelOp::Lst, self);
+                (this, other)
+            }
+            QuadrupelOp::Gre => {
+                let (other, this) = other.refine(QuadrupelOp::Lse, self);
+                (this, other)
+            }
+            QuadrupelOp::Equ => (self.meet(&other), self.meet(&other)),
+            QuadrupelOp::Neq => {
+                // Only a single excluded value at a bound can be removed
+                let exclude = |lo: i64, hi: i64, c: i64| {
+                    Self::new(
+                        if lo == c { lo + 1 } else { lo },
+                        if hi == c { hi - 1 } else { hi },
+                    )
+                };
+                (
+                    if c == d { exclude(a, b, c) } else { self },
+                    if a == b { exclude(c, d, a) } else { other },
+                )
+            }
+            _ => unreachable!("{op:?} is not a relational operator"),
+        }
+    }
+}
+
+/// The `i32` value of a bound, infinite bounds are the limits of `i32`.
+fn to_i32_bound(b: i64) -> i64 {
+    b.clamp(i64::from(i32::MIN), i64::from(i32::MAX))
+}
+
+/// Interval analysis: the range of every integer variable at block entry and exit.
+pub struct RangeAnalysis {
+    pub vars: Vec<QuadrupelVar>,
+    /// Ranges assigned by each block, independent of its input
+    pub gens: Vec<Vec<Interval>>,
+    /// Variables that are not assigned by each block
+    pub prsv: Vec<Vec<Interval>>,
+    pub r#in: Vec<Vec<Interval>>,
+    pub out: Vec<Vec<Interval>>,
+    /// `param` quads whose argument may be written by the called procedure
+    param_writes: Vec<BitVec>,
+}
+
+impl Worklist for RangeAnalysis {
+    type Lattice = Vec<Interval>;
+    type D = QuadrupelVar;
+
+    const EDGE_DIRECTION: worklist::EdgeDirection = worklist::EdgeDirection::Forward;
+    const NARROWING_ITERATIONS: usize = 2;
+
+    fn init(graph: &BlockGraph, local_table: &SymbolTable) -> Self {
+        let defs = graph.definitions(local_table);
+        let mut vars = defs.iter().map(|d| d.var.clone()).collect::<Vec<_>>();
+        vars.sort_by_key(QuadrupelVar::to_identifier);
+        vars.dedup();
+
+        let symbol_table = local_table.upper_level();
+        let param_writes = graph
+            .blocks
+            .iter()
+            .map(|b| match &b.content {
+                BlockContent::Code(quads) => quads
+                    .iter()
+                    .enumerate()
+                    .map(|(i, q)| {
+                        q.op == QuadrupelOp::Param
+                            && Quadrupel::find_param_access(quads, i, &symbol_table.borrow())
+                                .may_write
+                    })
+                    .collect(),
+                BlockContent::Start | BlockContent::Stop => BitVec::new(),
+            })
+            .collect();
+
+        let mut analysis = Self {
+            gens: Vec::new(),
+            prsv: Vec::new(),
+            r#in: Self::init_in_out(graph, &vars),
+            out: Self::init_in_out(graph, &vars),
+            param_writes,
+            vars,
+        };
+
+        for block_id in 0..graph.blocks.len() {
+            let assigned = analysis
+                .vars
+                .iter()
+                .map(|v| defs.iter().any(|d| d.block_id == block_id && d.var == *v))
+                .collect::<BitVec>();
+            let top = vec![Interval::TOP; analysis.vars.len()];
+            let gen_all = analysis.transfer(graph, block_id, &top);
+
+            analysis.gens.push(
+                gen_all
+                    .iter()
+                    .zip(assigned.iter())
+                    .map(|(i, a)| if *a { *i } else { Empty })
+                    .collect(),
+            );
+            analysis.prsv.push(
+                assigned
+                    .iter()
+                    .map(|a| if *a { Empty } else { Interval::TOP })
+                    .collect(),
+            );
+        }
+
+        analysis
+    }
+
+    fn state(&mut self) -> worklist::State<'_, Self> {
+        worklist::State::<Self> {
+            block_info_a: &mut self.gens,
+            block_info_b: &mut self.prsv,
+            input: &mut self.r#in,
+            output: &mut self.out,
+        }
+    }
+
+    // Evaluates the quads of the block with interval arithmetic
+    fn transfer(
+        &mut self,
+        graph: &BlockGraph,
+        node: BlockId,
+        input: &Self::Lattice,
+    ) -> Self::Lattice {
+        match &graph.blocks[node].content {
+            // Parameters and local variables are unknown at procedure entry
+            BlockContent::Start => vec![Interval::TOP; self.vars.len()],
+            BlockContent::Stop => input.clone(),
+            // Unreachable code does not change anything
+            BlockContent::Code(_) if input.iter().all(|i| *i == Empty) => input.clone(),
+            BlockContent::Code(quads) => {
+                let mut state = input.clone();
+                for (i, quad) in quads.iter().enumerate() {
+                    self.eval_quad(quad, self.param_writes[node][i], &mut state);
+                }
+                state
+            }
+        }
+    }
+
+    // Conditional jumps restrict the ranges of their operands on both outgoing edges
+    fn edge(
+        &mut self,
+        graph: &BlockGraph,
+        from: BlockId,
+        to: BlockId,
+        output: &Self::Lattice,
+    ) -> Self::Lattice {
+        let BlockContent::Code(quads) = &graph.blocks[from].content else {
+            return output.clone();
+        };
+        let Some(
+            quad @ Quadrupel {
+                result: QuadrupelResult::Label(label),
+                ..
+            },
+        ) = quads.last().filter(|q| q.op.is_relop())
+        else {
+            return output.clone();
+        };
+        // Both edges lead to the same block
+        if graph.edges[from].len() < 2 {
+            return output.clone();
+        }
+
+        let op = if graph.label_to_id.get(label) == Some(&to) {
+            quad.op
+        } else {
+            quad.op.inv()
+        };
+
+        let lhs = self.eval_arg(&quad.arg1, output);
+        let rhs = self.eval_arg(&quad.arg2, output);
+        let (lhs, rhs) = lhs.refine(op, rhs);
+
+        // The edge can never be taken
+        if lhs == Empty || rhs == Empty {
+            return vec![Empty; output.len()];
+        }
+
+        let mut state = output.clone();
+        for (arg, range) in [(&quad.arg1, lhs), (&quad.arg2, rhs)] {
+            if let QuadrupelArg::Var(var) = arg
+                && let Some(idx) = self.get_var_idx(var)
+            {
+                state[idx] = range;
+            }
+        }
+        state
+    }
+}
+
+impl GetVarIdx<QuadrupelVar> for RangeAnalysis {
+    fn vars(&self) -> &[QuadrupelVar] {
+        &self.vars
+    }
+}
+
+impl RangeAnalysis {
+    /// Range of an argument in the given state.
+    pub fn eval_arg(&self, arg: &QuadrupelArg, state: &[Interval]) -> Interval {
+        match arg {
+            QuadrupelArg::Var(var) => self
+                .get_var_idx(var)
+                .map_or(Interval::TOP, |idx| state[idx]),
+            QuadrupelArg::Const(c) => Interval::constant(*c),
+            QuadrupelArg::Empty => Empty,
+        }
+    }
+
+    /// Ranges right before each quad of the block.
+    pub fn ranges_before(&self, graph: &BlockGraph, node: BlockId) -> Vec<Vec<Interval>> {
+        let BlockContent::Code(quads) = &graph.blocks[node].content else {
+            return Vec::new();
+        };
+        let mut state = self.r#in[node].clone();
+        quads
+            .iter()
+            .enumerate()
+            .map(|(i, quad)| {
+                let before = state.clone();
+                self.eval_quad(quad, self.param_writes[node][i], &mut state);
+                before
+            })
+            .collect()
+    }
+
+    /// Updates the state with the effect of a single quad.
+    pub fn eval_quad(&self, quad: &Quadrupel, param_may_write: bool, state: &mut [Interval]) {
+        match (&quad.op, &quad.result) {
+            // Only single elements of arrays are written
+            (QuadrupelOp::ArrayStore, _) => {}
+            (op, QuadrupelResult::Var(var)) => {
+                let arg1 = self.eval_arg(&quad.arg1, state);
+                let arg2 = self.eval_arg(&quad.arg2, state);
+                let range = match op {
+                    QuadrupelOp::Assign => arg1,
+                    QuadrupelOp::Neg => arg1.neg(),
+                    QuadrupelOp::Add => arg1.add(arg2),
+                    QuadrupelOp::Sub => arg1.add(arg2.neg()),
+                    QuadrupelOp::Mul => arg1.mul(arg2),
+                    QuadrupelOp::Div => arg1.div(arg2),
+                    QuadrupelOp::Mod => arg1.rem(arg2),
+                    _ => Interval::TOP,
+                };
+                if let Some(idx) = self.get_var_idx(var) {
+                    state[idx] = range;
+                }
+            }
+            (QuadrupelOp::Param, _) if param_may_write => {
+                if let QuadrupelArg::Var(var) = &quad.arg1
+                    && let Some(idx) = self.get_var_idx(var)
+                {
+                    state[idx] = Interval::TOP;
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -3,7 +3,7 @@ use bitvec::vec::BitVec;
 use crate::base_blocks::{Block, BlockGraph};
 use crate::code_gen::quadrupel::{Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelVar};
 use crate::optimizations::worklist::{self, Definition, Worklist};
-use crate::table::entry::{Entry, Parameter};
+use crate::table::entry::{Entry, Parameter, ProcedureEntry};
 use crate::table::symbol_table::SymbolTable;
 
 /// Struct representing the Reaching Definitions dataflow analysis.
@@ -86,6 +86,17 @@ impl Quadrupel {
         quad_index_param: usize,
         symbol_table: &SymbolTable,
     ) -> Parameter {
+        let (call_proc, n) = Self::find_param_call(quads, quad_index_param, symbol_table);
+        call_proc.parameters.into_iter().nth(n).unwrap()
+    }
+
+    /// Given the index of a `param` quad, finds the called procedure and the
+    /// position of the corresponding parameter in its declaration.
+    pub(super) fn find_param_call(
+        quads: &[Self],
+        quad_index_param: usize,
+        symbol_table: &SymbolTable,
+    ) -> (ProcedureEntry, usize) {
         // Find the nth parameter by counting from the param to the corresponding call.
         let (n, call) = quads
             .iter()
@@ -105,7 +116,8 @@ impl Quadrupel {
             unreachable!()
         };
 
-        // Return the nth parameter (counted in reverse).
-        call_proc.parameters.into_iter().rev().nth(n - 1).unwrap()
+        // The nth parameter is counted in reverse.
+        let position = call_proc.parameters.len() - n;
+        (call_proc, position)
     }
 }
@@ -3,7 +3,6 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt,
-    ops::RangeBounds,
 };
 
 use crate::base_blocks::{BlockGraph, BlockId};
@@ -32,17 +31,13 @@ impl BlockGraph {
     /// Finds and stores SCCs in the control flow graph.
     pub fn tarjan(&mut self) -> &Vec<Scc> {
         let mut sccs = Vec::new();
-        self.tarjan_internal(&mut sccs, 0..self.blocks.len(), None);
+        let all_blocks = (0..self.blocks.len()).collect::<Vec<_>>();
+        self.tarjan_internal(&mut sccs, &all_blocks, None);
         self.sccs.insert(sccs)
     }
 
-    /// Runs Tarjan's algorithm on a subgraph, defined by a range.
-    fn tarjan_internal(
-        &self,
-        sccs: &mut Vec<Scc>,
-        subgraph: impl RangeBounds<BlockId> + Clone + IntoIterator<Item = BlockId>,
-        parent: Option<usize>,
-    ) {
+    /// Runs Tarjan's algorithm on a subgraph, defined by its (sorted) blocks.
+    fn tarjan_internal(&self, sccs: &mut Vec<Scc>, subgraph: &[BlockId], parent: Option<usize>) {
         let mut index = 0;
         let mut index_map = HashMap::new(); // Maps node to its index in DFS traversal
         let mut lowlink_map = HashMap::new(); // Maps node to the lowest index reachable
@@ -50,7 +45,7 @@ impl BlockGraph {
         let mut stack = Vec::new(); // DFS stack
 
         // Start DFS from each unvisited node in the subgraph
-        for id in subgraph.clone() {
+        for &id in subgraph {
             if !index_map.contains_key(&id) {
                 self.strong_connect(
                     id,
@@ -60,7 +55,7 @@ impl BlockGraph {
                     &mut on_stack,
                     &mut stack,
                     sccs,
-                    &subgraph,
+                    subgraph,
                     parent,
                 );
             }
@@ -77,7 +72,7 @@ impl BlockGraph {
         on_stack: &mut HashSet<BlockId>,
         stack: &mut Vec<BlockId>,
         sccs: &mut Vec<Scc>,
-        subgraph: &impl RangeBounds<BlockId>,
+        subgraph: &[BlockId],
         parent: Option<usize>,
     ) {
         // Assign DFS index and lowlink
@@ -127,15 +122,15 @@ impl BlockGraph {
             if component.len() > 1 {
                 component.reverse();
                 component.sort_unstable();
-                let min = *component.first().unwrap();
-                let max = *component.last().unwrap();
+                // The loop header is the block with the smallest id, blocks inserted by
+                // optimizations (e.g. preheaders) are appended at the end of the graph.
+                let inner = component[1..].to_vec();
 
                 sccs.push(Scc::new(component, parent));
                 let component_idx = sccs.len() - 1;
 
-                // Recursively apply Tarjan's algorithm to the inner blocks between min and max
-                #[expect(clippy::range_minus_one)]
-                self.tarjan_internal(sccs, (min + 1)..=(max - 1), Some(component_idx));
+                // Recursively apply Tarjan's algorithm to the loop without its header
+                self.tarjan_internal(sccs, &inner, Some(component_idx));
 
                 // Register child SCCs
                 let sccs_len = sccs.len();
@@ -0,0 +1,218 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Display},
+    rc::Rc,
+};
+
+use colored::Colorize;
+
+use crate::{
+    base_blocks::{BlockContent, BlockGraph, BlockId},
+    code_gen::{
+        Tac,
+        quadrupel::{Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar},
+    },
+    diagnostics::Span,
+    optimizations::{reaching_expressions::ReachingDefinitions, worklist::Worklist},
+    table::{
+        entry::{Entry, ProcedureEntry, VariableEntry},
+        symbol_table::SymbolTable,
+        types::Type,
+    },
+};
+
+/// How certain it is that a variable is read before it was assigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Uninitialized {
+    /// No assignment reaches the use
+    Always,
+    /// The use is reached by an assignment on some paths only
+    Maybe,
+}
+
+impl Display for Uninitialized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Always => write!(f, "{}", "always".red()),
+            Self::Maybe => write!(f, "{}", "maybe".yellow()),
+        }
+    }
+}
+
+/// A use of a local variable that is reached by its implicit definition in the start block.
+#[derive(Debug, Clone)]
+pub struct UninitializedUse {
+    pub block_id: BlockId,
+    /// Position of the quad in its block.
+    pub quad_idx: usize,
+    pub var: String,
+    pub kind: Uninitialized,
+    pub span: Option<Span>,
+}
+
+/// All uses of uninitialized variables in a procedure.
+#[derive(Debug, Clone, Default)]
+pub struct UninitializedVariables {
+    pub uses: Vec<UninitializedUse>,
+}
+
+impl UninitializedVariables {
+    /// Finds all uses of local variables that may be reached by the start block.
+    /// Parameters are initialized by the caller and temporaries are always assigned before use.
+    pub fn new(
+        graph: &BlockGraph,
+        reaching: &ReachingDefinitions,
+        proc_entry: &ProcedureEntry,
+    ) -> Self {
+        let local_table = &proc_entry.local_table;
+        let symbol_table = local_table.upper_level();
+        let is_local = |var: &QuadrupelVar| match var {
+            QuadrupelVar::Spl(name) => !proc_entry.parameters.iter().any(|p| p.name == *name),
+            QuadrupelVar::Tmp(_) => false,
+        };
+        let is_composite = |var: &QuadrupelVar| {
+            matches!(
+                local_table.entries.get(&var.to_identifier()),
+                Some(Entry::VariableEntry(VariableEntry {
+                    typ: Type::ArrayType(_) | Type::RecordType(_),
+                    ..
+                }))
+            )
+        };
+        let is_start =
+            |block_id: BlockId| matches!(graph.blocks[block_id].content, BlockContent::Start);
+
+        let mut uses = Vec::new();
+
+        for (block_id, block) in graph.blocks.iter().enumerate() {
+            let BlockContent::Code(quads) = &block.content else {
+                continue;
+            };
+            let mut reaching_defs = reaching.rchin[block_id].clone();
+
+            for (quad_idx, quad) in quads.iter().enumerate() {
+                let (read, by_reference) = match quad.op {
+                    // Reference arguments are only read if the called procedure may read them
+                    QuadrupelOp::Param => {
+                        let access =
+                            Quadrupel::find_param_access(quads, quad_idx, &symbol_table.borrow());
+                        (access.may_read, access.is_reference)
+                    }
+                    // Taking an element as reference argument does not read it
+                    QuadrupelOp::ArrayLoad => (!is_reference(&quad.result, local_table), false),
+                    _ => (true, false),
+                };
+
+                let mut vars = [&quad.arg1, &quad.arg2]
+                    .into_iter()
+                    .filter_map(|arg| match arg {
+                        QuadrupelArg::Var(var) if read && is_local(var) => Some(var),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                vars.dedup();
+
+                for var in vars {
+                    let (from_start, assigned) = reaching_defs
+                        .iter_ones()
+                        .map(|d| &reaching.defs[d])
+                        .filter(|d| d.var == *var)
+                        .fold((false, false), |(start, assigned), d| {
+                            if is_start(d.block_id) {
+                                (true, assigned)
+                            } else {
+                                (start, true)
+                            }
+                        });
+
+                    // The called procedure might not read the argument at all
+                    let kind = if assigned || by_reference {
+                        Uninitialized::Maybe
+                    } else {
+                        Uninitialized::Always
+                    };
+
+                    // Arrays and records are usually initialized element by element,
+                    // which cannot be told apart from a partial initialization.
+                    if from_start && (kind == Uninitialized::Always || !is_composite(var)) {
+                        uses.push(UninitializedUse {
+                            block_id,
+                            quad_idx,
+                            var: var.to_identifier(),
+                            kind,
+                            span: quad.span,
+                        });
+                    }
+                }
+
+                // Definitions of this quad replace all other definitions of the variable
+                for (def_idx, def) in reaching.defs.iter().enumerate() {
+                    if def.block_id == block_id && def.quad_id == quad_idx {
+                        for (other_idx, other) in reaching.defs.iter().enumerate() {
+                            if other.var == def.var {
+                                reaching_defs.set(other_idx, false);
+                            }
+                        }
+                        reaching_defs.set(def_idx, true);
+                    }
+                }
+            }
+        }
+
+        Self { uses }
+    }
+
+    /// Checks all procedures of the program, sorted by name.
+    pub fn check_program(
+        tac: &Tac,
+        symbol_table: &Rc<RefCell<SymbolTable>>,
+    ) -> Vec<(String, Self)> {
+        let mut procs = tac.proc_table.iter().collect::<Vec<_>>();
+        procs.sort_by_key(|(name, _)| *name);
+
+        procs
+            .into_iter()
+            .map(|(name, quads)| {
+                let Some(Entry::ProcedureEntry(proc_entry)) = symbol_table.borrow().lookup(name)
+                else {
+                    unreachable!()
+                };
+                let graph = BlockGraph::from_tac(quads);
+                let reaching = ReachingDefinitions::run(&graph, &proc_entry.local_table);
+                (name.clone(), Self::new(&graph, &reaching, &proc_entry))
+            })
+            .collect()
+    }
+}
+
+/// Whether the result of a quad is a reference temporary.
+fn is_reference(result: &QuadrupelResult, local_table: &SymbolTable) -> bool {
+    let QuadrupelResult::Var(var) = result else {
+        return false;
+    };
+    matches!(
+        local_table.entries.get(&var.to_identifier()),
+        Some(Entry::VariableEntry(VariableEntry {
+            is_reference: true,
+            ..
+        }))
+    )
+}
+
+impl Display for UninitializedVariables {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5} {:>5} {:<10} Uninitialized",
+            "Block", "Quad", "Variable"
+        )?;
+        for u in &self.uses {
+            writeln!(
+                f,
+                "{:>5} {:>5} {:<10} {}",
+                u.block_id, u.quad_idx, u.var, u.kind
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt::Write;
 
 use bitvec::vec::BitVec;
 
-use crate::base_blocks::{Block, BlockContent, BlockGraph};
+use crate::base_blocks::{Block, BlockContent, BlockGraph, BlockId};
 use crate::cli::FmtTable;
 use crate::code_gen::quadrupel::{Quadrupel, QuadrupelResult, QuadrupelVar, quad, quad_match};
 use crate::table::entry::Entry;
@@ -21,6 +21,17 @@ pub trait Lattice: LatticeJoinAssign + Clone + Eq {
     fn init(len: usize) -> Self;
     fn meet(&self, other: &Self) -> Self;
     fn join(&self, other: &Self) -> Self;
+
+    /// Widening, needed to reach a fixpoint for lattices of infinite height.
+    /// Lattices of finite height simply join.
+    fn widen(&self, other: &Self) -> Self {
+        self.join(other)
+    }
+
+    /// Narrowing, refines the (widened) fixpoint again. Keeps the old value by default.
+    fn narrow(&self, _other: &Self) -> Self {
+        self.clone()
+    }
 }
 
 /// Defines a trait for mutating a lattice via join.
@@ -47,6 +58,8 @@ pub trait Worklist {
     type D;
 
     const EDGE_DIRECTION: self::EdgeDirection;
+    /// Number of descending iterations after the (widened) fixpoint was reached.
+    const NARROWING_ITERATIONS: usize = 0;
 
     fn init(graph: &BlockGraph, local_table: &SymbolTable) -> Self;
     fn meet_override(lhs: &Self::Lattice, rhs: &Self::Lattice) -> Self::Lattice {
@@ -54,6 +67,28 @@ pub trait Worklist {
     }
     fn state(&mut self) -> State<'_, Self>;
 
+    /// Computes the output of a block from its input, `(in ⊓ b) ⊔ a` by default.
+    fn transfer(
+        &mut self,
+        _graph: &BlockGraph,
+        node: BlockId,
+        input: &Self::Lattice,
+    ) -> Self::Lattice {
+        let state = self.state();
+        Self::meet_override(input, &state.block_info_b[node]).join(&state.block_info_a[node])
+    }
+
+    /// Adjusts the output of a block along a single edge, e.g. for conditional jumps.
+    fn edge(
+        &mut self,
+        _graph: &BlockGraph,
+        _from: BlockId,
+        _to: BlockId,
+        output: &Self::Lattice,
+    ) -> Self::Lattice {
+        output.clone()
+    }
+
     fn run(graph: &BlockGraph, local_table: &SymbolTable) -> Self
     where
         Self: Sized,
@@ -69,7 +104,6 @@ impl BlockGraph {
     /// Executes a generic worklist algorithm based on the selected edge direction.
     fn run_worklist<W: Worklist>(&self, local_table: &SymbolTable) -> W {
         let mut state_res = W::init(self, local_table);
-        let mut state = state_res.state();
 
         let mut edges_both = [self.edges(), &self.edges_prev()];
         if matches!(W::EDGE_DIRECTION, self::EdgeDirection::Backward) {
@@ -77,34 +111,79 @@ impl BlockGraph {
         }
         let [edges_forward, edges_backward] = edges_both;
 
-        if matches!(W::EDGE_DIRECTION, self::EdgeDirection::Backward) {
-            std::mem::swap(&mut state.input, &mut state.output);
-        }
+        // Every cycle has an edge to an earlier block, widening there is enough to terminate
+        let widen_at = edges_backward
+            .iter()
+            .enumerate()
+            .map(|(node, preds)| preds.iter().any(|&p| p >= node))
+            .collect::<Vec<_>>();
 
-        let mut changed = match W::EDGE_DIRECTION {
-            EdgeDirection::Forward => (0..self.blocks.len()).collect::<VecDeque<_>>(),
-            EdgeDirection::Backward => (0..self.blocks.len()).rev().collect::<VecDeque<_>>(),
-        };
+        let mut changed = visit_order::<W>(self.blocks.len())
+            .into_iter()
+            .collect::<VecDeque<_>>();
 
         while let Some(node) = changed.pop_front() {
+            let mut joined = Self::input(&mut state_res)[node].clone();
             for &p in &edges_backward[node] {
-                state.input[node].join_assign(&state.output[p]);
+                let output = Self::output(&mut state_res)[p].clone();
+                joined.join_assign(&state_res.edge(self, p, node, &output));
             }
 
-            let output_first_part = W::meet_override(&state.input[node], &state.block_info_b[node]);
+            let input = if widen_at[node] {
+                Self::input(&mut state_res)[node].widen(&joined)
+            } else {
+                joined
+            };
+            Self::input(&mut state_res)[node] = input.clone();
 
-            let output_old = std::mem::replace(
-                &mut state.output[node],
-                output_first_part.join(&state.block_info_a[node]),
-            );
+            let output = state_res.transfer(self, node, &input);
+            let output_old = std::mem::replace(&mut Self::output(&mut state_res)[node], output);
 
-            if state.output[node] != output_old {
+            if Self::output(&mut state_res)[node] != output_old {
                 changed.extend(&edges_forward[node]);
             }
         }
+
+        for _ in 0..W::NARROWING_ITERATIONS {
+            for node in visit_order::<W>(self.blocks.len()) {
+                let Some(narrowed) = edges_backward[node]
+                    .iter()
+                    .map(|&p| {
+                        let output = Self::output(&mut state_res)[p].clone();
+                        state_res.edge(self, p, node, &output)
+                    })
+                    .reduce(|a, b| a.join(&b))
+                else {
+                    continue;
+                };
+
+                let input = Self::input(&mut state_res)[node].narrow(&narrowed);
+                Self::input(&mut state_res)[node] = input.clone();
+                Self::output(&mut state_res)[node] = state_res.transfer(self, node, &input);
+            }
+        }
+
         state_res
     }
 
+    /// The lattice values flowing into the blocks (in analysis direction).
+    fn input<W: Worklist>(state: &mut W) -> &mut [W::Lattice] {
+        let state = state.state();
+        match W::EDGE_DIRECTION {
+            EdgeDirection::Forward => state.input,
+            EdgeDirection::Backward => state.output,
+        }
+    }
+
+    /// The lattice values flowing out of the blocks (in analysis direction).
+    fn output<W: Worklist>(state: &mut W) -> &mut [W::Lattice] {
+        let state = state.state();
+        match W::EDGE_DIRECTION {
+            EdgeDirection::Forward => state.output,
+            EdgeDirection::Backward => state.input,
+        }
+    }
+
     /// Collects all variable definitions for the entire graph.
     pub(super) fn definitions(&self, local_table: &SymbolTable) -> Vec<Definition> {
         (0..self.blocks.len())
@@ -169,9 +248,9 @@ impl Block {
                     var: v.clone(),
                 }),
                 quad_match!((p), (~v), _ => _) => {
-                    let param = Quadrupel::find_param_declaration(quads, i, symbol_table);
+                    let access = Quadrupel::find_param_access(quads, i, symbol_table);
 
-                    param.is_reference.then(|| Definition {
+                    access.may_write.then(|| Definition {
                         block_id,
                         quad_id: i,
                         var: v.clone(),
@@ -183,6 +262,14 @@ impl Block {
     }
 }
 
+/// Order in which the blocks are visited, following the edge direction.
+fn visit_order<W: Worklist>(len: usize) -> Vec<BlockId> {
+    match W::EDGE_DIRECTION {
+        EdgeDirection::Forward => (0..len).collect(),
+        EdgeDirection::Backward => (0..len).rev().collect(),
+    }
+}
+
 /// Represents a definition of a variable (from a specific block and instruction).
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Definition {
@@ -216,7 +303,7 @@ impl From<(&String, &Entry)> for Definition {
         Self {
             block_id: 0,
             quad_id: 0,
-            var: QuadrupelVar::Spl(name.to_string()),
+            var: QuadrupelVar::Spl(name.clone()),
         }
     }
 }
@@ -254,6 +341,20 @@ impl<L: Lattice> Lattice for Vec<L> {
     fn join(&self, other: &Self) -> Self {
         self.iter().zip(other).map(|(s, o)| L::join(s, o)).collect()
     }
+
+    fn widen(&self, other: &Self) -> Self {
+        self.iter()
+            .zip(other)
+            .map(|(s, o)| L::widen(s, o))
+            .collect()
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        self.iter()
+            .zip(other)
+            .map(|(s, o)| L::narrow(s, o))
+            .collect()
+    }
 }
 
 impl<L: Lattice> LatticeJoinAssign for Vec<L> {
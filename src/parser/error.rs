@@ -0,0 +1,122 @@
+use std::fmt;
+
+use crate::diagnostics::Span;
+
+/// A lexical or syntax error.
+#[derive(Debug)]
+pub struct ParseError {
+    pub span: Span,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    /// A character that does not start any token
+    IllegalCharacter(char),
+    /// A `'` that is not followed by a character and another `'`
+    UnterminatedCharacter,
+    /// A `"` without a closing `"` on the same line
+    UnterminatedString,
+    /// A `\` in a string literal that is not followed by `n`, `t`, `"` or `\`
+    UnknownEscape(char),
+    /// An integer literal that does not fit into 32 bits
+    IntegerTooLarge(String),
+    /// An imported file that cannot be read
+    ImportNotFound { path: String, reason: String },
+    /// A file that imports itself, directly or through other files
+    ImportCycle(String),
+    /// A token that does not fit the grammar, located at the furthest position
+    /// the parser could reach
+    Unexpected {
+        /// Everything that would have been accepted at the error
+        expected: Vec<String>,
+        /// The text found at the error, `None` at the end of the input
+        found: Option<String>,
+    },
+}
+
+impl ParseError {
+    /// The exit code of the reference compiler: 99 for lexical, 100 for syntax errors.
+    /// Imports are unknown to the reference compiler, their errors count as syntax errors.
+    pub const fn exit_code(&self) -> i32 {
+        match self.kind {
+            ParseErrorKind::Unexpected { .. }
+            | ParseErrorKind::ImportNotFound { .. }
+            | ParseErrorKind::ImportCycle(_) => 100,
+            _ => 99,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::IllegalCharacter(c) => {
+                write!(f, "illegal character `{}`", c.escape_default())
+            }
+            ParseErrorKind::UnterminatedCharacter => write!(f, "unterminated character literal"),
+            ParseErrorKind::UnknownEscape(c) => {
+                write!(f, "unknown escape sequence `\\{}`", c.escape_default())
+            }
+            ParseErrorKind::UnterminatedString => write!(f, "unterminated string literal"),
+            ParseErrorKind::IntegerTooLarge(literal) => {
+                write!(f, "integer literal `{literal}` is too large")
+            }
+            ParseErrorKind::ImportNotFound { path, reason } => {
+                write!(f, "cannot import `{path}`: {reason}")
+            }
+            ParseErrorKind::ImportCycle(path) => write!(f, "cyclic import of `{path}`"),
+            ParseErrorKind::Unexpected { expected, found } => {
+                fmt_unexpected(f, expected, found.as_deref())
+            }
+        }
+    }
+}
+
+fn fmt_unexpected(
+    f: &mut fmt::Formatter<'_>,
+    expected: &[String],
+    found: Option<&str>,
+) -> fmt::Result {
+    // Binary operators are summarized, they may follow almost every expression
+    let is_operator = |e: &str| {
+        [
+            "`+`", "`-`", "`*`", "`/`", "`%`", "`&`", "`|`", "`^`", "`<<`", "`>>`", "`=`", "`#`",
+            "`<`", "`<=`", "`>`", "`>=`", "`&&`", "`||`",
+        ]
+        .contains(&e)
+    };
+    let mut expected = expected.iter().map(String::as_str).collect::<Vec<_>>();
+    if expected.iter().filter(|e| is_operator(e)).count() > 1 {
+        expected.retain(|e| !is_operator(e));
+        expected.push("an operator");
+    }
+
+    match expected.as_slice() {
+        [] => write!(f, "unexpected ")?,
+        [e] => write!(f, "expected {e}, found ")?,
+        [a, b] => write!(f, "expected {a} or {b}, found ")?,
+        [init @ .., last] => {
+            write!(f, "expected one of {}, or {last}, found ", init.join(", "))?;
+        }
+    }
+    match found {
+        Some(found) => write!(f, "`{found}`"),
+        None => write!(f, "end of file"),
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// All lexical and syntax errors of a program, sorted by position.
+#[derive(Debug)]
+pub struct ParseErrors(pub Vec<ParseError>);
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors = self.0.iter().map(ToString::to_string).collect::<Vec<_>>();
+        write!(f, "{}", errors.join("\n"))
+    }
+}
+
+impl std::error::Error for ParseErrors {}
@@ -0,0 +1,219 @@
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{
+        anychar, char, digit1, hex_digit1, multispace0, not_line_ending, satisfy,
+    },
+    combinator::{recognize, verify},
+    multi::many0,
+    sequence::{delimited, pair, preceded},
+};
+
+use crate::diagnostics::Span;
+
+use super::{
+    error::{ParseError, ParseErrorKind},
+    tokens::{Comment, Token, Tokens},
+};
+
+/// Splits the input into tokens. The last token is always `Eof`.
+/// Lexical errors are collected, the offending characters are skipped
+/// and literals that are too large are read as `0`, so parsing can go on.
+pub fn tokenize(input: &str) -> (Vec<Token>, Vec<ParseError>) {
+    let (tokens, _, errors) = tokenize_with_comments(input);
+    (tokens, errors)
+}
+
+/// Like `tokenize`, but also returns all comments of the input.
+pub fn tokenize_with_comments(input: &str) -> (Vec<Token>, Vec<Comment>, Vec<ParseError>) {
+    let mut lexer = Lexer {
+        text: input,
+        line_starts: std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(i, _)| i + 1))
+            .collect(),
+        tokens: Vec::new(),
+        comments: Vec::new(),
+        errors: Vec::new(),
+    };
+    lexer.run();
+    (lexer.tokens, lexer.comments, lexer.errors)
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+    tokens: Vec<Token>,
+    comments: Vec<Comment>,
+    errors: Vec<ParseError>,
+}
+
+impl Lexer<'_> {
+    fn run(&mut self) {
+        let mut rem = self.text;
+
+        loop {
+            rem = multispace0::<_, nom::error::Error<_>>(rem).map_or(rem, |(rem, _)| rem);
+            let start = self.offset(rem);
+
+            if let Ok((next, text)) = comment(rem) {
+                let span = self.span(start, self.offset(next));
+                self.comments.push(Comment {
+                    text: text.to_string(),
+                    span,
+                });
+                rem = next;
+                continue;
+            }
+
+            let Some(c) = rem.chars().next() else {
+                let end = self.text.len();
+                self.push(Tokens::Eof, end, end);
+                return;
+            };
+
+            if let Ok((next, value)) = alt([hex_num, int]).parse(rem) {
+                let end = self.offset(next);
+                let value = value.unwrap_or_else(|| {
+                    let literal = self.text[start..end].to_string();
+                    self.error(ParseErrorKind::IntegerTooLarge(literal), start, end);
+                    0
+                });
+                self.push(Tokens::Intlit(value), start, end);
+                rem = next;
+            } else if let Ok((next, literal)) = string(rem) {
+                let end = self.offset(next);
+                let text = unescape(literal).unwrap_or_else(|c| {
+                    self.error(ParseErrorKind::UnknownEscape(c), start, end);
+                    literal.to_string()
+                });
+                self.push(Tokens::Strlit(text), start, end);
+                rem = next;
+            } else if let Ok((next, kind)) = alt([newline, character, word]).parse(rem) {
+                self.push(kind, start, self.offset(next));
+                rem = next;
+            } else if let Some((text, kind)) = Tokens::SYMBOLS
+                .iter()
+                .find(|(text, _)| rem.starts_with(text))
+            {
+                self.push(kind.clone(), start, start + text.len());
+                rem = &rem[text.len()..];
+            } else if c == '"' {
+                // The rest of the line belongs to the unterminated string
+                let (next, _) = not_line_ending::<_, nom::error::Error<_>>(rem).unwrap();
+                self.error(ParseErrorKind::UnterminatedString, start, self.offset(next));
+                rem = next;
+            } else {
+                let kind = if c == '\'' {
+                    ParseErrorKind::UnterminatedCharacter
+                } else {
+                    ParseErrorKind::IllegalCharacter(c)
+                };
+                self.error(kind, start, start + c.len_utf8());
+                rem = &rem[c.len_utf8()..];
+            }
+        }
+    }
+
+    const fn offset(&self, rem: &str) -> usize {
+        self.text.len() - rem.len()
+    }
+
+    fn push(&mut self, kind: Tokens, start: usize, end: usize) {
+        let span = self.span(start, end);
+        self.tokens.push(Token { kind, span });
+    }
+
+    fn error(&mut self, kind: ParseErrorKind, start: usize, end: usize) {
+        let span = self.span(start, end);
+        self.errors.push(ParseError { span, kind });
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        let line = self.line_starts.partition_point(|&s| s <= start);
+        let col = self.text[self.line_starts[line - 1]..start].chars().count() + 1;
+        Span {
+            start,
+            end,
+            line,
+            col,
+        }
+    }
+}
+
+fn comment(input: &str) -> IResult<&str, &str> {
+    recognize(pair(tag("//"), not_line_ending)).parse(input)
+}
+
+// Keywords and identifiers
+fn word(input: &str) -> IResult<&str, Tokens> {
+    let (rem, word) = recognize(pair(
+        verify(anychar, |c: &char| c.is_alphabetic() || *c == '_'),
+        many0(satisfy(|c| c.is_alphanumeric() || c == '_')),
+    ))
+    .parse(input)?;
+
+    let kind = Tokens::KEYWORDS
+        .iter()
+        .find(|(keyword, _)| *keyword == word)
+        .map_or_else(|| Tokens::Ident(word.to_string()), |(_, kind)| kind.clone());
+    Ok((rem, kind))
+}
+
+// `None` if the literal does not fit into 32 bits
+fn int(input: &str) -> IResult<&str, Option<i32>> {
+    let res = digit1(input)?;
+    Ok((res.0, res.1.parse::<i32>().ok()))
+}
+
+// Hexadecimal literals may use all 32 bits, `0xFFFFFFFF` is -1
+fn hex_num(input: &str) -> IResult<&str, Option<i32>> {
+    let res = preceded(tag("0x"), hex_digit1).parse(input)?;
+    Ok((
+        res.0,
+        u32::from_str_radix(res.1, 16).ok().map(u32::cast_signed),
+    ))
+}
+
+fn newline(input: &str) -> IResult<&str, Tokens> {
+    let (rem, _) = tag("'\\n'")(input)?;
+    Ok((rem, Tokens::Intlit(10)))
+}
+
+// Strings end on the same line, the text between the quotes is returned as written
+fn string(input: &str) -> IResult<&str, &str> {
+    let escape = recognize(pair(char('\\'), satisfy(|c| c != '\n')));
+    let text = recognize(many0(alt((
+        escape,
+        recognize(satisfy(|c| c != '"' && c != '\\' && c != '\n')),
+    ))));
+    delimited(char('"'), text, char('"')).parse(input)
+}
+
+// Replaces the escape sequences `\n`, `\t`, `\"` and `\\`, or returns the first unknown one
+fn unescape(literal: &str) -> Result<String, char> {
+    let mut chars = literal.chars();
+    let mut text = String::new();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        // `string` only accepts a `\` followed by another character
+        text.push(match chars.next().unwrap() {
+            'n' => '\n',
+            't' => '\t',
+            '"' => '"',
+            '\\' => '\\',
+            c => return Err(c),
+        });
+    }
+    Ok(text)
+}
+
+fn character(input: &str) -> IResult<&str, Tokens> {
+    let (rem, _) = tag("'")(input)?;
+    let res = anychar(rem)?;
+    let (rem, _) = tag("'")(res.0)?;
+    Ok((rem, Tokens::Intlit(u32::from(res.1).cast_signed())))
+}
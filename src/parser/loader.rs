@@ -0,0 +1,121 @@
+#![expect(clippy::linkedlist)]
+
+use std::{
+    collections::{HashSet, LinkedList},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    absyn::absyn::{Definition, Program},
+    diagnostics::{Source, SourceMap, Span},
+};
+
+use super::{
+    error::{ParseError, ParseErrorKind, ParseErrors},
+    parse_everything_else::parse_at,
+};
+
+/// Parses a program together with all files it imports.
+///
+/// Imported paths are relative to the directory of the importing file. Every file
+/// is read once, even if several files import it, and the definitions of imported
+/// files come before the definitions of the importing file. All files are added to
+/// `sources`, the main file first, so the spans of the program refer to them.
+pub fn load(
+    name: &str,
+    text: &'static str,
+    sources: &mut SourceMap<'static>,
+) -> Result<Program, ParseErrors> {
+    let path = Path::new(name);
+    let mut loader = Loader {
+        sources,
+        loaded: HashSet::new(),
+        stack: Vec::new(),
+        definitions: LinkedList::new(),
+        errors: Vec::new(),
+    };
+    loader.load_file(path, canonical(path), text);
+
+    let Loader {
+        definitions,
+        mut errors,
+        ..
+    } = loader;
+    if errors.is_empty() {
+        Ok(Program { definitions })
+    } else {
+        errors.sort_by_key(|e| e.span.start);
+        Err(ParseErrors(errors))
+    }
+}
+
+struct Loader<'s> {
+    sources: &'s mut SourceMap<'static>,
+    /// All files read so far
+    loaded: HashSet<PathBuf>,
+    /// The files whose imports are loaded at the moment, the main file first
+    stack: Vec<PathBuf>,
+    definitions: LinkedList<Box<Definition>>,
+    errors: Vec<ParseError>,
+}
+
+impl Loader<'_> {
+    fn load_file(&mut self, path: &Path, canonical: PathBuf, text: &'static str) {
+        let offset = self.sources.add(Source {
+            name: path.display().to_string(),
+            text,
+        });
+        self.loaded.insert(canonical.clone());
+
+        let program = match parse_at(text, offset) {
+            Ok(program) => program,
+            Err(mut errors) => {
+                self.errors.append(&mut errors.0);
+                return;
+            }
+        };
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        self.stack.push(canonical);
+        for def in &program.definitions {
+            if let Definition::ImportDefinition(import) = def.as_ref() {
+                self.import(&dir.join(&import.path), import.span);
+            }
+        }
+        self.stack.pop();
+
+        self.definitions.extend(program.definitions);
+    }
+
+    fn import(&mut self, path: &Path, span: Span) {
+        let not_found = |err: std::io::Error| ParseError {
+            span,
+            kind: ParseErrorKind::ImportNotFound {
+                path: path.display().to_string(),
+                reason: err.to_string(),
+            },
+        };
+
+        let canonical = match fs::canonicalize(path) {
+            Ok(canonical) => canonical,
+            Err(err) => return self.errors.push(not_found(err)),
+        };
+        if self.stack.contains(&canonical) {
+            self.errors.push(ParseError {
+                span,
+                kind: ParseErrorKind::ImportCycle(path.display().to_string()),
+            });
+        } else if !self.loaded.contains(&canonical) {
+            match fs::read_to_string(path) {
+                Ok(text) => self.load_file(path, canonical, text.leak()),
+                Err(err) => self.errors.push(not_found(err)),
+            }
+        }
+    }
+}
+
+// Files are told apart by their canonical path, `a/../b.spl` is the same as `b.spl`
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
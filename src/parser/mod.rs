@@ -1,3 +1,6 @@
+pub mod error;
+pub mod lexer;
+pub mod loader;
 pub mod parse_everything_else;
 pub mod token_parser;
 pub mod tokens;
@@ -1,16 +1,8 @@
 #![expect(clippy::unnecessary_wraps, clippy::linkedlist)]
 
-use std::collections::LinkedList;
-
-use nom::{
-    IResult, Parser,
-    branch::alt,
-    character::complete::{char, space0},
-    combinator::eof,
-    error::Error,
-    multi::fold_many0,
-    sequence::{delimited, preceded},
-};
+use std::{cell::RefCell, collections::LinkedList};
+
+use nom::{IResult, Parser, branch::alt};
 
 use crate::{
     absyn::{
@@ -20,162 +12,363 @@ use crate::{
         assign_statement::AssignStatement,
         binary_expression::{BinaryExpression, Operator},
         call_statement::CallStatement,
+        case_statement::{CaseBranch, CaseStatement},
+        const_definition::ConstDefinition,
+        field_access::FieldAccess,
+        for_statement::ForStatement,
         if_statement::IfStatement,
+        import_definition::ImportDefinition,
         parameter_definition::ParameterDefinition,
         procedure_definition::ProcedureDefinition,
+        record_type_expression::{FieldDefinition, RecordTypeExpression},
+        repeat_statement::RepeatStatement,
+        return_statement::ReturnStatement,
         type_definition::TypeDefinition,
-        unary_expression::UnaryExpression,
+        unary_expression::{UnaryExpression, UnaryOperator},
         variable_definition::VariableDefinition,
         while_statement::WhileStatement,
     },
-    parser::token_parser::{ident, intlit},
+    diagnostics::Span,
+    parser::token_parser::{ident, intlit, strlit},
 };
 
 use super::{
-    token_parser::{eq, ge, gt, le, lt, minus, ne, parse_tag, plus, slash, star},
-    tokens::Tokens,
+    error::{ParseError, ParseErrorKind, ParseErrors},
+    lexer::tokenize,
+    token_parser::{
+        amp, and, caret, eq, expect_label, failure_rest, furthest_failure, ge, gt, init_source, le,
+        lt, minus, ne, not, or, parse_tag, percent, pipe, plus, reset_failure, shl, shr, slash,
+        star,
+    },
+    tokens::{Token, Tokens},
 };
 
-pub fn parse(input: &str) -> Result<Program, nom::Err<Error<&str>>> {
-    let (rem, n) = program(input)?;
-    let (_, _) = eof(rem)?;
+thread_local! {
+    // Syntax errors the parser recovered from
+    static ERRORS: RefCell<Vec<ParseError>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Parses a program. The parser recovers from syntax errors at the end of a statement
+/// (`;` or `}`) and at the next global definition, so all errors are reported at once.
+/// Lexical errors are reported together with the syntax errors.
+pub fn parse(input: &str) -> Result<Program, ParseErrors> {
+    parse_at(input, 0)
+}
+
+/// Parses a file of a program with several files. All spans are moved by `offset`,
+/// the position of the file in the `SourceMap`.
+pub fn parse_at(input: &str, offset: usize) -> Result<Program, ParseErrors> {
+    let (mut tokens, mut errors) = tokenize(input);
+    for span in tokens
+        .iter_mut()
+        .map(|t| &mut t.span)
+        .chain(errors.iter_mut().map(|e| &mut e.span))
+    {
+        span.start += offset;
+        span.end += offset;
+    }
+    init_source(input, offset, &tokens);
+    ERRORS.take();
+
+    let program = program(&tokens);
 
-    Ok(n)
+    errors.append(&mut ERRORS.take());
+    if errors.is_empty() {
+        Ok(program)
+    } else {
+        errors.sort_by_key(|e| e.span.start);
+        Err(ParseErrors(errors))
+    }
 }
 
-fn program(input: &str) -> IResult<&str, Program> {
-    let (rem, gd) = global_definition_list(input)?;
-    let gd = gd.into_iter().map(Box::new).collect();
-    Ok((rem, Program { definitions: gd }))
+/// Reports the furthest failure as syntax error and starts searching for the next one.
+/// An error at the same position is only reported once, the parser may backtrack over it.
+fn report_error() {
+    let (span, expected, found) = furthest_failure();
+    ERRORS.with_borrow_mut(|errors| {
+        if !errors.iter().any(|e| e.span.start == span.start) {
+            errors.push(ParseError {
+                span,
+                kind: ParseErrorKind::Unexpected { expected, found },
+            });
+        }
+    });
+}
+
+fn program(input: &[Token]) -> Program {
+    let mut definitions = LinkedList::new();
+    let mut rem = input;
+
+    while !at_eof(rem) {
+        if let Ok((next, def)) = global_definition(rem) {
+            definitions.push_back(Box::new(def));
+            rem = next;
+        } else {
+            report_error();
+            rem = synchronize_definition(rem);
+            reset_failure(rem);
+        }
+    }
+
+    Program { definitions }
 }
 
-fn global_definition_list(input: &str) -> IResult<&str, LinkedList<Definition>> {
-    alt([non_empty_definition_list, empty_definition_list]).parse(input)
+fn global_definition(input: &[Token]) -> IResult<&[Token], Definition> {
+    alt([
+        procedure_definition,
+        type_definition,
+        const_definition,
+        import_definition,
+    ])
+    .parse(input)
 }
 
-fn empty_definition_list(input: &str) -> IResult<&str, LinkedList<Definition>> {
-    Ok((input, LinkedList::from([])))
+fn at_eof(input: &[Token]) -> bool {
+    input.first().is_none_or(|token| token.kind == Tokens::Eof)
 }
 
-fn non_empty_definition_list(input: &str) -> IResult<&str, LinkedList<Definition>> {
-    let (rem, dhead) = global_definition(input)?;
-    let (rem, mut dtail) = global_definition_list(rem)?;
-    dtail.push_front(dhead);
-    Ok((rem, dtail))
+/// Skips the remaining tokens up to the next global definition.
+fn synchronize_definition(input: &[Token]) -> &[Token] {
+    let mut rem = failure_rest(input);
+    // The definition that failed is skipped in any case
+    if rem.len() == input.len() {
+        rem = skip_token(rem);
+    }
+
+    while !at_eof(rem)
+        && ![Tokens::Proc, Tokens::Type, Tokens::Const, Tokens::Import].contains(&rem[0].kind)
+    {
+        rem = skip_token(rem);
+    }
+    rem
+}
+
+/// Skips the remaining tokens up to the end of the statement: after the next `;` or block,
+/// or before the `}` that closes the statement list.
+fn synchronize_statement(input: &[Token]) -> &[Token] {
+    let mut rem = failure_rest(input);
+    let mut depth = 0usize;
+
+    loop {
+        match rem.first().map(|token| &token.kind) {
+            None | Some(Tokens::Eof) => return rem,
+            Some(Tokens::Semic) if depth == 0 => return skip_token(rem),
+            Some(Tokens::RCurl) if depth == 0 => return rem,
+            Some(Tokens::RCurl) => {
+                depth -= 1;
+                if depth == 0 {
+                    return skip_token(rem);
+                }
+            }
+            Some(Tokens::LCurl) => depth += 1,
+            _ => {}
+        }
+        rem = skip_token(rem);
+    }
 }
 
-fn global_definition(input: &str) -> IResult<&str, Definition> {
-    alt([procedure_definition, type_definition]).parse(input)
+/// Skips a single token, the end of the input is never skipped.
+fn skip_token(input: &[Token]) -> &[Token] {
+    if at_eof(input) { input } else { &input[1..] }
 }
 
-fn variable_definition(input: &str) -> IResult<&str, VariableDefinition> {
-    let (rem, _) = parse_tag(input, "var")?;
+fn variable_definition(input: &[Token]) -> IResult<&[Token], VariableDefinition> {
+    let (rem, var) = parse_tag(input, &Tokens::Var)?;
     let (rem, m1) = ident(rem)?;
-    let Tokens::Ident(name) = m1 else { panic!() };
-    let (rem, _) = parse_tag(rem, ":")?;
+    let Tokens::Ident(name) = m1.kind else {
+        panic!()
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::Colon)?;
     let (rem, te) = type_expression(rem)?;
-    let (rem, _) = parse_tag(rem, ";")?;
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
     Ok((
         rem,
         VariableDefinition {
             name,
             type_expression: te,
+            span: var.span.to(semicolon.span),
         },
     ))
 }
 
-fn array_type_expression(input: &str) -> IResult<&str, TypeExpression> {
-    let (rem, _) = parse_tag(input, "array")?;
-    let (rem, _) = parse_tag(rem, "[")?;
-    let (rem, intlit) = intlit(rem)?;
-
-    let Tokens::Intlit(intlit) = intlit else {
-        panic!()
-    };
-    let (rem, _) = parse_tag(rem, "]")?;
-    let (rem, _) = parse_tag(rem, "of")?;
+fn array_type_expression(input: &[Token]) -> IResult<&[Token], TypeExpression> {
+    let (rem, array) = parse_tag(input, &Tokens::Array)?;
+    let (rem, _) = parse_tag(rem, &Tokens::LBrack)?;
+    let (rem, size) = expression(rem)?;
+    let (rem, _) = parse_tag(rem, &Tokens::RBrack)?;
+    let (rem, _) = parse_tag(rem, &Tokens::Of)?;
     let (rem, te) = type_expression(rem)?;
     let ate = ArrayTypeExpression {
-        array_size: usize::try_from(intlit).unwrap(),
+        array_size: size,
+        span: array.span.to(te.span()),
         base_type: te,
     };
     Ok((rem, TypeExpression::ArrayTypeExpression(Box::new(ate))))
 }
 
-fn named_type_expression(input: &str) -> IResult<&str, TypeExpression> {
+fn record_type_expression(input: &[Token]) -> IResult<&[Token], TypeExpression> {
+    let (mut rem, record) = parse_tag(input, &Tokens::Record)?;
+    let mut fields = LinkedList::new();
+    loop {
+        let (next, field) = field_definition(rem)?;
+        fields.push_back(field);
+        rem = next;
+        if let Ok((next, end)) = parse_tag(rem, &Tokens::End) {
+            let rte = RecordTypeExpression {
+                fields,
+                span: record.span.to(end.span),
+            };
+            return Ok((next, TypeExpression::RecordTypeExpression(Box::new(rte))));
+        }
+    }
+}
+
+fn field_definition(input: &[Token]) -> IResult<&[Token], FieldDefinition> {
+    let (rem, m1) = ident(input)?;
+    let Tokens::Ident(name) = m1.kind else {
+        panic!()
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::Colon)?;
+    let (rem, te) = type_expression(rem)?;
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
+    Ok((
+        rem,
+        FieldDefinition {
+            name,
+            type_expression: te,
+            span: m1.span.to(semicolon.span),
+        },
+    ))
+}
+
+fn named_type_expression(input: &[Token]) -> IResult<&[Token], TypeExpression> {
     let (rem, name) = ident(input)?;
-    let Tokens::Ident(name) = name else { panic!() };
+    let Tokens::Ident(ident) = name.kind else {
+        panic!()
+    };
 
-    Ok((rem, TypeExpression::NamedTypeExpression(name)))
+    Ok((rem, TypeExpression::NamedTypeExpression(ident, name.span)))
 }
 
-fn type_expression(input: &str) -> IResult<&str, TypeExpression> {
-    alt([array_type_expression, named_type_expression]).parse(input)
+fn type_expression(input: &[Token]) -> IResult<&[Token], TypeExpression> {
+    alt([
+        array_type_expression,
+        record_type_expression,
+        named_type_expression,
+    ])
+    .parse(input)
 }
 
-fn type_definition(input: &str) -> IResult<&str, Definition> {
-    let (rem, _) = parse_tag(input, "type")?;
+fn type_definition(input: &[Token]) -> IResult<&[Token], Definition> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Type)?;
     let (rem, ident) = ident(rem)?;
-    let Tokens::Ident(name) = ident else { panic!() };
-    let (rem, _) = parse_tag(rem, "=")?;
+    let Tokens::Ident(name) = ident.kind else {
+        panic!()
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::Eq)?;
     let (rem, te) = type_expression(rem)?;
-    let (rem, _) = parse_tag(rem, ";")?;
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
     let type_def = TypeDefinition {
         name,
         type_expression: te,
+        span: keyword.span.to(semicolon.span),
     };
 
     Ok((rem, Definition::TypeDefinition(Box::new(type_def))))
 }
 
-fn procedure_definition(input: &str) -> IResult<&str, Definition> {
-    let (rem, _) = parse_tag(input, "proc")?;
+fn const_definition(input: &[Token]) -> IResult<&[Token], Definition> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Const)?;
     let (rem, ident) = ident(rem)?;
-    let Tokens::Ident(name) = ident else { panic!() };
-    let (rem, _) = parse_tag(rem, "(")?;
+    let Tokens::Ident(name) = ident.kind else {
+        panic!()
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::Eq)?;
+    let (rem, value) = expression(rem)?;
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
+    let const_def = ConstDefinition {
+        name,
+        value,
+        span: keyword.span.to(semicolon.span),
+    };
+
+    Ok((rem, Definition::ConstDefinition(Box::new(const_def))))
+}
+
+fn import_definition(input: &[Token]) -> IResult<&[Token], Definition> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Import)?;
+    let (rem, path) = strlit(rem)?;
+    let Tokens::Strlit(path) = path.kind else {
+        panic!()
+    };
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
+    let import_def = ImportDefinition {
+        path,
+        span: keyword.span.to(semicolon.span),
+    };
+
+    Ok((rem, Definition::ImportDefinition(Box::new(import_def))))
+}
+
+fn procedure_definition(input: &[Token]) -> IResult<&[Token], Definition> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Proc)?;
+    let (rem, ident) = ident(rem)?;
+    let Tokens::Ident(name) = ident.kind else {
+        panic!()
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::LParen)?;
     let (rem, pl) = parameter_list(rem)?;
-    let (rem, _) = parse_tag(rem, ")")?;
-    let (rem, _) = parse_tag(rem, "{")?;
+    let (rem, _) = parse_tag(rem, &Tokens::RParen)?;
+    let (rem, return_type) = match parse_tag(rem, &Tokens::Colon) {
+        Ok((rem, _)) => type_expression(rem).map(|(rem, te)| (rem, Some(te)))?,
+        Err(_) => (rem, None),
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::LCurl)?;
     let (rem, vl) = variable_list(rem)?;
     let (rem, stl) = statement_list(rem)?;
-    let (rem, _) = parse_tag(rem, "}")?;
+    let (rem, close) = parse_tag(rem, &Tokens::RCurl)?;
     let pd = ProcedureDefinition {
         name,
         parameters: pl,
+        return_type,
         body: stl,
         variables: vl,
+        span: keyword.span.to(close.span),
     };
 
     Ok((rem, Definition::ProcedureDefinition(Box::new(pd))))
 }
 
-fn parameter_list(input: &str) -> IResult<&str, LinkedList<ParameterDefinition>> {
+fn parameter_list(input: &[Token]) -> IResult<&[Token], LinkedList<ParameterDefinition>> {
     alt([non_empty_parameter_list, empty_parameter_list]).parse(input)
 }
 
-fn empty_parameter_list(input: &str) -> IResult<&str, LinkedList<ParameterDefinition>> {
+fn empty_parameter_list(input: &[Token]) -> IResult<&[Token], LinkedList<ParameterDefinition>> {
     Ok((input, LinkedList::from([])))
 }
 
-fn non_empty_parameter_list(input: &str) -> IResult<&str, LinkedList<ParameterDefinition>> {
+fn non_empty_parameter_list(input: &[Token]) -> IResult<&[Token], LinkedList<ParameterDefinition>> {
     alt([more_than_one_parameter, parameter]).parse(input)
 }
 
-fn more_than_one_parameter(input: &str) -> IResult<&str, LinkedList<ParameterDefinition>> {
+fn more_than_one_parameter(input: &[Token]) -> IResult<&[Token], LinkedList<ParameterDefinition>> {
     let (rem, mut phead) = parameter(input)?;
-    let (rem, _) = parse_tag(rem, ",")?;
+    let (rem, _) = parse_tag(rem, &Tokens::Comma)?;
     let (rem, mut ptail) = non_empty_parameter_list(rem)?;
     ptail.push_front(phead.pop_back().unwrap());
     Ok((rem, ptail))
 }
 
-fn non_ref_parameter(input: &str) -> IResult<&str, ParameterDefinition> {
+fn non_ref_parameter(input: &[Token]) -> IResult<&[Token], ParameterDefinition> {
     let (rem, ident) = ident(input)?;
-    let Tokens::Ident(name) = ident else { panic!() };
-    let (rem, _) = parse_tag(rem, ":")?;
+    let Tokens::Ident(name) = ident.kind else {
+        panic!()
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::Colon)?;
     let (rem, te) = type_expression(rem)?;
     let pd = ParameterDefinition {
         name,
+        span: ident.span.to(te.span()),
         type_expression: te,
         is_reference: false,
     };
@@ -183,19 +376,22 @@ fn non_ref_parameter(input: &str) -> IResult<&str, ParameterDefinition> {
     Ok((rem, pd))
 }
 
-fn parameter(input: &str) -> IResult<&str, LinkedList<ParameterDefinition>> {
+fn parameter(input: &[Token]) -> IResult<&[Token], LinkedList<ParameterDefinition>> {
     let res = alt([non_ref_parameter, ref_parameter]).parse(input)?;
     Ok((res.0, LinkedList::from([res.1])))
 }
 
-fn ref_parameter(input: &str) -> IResult<&str, ParameterDefinition> {
-    let (rem, _) = parse_tag(input, "ref")?;
+fn ref_parameter(input: &[Token]) -> IResult<&[Token], ParameterDefinition> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Ref)?;
     let (rem, ident) = ident(rem)?;
-    let Tokens::Ident(name) = ident else { panic!() };
-    let (rem, _) = parse_tag(rem, ":")?;
+    let Tokens::Ident(name) = ident.kind else {
+        panic!()
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::Colon)?;
     let (rem, te) = type_expression(rem)?;
     let pd = ParameterDefinition {
         name,
+        span: keyword.span.to(te.span()),
         type_expression: te,
         is_reference: true,
     };
@@ -203,203 +399,410 @@ fn ref_parameter(input: &str) -> IResult<&str, ParameterDefinition> {
     Ok((rem, pd))
 }
 
-fn variable_list(input: &str) -> IResult<&str, LinkedList<VariableDefinition>> {
+fn variable_list(input: &[Token]) -> IResult<&[Token], LinkedList<VariableDefinition>> {
     alt([non_empty_variable_list, empty_variable_list]).parse(input)
 }
 
-fn empty_variable_list(input: &str) -> IResult<&str, LinkedList<VariableDefinition>> {
+fn empty_variable_list(input: &[Token]) -> IResult<&[Token], LinkedList<VariableDefinition>> {
     Ok((input, LinkedList::from([])))
 }
 
-fn non_empty_variable_list(input: &str) -> IResult<&str, LinkedList<VariableDefinition>> {
+fn non_empty_variable_list(input: &[Token]) -> IResult<&[Token], LinkedList<VariableDefinition>> {
     let (rem, vd) = variable_definition(input)?;
     let (rem, mut vl) = variable_list(rem)?;
     vl.push_front(vd);
     Ok((rem, vl))
 }
 
-fn statement_list(input: &str) -> IResult<&str, LinkedList<Statement>> {
-    alt([non_empty_statement_list, empty_statement_list]).parse(input)
-}
-
-fn empty_statement_list(input: &str) -> IResult<&str, LinkedList<Statement>> {
-    Ok((input, LinkedList::from([])))
-}
-
-fn non_empty_statement_list(input: &str) -> IResult<&str, LinkedList<Statement>> {
-    let (rem, st) = statement(input)?;
-    let (rem, mut stl) = statement_list(rem)?;
-    stl.push_front(st);
-    Ok((rem, stl))
+// Parses statements up to the closing `}`, statements with syntax errors are reported and skipped
+fn statement_list(input: &[Token]) -> IResult<&[Token], LinkedList<Statement>> {
+    let mut stl = LinkedList::new();
+    let mut rem = input;
+
+    loop {
+        match expect_label(rem, "statement", statement) {
+            Ok((next, st)) => {
+                stl.push_back(st);
+                rem = next;
+            }
+            Err(nom::Err::Error(_)) if at_eof(rem) || rem[0].kind == Tokens::RCurl => {
+                return Ok((rem, stl));
+            }
+            Err(nom::Err::Error(_)) => {
+                report_error();
+                rem = synchronize_statement(rem);
+                reset_failure(rem);
+            }
+            Err(err) => return Err(err),
+        }
+    }
 }
 
-fn statement(input: &str) -> IResult<&str, Statement> {
+fn statement(input: &[Token]) -> IResult<&[Token], Statement> {
     alt([
         empty_statement,
         if_statement,
         assign_statement,
         while_statement,
+        for_statement,
+        repeat_statement,
+        case_statement,
         compound_statement,
         call_statement,
+        return_statement,
+        break_statement,
+        continue_statement,
     ])
     .parse(input)
 }
 
-fn empty_statement(input: &str) -> IResult<&str, Statement> {
-    let (rem, _) = parse_tag(input, ";")?;
-    Ok((rem, Statement::EmptyStatement))
-}
-
-fn if_statement(input: &str) -> IResult<&str, Statement> {
-    alt([if_statement_with_else, if_statement_without_else]).parse(input)
-}
-
-fn if_statement_without_else(input: &str) -> IResult<&str, Statement> {
-    let (rem, _) = parse_tag(input, "if")?;
-    let (rem, _) = parse_tag(rem, "(")?;
-    let (rem, ex) = expression(rem)?;
-    let (rem, _) = parse_tag(rem, ")")?;
-    let (rem, st) = statement(rem)?;
-    let if_stmt = IfStatement {
-        condition: ex,
-        then_branch: st,
-        else_branch: None,
-    };
-    Ok((rem, Statement::IfStatement(Box::new(if_stmt))))
+fn empty_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, semicolon) = parse_tag(input, &Tokens::Semic)?;
+    Ok((rem, Statement::EmptyStatement(semicolon.span)))
 }
 
-fn if_statement_with_else(input: &str) -> IResult<&str, Statement> {
-    let (rem, _) = parse_tag(input, "if")?;
-    let (rem, _) = parse_tag(rem, "(")?;
+// The else branch is parsed directly after the then branch, so the then branch
+// is never parsed twice and its syntax errors are reported once
+fn if_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, keyword) = parse_tag(input, &Tokens::If)?;
+    let (rem, _) = parse_tag(rem, &Tokens::LParen)?;
     let (rem, ex) = expression(rem)?;
-    let (rem, _) = parse_tag(rem, ")")?;
+    let (rem, _) = parse_tag(rem, &Tokens::RParen)?;
     let (rem, then_part) = statement(rem)?;
-    let (rem, _) = parse_tag(rem, "else")?;
-    let (rem, else_part) = statement(rem)?;
+    let (rem, else_part) = match parse_tag(rem, &Tokens::Else) {
+        Ok((rem, _)) => statement(rem).map(|(rem, st)| (rem, Some(st)))?,
+        Err(_) => (rem, None),
+    };
     let if_stmt = IfStatement {
         condition: ex,
+        span: keyword
+            .span
+            .to(else_part.as_ref().unwrap_or(&then_part).span()),
         then_branch: then_part,
-        else_branch: Some(else_part),
+        else_branch: else_part,
     };
 
     Ok((rem, Statement::IfStatement(Box::new(if_stmt))))
 }
 
-fn while_statement(input: &str) -> IResult<&str, Statement> {
-    let (rem, _) = parse_tag(input, "while")?;
-    let (rem, _) = parse_tag(rem, "(")?;
+fn while_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, keyword) = parse_tag(input, &Tokens::While)?;
+    let (rem, _) = parse_tag(rem, &Tokens::LParen)?;
     let (rem, cond) = expression(rem)?;
-    let (rem, _) = parse_tag(rem, ")")?;
+    let (rem, _) = parse_tag(rem, &Tokens::RParen)?;
     let (rem, st) = statement(rem)?;
     let while_stmt = WhileStatement {
         condition: cond,
+        span: keyword.span.to(st.span()),
         body: st,
     };
     Ok((rem, Statement::WhileStatement(Box::new(while_stmt))))
 }
 
-fn compound_statement(input: &str) -> IResult<&str, Statement> {
-    let (rem, _) = parse_tag(input, "{")?;
+fn for_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, keyword) = parse_tag(input, &Tokens::For)?;
+    let (rem, var) = variable(rem)?;
+    let (rem, _) = parse_tag(rem, &Tokens::Asgn)?;
+    let (rem, start) = expression(rem)?;
+    let (rem, _) = parse_tag(rem, &Tokens::To)?;
+    let (rem, end) = expression(rem)?;
+    let (rem, step) = match parse_tag(rem, &Tokens::Step) {
+        Ok((rem, _)) => step(rem)?,
+        Err(_) => (rem, 1),
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::Do)?;
+    let (rem, st) = statement(rem)?;
+    let for_stmt = ForStatement {
+        variable: var,
+        start,
+        end,
+        step,
+        span: keyword.span.to(st.span()),
+        body: st,
+    };
+    Ok((rem, Statement::ForStatement(Box::new(for_stmt))))
+}
+
+// The step of a for loop is an integer literal, negative ones count down
+fn step(input: &[Token]) -> IResult<&[Token], i32> {
+    let (rem, sign) = match minus(input) {
+        Ok((rem, _)) => (rem, -1),
+        Err(_) => (input, 1),
+    };
+    let (rem, lit) = intlit(rem)?;
+    let Tokens::Intlit(value) = lit.kind else {
+        panic!()
+    };
+    Ok((rem, value.wrapping_mul(sign)))
+}
+
+fn repeat_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Repeat)?;
+    let (rem, st) = statement(rem)?;
+    let (rem, _) = parse_tag(rem, &Tokens::Until)?;
+    let (rem, _) = parse_tag(rem, &Tokens::LParen)?;
+    let (rem, cond) = expression(rem)?;
+    let (rem, _) = parse_tag(rem, &Tokens::RParen)?;
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
+    let repeat_stmt = RepeatStatement {
+        body: st,
+        condition: cond,
+        span: keyword.span.to(semicolon.span),
+    };
+    Ok((rem, Statement::RepeatStatement(Box::new(repeat_stmt))))
+}
+
+// The branches are followed by an optional `else` branch and the closing `end`
+fn case_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Case)?;
+    let (rem, selector) = expression(rem)?;
+    let (mut rem, _) = parse_tag(rem, &Tokens::Of)?;
+    let mut branches = LinkedList::new();
+    let (rem, default) = loop {
+        let (next, branch) = case_branch(rem)?;
+        branches.push_back(branch);
+        rem = next;
+        if let Ok((next, _)) = parse_tag(rem, &Tokens::Else) {
+            break statement(next).map(|(next, st)| (next, Some(st)))?;
+        }
+        if parse_tag(rem, &Tokens::End).is_ok() {
+            break (rem, None);
+        }
+    };
+    let (rem, end) = parse_tag(rem, &Tokens::End)?;
+    let case_stmt = CaseStatement {
+        selector,
+        branches,
+        default,
+        span: keyword.span.to(end.span),
+    };
+    Ok((rem, Statement::CaseStatement(Box::new(case_stmt))))
+}
+
+// A branch of a case statement: `1, 2: statement`
+fn case_branch(input: &[Token]) -> IResult<&[Token], CaseBranch> {
+    let (mut rem, first) = expression(input)?;
+    let span = first.span();
+    let mut labels = LinkedList::from([first]);
+    while let Ok((next, _)) = parse_tag(rem, &Tokens::Comma) {
+        let (next, label) = expression(next)?;
+        labels.push_back(label);
+        rem = next;
+    }
+    let (rem, _) = parse_tag(rem, &Tokens::Colon)?;
+    let (rem, st) = statement(rem)?;
+    let branch = CaseBranch {
+        labels,
+        values: Vec::new(),
+        span: span.to(st.span()),
+        statement: st,
+    };
+    Ok((rem, branch))
+}
+
+fn compound_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, open) = parse_tag(input, &Tokens::LCurl)?;
     let (rem, stl) = statement_list(rem)?;
-    let (rem, _) = parse_tag(rem, "}")?;
+    let (rem, close) = parse_tag(rem, &Tokens::RCurl)?;
     let stl = stl.into_iter().map(Box::new).collect();
-    Ok((rem, Statement::CompoundStatement(stl)))
+    Ok((
+        rem,
+        Statement::CompoundStatement(stl, open.span.to(close.span)),
+    ))
 }
 
-fn assign_statement(input: &str) -> IResult<&str, Statement> {
+fn assign_statement(input: &[Token]) -> IResult<&[Token], Statement> {
     let (rem, var) = variable(input)?;
-    let (rem, _) = parse_tag(rem, ":=")?;
+    let (rem, _) = parse_tag(rem, &Tokens::Asgn)?;
     let (rem, exp) = expression(rem)?;
-    let (rem, _) = parse_tag(rem, ";")?;
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
     let asgn_statement = AssignStatement {
+        span: var.span().to(semicolon.span),
         target: var,
         value: exp,
     };
     Ok((rem, Statement::AssignStatement(Box::new(asgn_statement))))
 }
 
-fn call_statement(input: &str) -> IResult<&str, Statement> {
+fn call_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, mut call) = call(input)?;
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
+    call.span = call.span.to(semicolon.span);
+
+    Ok((rem, Statement::CallStatement(Box::new(call))))
+}
+
+// A call without the `;`, used as statement and as expression
+fn call(input: &[Token]) -> IResult<&[Token], CallStatement> {
     let (rem, ident) = ident(input)?;
-    let Tokens::Ident(name) = ident else { panic!() };
-    let (rem, _) = parse_tag(rem, "(")?;
+    let Tokens::Ident(name) = ident.kind else {
+        panic!()
+    };
+    let (rem, _) = parse_tag(rem, &Tokens::LParen)?;
     let (rem, arguments) = argument_list(rem)?;
-    let (rem, _) = parse_tag(rem, ")")?;
-    let (rem, _) = parse_tag(rem, ";")?;
-    let call_statement = CallStatement { name, arguments };
+    let (rem, close) = parse_tag(rem, &Tokens::RParen)?;
+    let call = CallStatement {
+        name,
+        arguments,
+        span: ident.span.to(close.span),
+    };
+
+    Ok((rem, call))
+}
+
+fn return_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Return)?;
+    let (rem, value) = match parse_tag(rem, &Tokens::Semic) {
+        Ok(_) => (rem, None),
+        Err(_) => expression(rem).map(|(rem, exp)| (rem, Some(exp)))?,
+    };
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
+    let return_statement = ReturnStatement {
+        value,
+        span: keyword.span.to(semicolon.span),
+    };
+
+    Ok((rem, Statement::ReturnStatement(Box::new(return_statement))))
+}
+
+fn break_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Break)?;
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
+    Ok((
+        rem,
+        Statement::BreakStatement(keyword.span.to(semicolon.span)),
+    ))
+}
 
-    Ok((rem, Statement::CallStatement(Box::new(call_statement))))
+fn continue_statement(input: &[Token]) -> IResult<&[Token], Statement> {
+    let (rem, keyword) = parse_tag(input, &Tokens::Continue)?;
+    let (rem, semicolon) = parse_tag(rem, &Tokens::Semic)?;
+    Ok((
+        rem,
+        Statement::ContinueStatement(keyword.span.to(semicolon.span)),
+    ))
 }
 
-fn argument_list(input: &str) -> IResult<&str, LinkedList<Expression>> {
+fn argument_list(input: &[Token]) -> IResult<&[Token], LinkedList<Expression>> {
     alt([non_empty_argument_list, empty_argument_list]).parse(input)
 }
 
-fn empty_argument_list(input: &str) -> IResult<&str, LinkedList<Expression>> {
+fn empty_argument_list(input: &[Token]) -> IResult<&[Token], LinkedList<Expression>> {
     Ok((input, LinkedList::from([])))
 }
 
-fn non_empty_argument_list(input: &str) -> IResult<&str, LinkedList<Expression>> {
+fn non_empty_argument_list(input: &[Token]) -> IResult<&[Token], LinkedList<Expression>> {
     alt([more_than_one_argument, expression_head]).parse(input)
 }
 
-fn expression_head(input: &str) -> IResult<&str, LinkedList<Expression>> {
+fn expression_head(input: &[Token]) -> IResult<&[Token], LinkedList<Expression>> {
     let (rem, exp) = expression(input)?;
     Ok((rem, LinkedList::from([exp])))
 }
 
-fn more_than_one_argument(input: &str) -> IResult<&str, LinkedList<Expression>> {
+fn more_than_one_argument(input: &[Token]) -> IResult<&[Token], LinkedList<Expression>> {
     let (rem, mut ehead) = expression_head(input)?;
-    let (rem, _) = parse_tag(rem, ",")?;
+    let (rem, _) = parse_tag(rem, &Tokens::Comma)?;
     let (rem, mut etail) = non_empty_argument_list(rem)?;
     etail.push_front(ehead.pop_back().unwrap());
     Ok((rem, etail))
 }
 
-// Parses a single [index] access
-fn single_array_access(input: &str) -> IResult<&str, Expression> {
-    delimited(
-        preceded(space0, char('[')),
-        preceded(space0, expression),
-        preceded(space0, char(']')),
-    )
-    .parse(input)
+// Parses a single [index] access, together with the span of the closing bracket
+fn single_array_access(input: &[Token]) -> IResult<&[Token], (Expression, Span)> {
+    let (rem, _) = parse_tag(input, &Tokens::LBrack)?;
+    let (rem, index) = expression(rem)?;
+    let (rem, close) = parse_tag(rem, &Tokens::RBrack)?;
+    Ok((rem, (index, close.span)))
+}
+
+// Parses a single .field access, together with the span of the field name
+fn single_field_access(input: &[Token]) -> IResult<&[Token], (String, Span)> {
+    let (rem, _) = parse_tag(input, &Tokens::Dot)?;
+    let (rem, ident) = ident(rem)?;
+    let Tokens::Ident(name) = ident.kind else {
+        panic!()
+    };
+    Ok((rem, (name, ident.span)))
 }
 
-// Parses a variable with 0 or more array accesses: myArray[1][2]
-fn variable(input: &str) -> IResult<&str, Variable> {
-    let (input, base) = named_var(input)?;
+// Parses a variable with 0 or more array and field accesses: myArray[1][2].field
+fn variable(input: &[Token]) -> IResult<&[Token], Variable> {
+    let (mut rem, mut var) = named_var(input)?;
 
-    fold_many0(
-        single_array_access,
-        move || base.clone(),
-        |acc, idx| {
-            Variable::ArrayAccess(Box::new(ArrayAccess {
-                array: acc,
+    loop {
+        if let Ok((next, (idx, close))) = single_array_access(rem) {
+            var = Variable::ArrayAccess(Box::new(ArrayAccess {
+                span: var.span().to(close),
+                array: var,
                 index: idx,
                 typ: None,
-            }))
-        },
-    )
-    .parse(input)
+            }));
+            rem = next;
+        } else if let Ok((next, (field, span))) = single_field_access(rem) {
+            var = Variable::FieldAccess(Box::new(FieldAccess {
+                span: var.span().to(span),
+                record: var,
+                field,
+                typ: None,
+            }));
+            rem = next;
+        } else {
+            return Ok((rem, var));
+        }
+    }
 }
 
-fn named_var(input: &str) -> IResult<&str, Variable> {
+fn named_var(input: &[Token]) -> IResult<&[Token], Variable> {
     let (rem, ident) = ident(input)?;
-    let Tokens::Ident(name) = ident else { panic!() };
+    let Tokens::Ident(name) = ident.kind else {
+        panic!()
+    };
 
-    Ok((rem, Variable::NamedVariable(name)))
+    Ok((rem, Variable::NamedVariable(name, ident.span)))
+}
+
+fn expression(input: &[Token]) -> IResult<&[Token], Expression> {
+    or_expression(input)
+}
+
+// `||` binds weaker than `&&`, both bind weaker than comparisons
+fn or_expression(input: &[Token]) -> IResult<&[Token], Expression> {
+    let (mut inp, mut expr) = and_expression(input)?;
+    while let Ok((rem, _)) = or(inp) {
+        let (rem, right) = and_expression(rem)?;
+        expr = Expression::BinaryExpression(Box::new(BinaryExpression {
+            operator: Operator::Or,
+            span: expr.span().to(right.span()),
+            left: expr,
+            right,
+        }));
+        inp = rem;
+    }
+    Ok((inp, expr))
 }
 
-fn expression(input: &str) -> IResult<&str, Expression> {
-    expression0(input)
+fn and_expression(input: &[Token]) -> IResult<&[Token], Expression> {
+    let (mut inp, mut expr) = expression0(input)?;
+    while let Ok((rem, _)) = and(inp) {
+        let (rem, right) = expression0(rem)?;
+        expr = Expression::BinaryExpression(Box::new(BinaryExpression {
+            operator: Operator::And,
+            span: expr.span().to(right.span()),
+            left: expr,
+            right,
+        }));
+        inp = rem;
+    }
+    Ok((inp, expr))
 }
 
-fn expression0(input: &str) -> IResult<&str, Expression> {
+fn expression0(input: &[Token]) -> IResult<&[Token], Expression> {
     let (mut inp, mut expr) = expression2(input)?;
     while let Ok((rem, op)) = alt([eq, ne, le, lt, ge, gt]).parse(inp) {
         let (rem, right) = expression2(rem)?;
         expr = Expression::BinaryExpression(Box::new(BinaryExpression {
-            operator: match op {
+            operator: match op.kind {
                 Tokens::Eq => Operator::Equ,
                 Tokens::Ne => Operator::Neq,
                 Tokens::Le => Operator::Lse,
@@ -408,6 +811,7 @@ fn expression0(input: &str) -> IResult<&str, Expression> {
                 Tokens::Gt => Operator::Grt,
                 _ => unreachable!(),
             },
+            span: expr.span().to(right.span()),
             left: expr,
             right,
         }));
@@ -416,16 +820,20 @@ fn expression0(input: &str) -> IResult<&str, Expression> {
     Ok((inp, expr))
 }
 
-fn expression2(input: &str) -> IResult<&str, Expression> {
+// `|` and `^` bind like `+`, `&` and the shifts like `*`
+fn expression2(input: &[Token]) -> IResult<&[Token], Expression> {
     let (mut inp, mut expr) = expression3(input)?;
-    while let Ok((rem, op)) = alt([plus, minus]).parse(inp) {
+    while let Ok((rem, op)) = alt([plus, minus, pipe, caret]).parse(inp) {
         let (rem, right) = expression3(rem)?;
         expr = Expression::BinaryExpression(Box::new(BinaryExpression {
-            operator: match op {
+            operator: match op.kind {
                 Tokens::Plus => Operator::Add,
                 Tokens::Minus => Operator::Sub,
+                Tokens::Pipe => Operator::BitOr,
+                Tokens::Caret => Operator::BitXor,
                 _ => unreachable!(),
             },
+            span: expr.span().to(right.span()),
             left: expr,
             right,
         }));
@@ -434,16 +842,21 @@ fn expression2(input: &str) -> IResult<&str, Expression> {
     Ok((inp, expr))
 }
 
-fn expression3(input: &str) -> IResult<&str, Expression> {
+fn expression3(input: &[Token]) -> IResult<&[Token], Expression> {
     let (mut inp, mut expr) = expression4(input)?;
-    while let Ok((rem, op)) = alt([star, slash]).parse(inp) {
+    while let Ok((rem, op)) = alt([star, slash, percent, amp, shl, shr]).parse(inp) {
         let (rem, right) = expression4(rem)?;
         expr = Expression::BinaryExpression(Box::new(BinaryExpression {
-            operator: match op {
+            operator: match op.kind {
                 Tokens::Star => Operator::Mul,
                 Tokens::Slash => Operator::Div,
+                Tokens::Percent => Operator::Mod,
+                Tokens::Amp => Operator::BitAnd,
+                Tokens::Shl => Operator::Shl,
+                Tokens::Shr => Operator::Shr,
                 _ => unreachable!(),
             },
+            span: expr.span().to(right.span()),
             left: expr,
             right,
         }));
@@ -452,40 +865,85 @@ fn expression3(input: &str) -> IResult<&str, Expression> {
     Ok((inp, expr))
 }
 
-fn expression4(input: &str) -> IResult<&str, Expression> {
-    alt([unary_expression, expression5]).parse(input)
+// An operand, reported as `expression` if it is missing
+fn expression4(input: &[Token]) -> IResult<&[Token], Expression> {
+    expect_label(input, "expression", |input| {
+        alt([unary_expression, expression5]).parse(input)
+    })
 }
 
-fn unary_expression(input: &str) -> IResult<&str, Expression> {
-    let (rem, _) = parse_tag(input, "-")?;
+fn unary_expression(input: &[Token]) -> IResult<&[Token], Expression> {
+    let (rem, op) = alt([minus, not]).parse(input)?;
     let (rem, exp) = expression4(rem)?;
     let unary_expression = UnaryExpression {
-        operator: crate::absyn::unary_expression::UnaryOperator::Minus,
+        operator: match op.kind {
+            Tokens::Minus => UnaryOperator::Minus,
+            Tokens::Not => UnaryOperator::Not,
+            _ => unreachable!(),
+        },
+        span: op.span.to(exp.span()),
         operand: exp,
     };
 
     Ok((rem, Expression::UnaryExpression(Box::new(unary_expression))))
 }
 
-fn intlit_exp(input: &str) -> IResult<&str, Expression> {
-    let (rem, i) = intlit(input)?;
-    let Tokens::Intlit(i) = i else { panic!() };
+fn intlit_exp(input: &[Token]) -> IResult<&[Token], Expression> {
+    let (rem, token) = intlit(input)?;
+    let Tokens::Intlit(i) = token.kind else {
+        panic!()
+    };
+
+    Ok((rem, Expression::IntLiteral(i, token.span)))
+}
+
+fn strlit_exp(input: &[Token]) -> IResult<&[Token], Expression> {
+    let (rem, token) = strlit(input)?;
+    let Tokens::Strlit(text) = token.kind else {
+        panic!()
+    };
+
+    Ok((rem, Expression::StringLiteral(text, token.span)))
+}
+
+fn boollit_exp(input: &[Token]) -> IResult<&[Token], Expression> {
+    let (rem, token) = alt([
+        |input| parse_tag(input, &Tokens::True),
+        |input| parse_tag(input, &Tokens::False),
+    ])
+    .parse(input)?;
 
-    Ok((rem, Expression::IntLiteral(i)))
+    Ok((
+        rem,
+        Expression::BoolLiteral(token.kind == Tokens::True, token.span),
+    ))
 }
 
-fn variable_exp(input: &str) -> IResult<&str, Expression> {
+fn call_exp(input: &[Token]) -> IResult<&[Token], Expression> {
+    let (rem, call) = call(input)?;
+    Ok((rem, Expression::CallExpression(Box::new(call))))
+}
+
+fn variable_exp(input: &[Token]) -> IResult<&[Token], Expression> {
     let (rem, i) = variable(input)?;
     Ok((rem, Expression::VariableExpression(Box::new(i))))
 }
 
-fn parentheses_exp(input: &str) -> IResult<&str, Expression> {
-    let (rem, _) = parse_tag(input, "(")?;
+fn parentheses_exp(input: &[Token]) -> IResult<&[Token], Expression> {
+    let (rem, _) = parse_tag(input, &Tokens::LParen)?;
     let (rem, ex) = expression(rem)?;
-    let (rem, _) = parse_tag(rem, ")")?;
+    let (rem, _) = parse_tag(rem, &Tokens::RParen)?;
     Ok((rem, ex))
 }
 
-fn expression5(input: &str) -> IResult<&str, Expression> {
-    alt([intlit_exp, variable_exp, parentheses_exp]).parse(input)
+fn expression5(input: &[Token]) -> IResult<&[Token], Expression> {
+    alt([
+        intlit_exp,
+        strlit_exp,
+        boollit_exp,
+        call_exp,
+        variable_exp,
+        parentheses_exp,
+    ])
+    .parse(input)
 }
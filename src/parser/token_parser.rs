@@ -1,131 +1,237 @@
-use nom::{
-    IResult, Parser,
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::{anychar, digit1, hex_digit1, multispace1, not_line_ending, satisfy},
-    combinator::verify,
-    multi::many0,
-    sequence::{pair, preceded},
-};
+use std::cell::RefCell;
+
+use nom::{IResult, error::ErrorKind};
+
+use crate::diagnostics::Span;
+
+use super::tokens::{Token, Tokens};
+
+/// The input of the running parse, needed to turn the remaining tokens into positions.
+#[derive(Default)]
+struct SourceInfo {
+    text: String,
+    /// The position of the text in the `SourceMap`, added to all spans
+    offset: usize,
+    tokens: Vec<Token>,
+    /// Index of the furthest token that could not be parsed
+    furthest_failure: usize,
+    /// Everything that was expected at the furthest failure, in the order it was tried
+    expected: Vec<String>,
+}
+
+thread_local! {
+    static SOURCE: RefCell<SourceInfo> = RefCell::default();
+}
+
+/// Remembers the input of a new parse.
+pub fn init_source(input: &str, offset: usize, tokens: &[Token]) {
+    SOURCE.with_borrow_mut(|source| {
+        *source = SourceInfo {
+            text: input.to_string(),
+            offset,
+            tokens: tokens.to_vec(),
+            furthest_failure: 0,
+            expected: Vec::new(),
+        };
+    });
+}
+
+fn position(rem: &[Token]) -> usize {
+    SOURCE.with_borrow(|source| source.tokens.len() - rem.len())
+}
+
+/// Records that `expected` could not be parsed at the start of `rem`.
+pub fn record_failure(rem: &[Token], expected: &str) {
+    let pos = position(rem);
+    SOURCE.with_borrow_mut(|source| {
+        if pos > source.furthest_failure {
+            source.furthest_failure = pos;
+            source.expected.clear();
+        }
+        if pos == source.furthest_failure && !source.expected.iter().any(|e| e == expected) {
+            source.expected.push(expected.to_string());
+        }
+    });
+}
+
+/// Forgets all failures, the next error is searched from the start of `rem` on.
+/// Failures further than `rem` are kept, they were found after backtracking.
+pub fn reset_failure(rem: &[Token]) {
+    let pos = position(rem);
+    SOURCE.with_borrow_mut(|source| {
+        if pos >= source.furthest_failure {
+            source.furthest_failure = pos;
+            source.expected.clear();
+        }
+    });
+}
+
+/// Runs `parser` and, if it fails right at the start of `input`, replaces everything
+/// it expected with a single `label`, e.g. `expression` instead of all its alternatives.
+pub fn expect_label<'a, T>(
+    input: &'a [Token],
+    label: &str,
+    parser: impl FnOnce(&'a [Token]) -> IResult<&'a [Token], T>,
+) -> IResult<&'a [Token], T> {
+    let pos = position(input);
+    let mark = SOURCE
+        .with_borrow(|source| (source.furthest_failure == pos).then_some(source.expected.len()));
+
+    parser(input).inspect_err(|_| {
+        SOURCE.with_borrow_mut(|source| {
+            if source.furthest_failure == pos {
+                source.expected.truncate(mark.unwrap_or(0));
+            }
+        });
+        record_failure(input, label);
+    })
+}
+
+/// The furthest token at which parsing failed, what was expected there,
+/// and the text found there. The text is `None` at the end of the input.
+pub fn furthest_failure() -> (Span, Vec<String>, Option<String>) {
+    SOURCE.with_borrow(|source| {
+        let token = &source.tokens[source.furthest_failure];
+        let found = (token.kind != Tokens::Eof).then(|| {
+            source.text[token.span.start - source.offset..token.span.end - source.offset]
+                .to_string()
+        });
+        (token.span, source.expected.clone(), found)
+    })
+}
+
+/// The part of `rem` that starts at the furthest failure.
+pub fn failure_rest(rem: &[Token]) -> &[Token] {
+    let offset = SOURCE
+        .with_borrow(|source| source.furthest_failure)
+        .saturating_sub(position(rem));
+    &rem[offset..]
+}
 
-use super::tokens::Tokens;
+fn failure<'a>(input: &'a [Token], expected: &str) -> nom::Err<nom::error::Error<&'a [Token]>> {
+    record_failure(input, expected);
+    nom::Err::Error(nom::error::Error::new(input, ErrorKind::Tag))
+}
 
-pub fn parse_tag<'a>(input: &'a str, word: &'a str) -> IResult<&'a str, Tokens> {
-    #![expect(clippy::similar_names)]
-    let (rem, ()) = parse_whitespace(input)?;
-    let res = tag(word)(rem)?;
-    let (rem, ()) = parse_whitespace(res.0)?;
-    Ok((rem, Tokens::Other))
+/// Parses a single token of the given kind, e.g. a keyword or `(`.
+pub fn parse_tag<'a>(input: &'a [Token], kind: &Tokens) -> IResult<&'a [Token], Token> {
+    match input {
+        [token, rem @ ..] if &token.kind == kind => Ok((rem, token.clone())),
+        _ => Err(failure(input, &format!("`{kind}`"))),
+    }
 }
 
-fn ident_first_char(input: &str) -> IResult<&str, char> {
-    verify(anychar, |c: &char| c.is_alphabetic() || *c == '_').parse(input)
+pub fn ident(input: &[Token]) -> IResult<&[Token], Token> {
+    match input {
+        [
+            token @ Token {
+                kind: Tokens::Ident(_),
+                ..
+            },
+            rem @ ..,
+        ] => Ok((rem, token.clone())),
+        _ => Err(failure(input, "identifier")),
+    }
 }
 
-fn ident_later_chars(input: &str) -> IResult<&str, String> {
-    let res = many0(satisfy(|c| c.is_alphanumeric() || c == '_')).parse(input)?;
-    Ok((res.0, res.1.iter().collect()))
+pub fn intlit(input: &[Token]) -> IResult<&[Token], Token> {
+    match input {
+        [
+            token @ Token {
+                kind: Tokens::Intlit(_),
+                ..
+            },
+            rem @ ..,
+        ] => Ok((rem, token.clone())),
+        _ => Err(failure(input, "integer literal")),
+    }
 }
 
-pub fn ident(input: &str) -> IResult<&str, Tokens> {
-    let (rem, ()) = parse_whitespace(input)?;
-    let (rem, m) = ident_first_char(rem)?;
-    let (rem1, m1) = ident_later_chars(rem)?;
-    let (rem1, ()) = parse_whitespace(rem1)?;
-    Ok((rem1, Tokens::Ident(format!("{m}{m1}"))))
+pub fn strlit(input: &[Token]) -> IResult<&[Token], Token> {
+    match input {
+        [
+            token @ Token {
+                kind: Tokens::Strlit(_),
+                ..
+            },
+            rem @ ..,
+        ] => Ok((rem, token.clone())),
+        _ => Err(failure(input, "string literal")),
+    }
 }
 
-fn int(input: &str) -> IResult<&str, Tokens> {
-    let res = digit1(input)?;
-    Ok((res.0, Tokens::Intlit(res.1.parse::<i32>().unwrap())))
+pub fn eq(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Eq)
 }
 
-fn newline(input: &str) -> IResult<&str, Tokens> {
-    let (rem, _) = tag("'\\n'")(input)?;
-    Ok((rem, Tokens::Intlit(10)))
+pub fn ne(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Ne)
 }
 
-pub fn parse_whitespace(input: &str) -> IResult<&str, ()> {
-    let comment: fn(_) -> _ = comment;
-    let res = many0(alt([multispace1, comment])).parse(input)?;
-    Ok((res.0, ()))
+pub fn lt(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Lt)
 }
 
-fn character(input: &str) -> IResult<&str, Tokens> {
-    #![expect(clippy::similar_names)]
-    let (rem, _) = tag("'")(input)?;
-    let res = anychar(rem)?;
-    let (rem, _) = tag("'")(res.0)?;
-    Ok((rem, Tokens::Intlit(res.1 as i32)))
+pub fn gt(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Gt)
 }
 
-fn hex_num(input: &str) -> IResult<&str, Tokens> {
-    let res = preceded(tag("0x"), hex_digit1).parse(input)?;
-    Ok((
-        res.0,
-        Tokens::Intlit(i32::from_str_radix(res.1, 16).unwrap()),
-    ))
+pub fn ge(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Ge)
 }
 
-pub fn intlit(input: &str) -> IResult<&str, Tokens> {
-    let (rem, ()) = parse_whitespace(input)?;
-    let (rem, x) = alt([hex_num, int, character, newline]).parse(rem)?;
-    let (rem, ()) = parse_whitespace(rem)?;
+pub fn le(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Le)
+}
+
+pub fn plus(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Plus)
+}
 
-    Ok((rem, x))
+pub fn minus(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Minus)
 }
 
-fn comment(input: &str) -> IResult<&str, &str> {
-    let res = pair(tag("//"), not_line_ending).parse(input)?;
-    Ok((res.0, ""))
+pub fn and(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::And)
 }
 
-pub fn eq(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, "=")?;
-    Ok((res.0, Tokens::Eq))
+pub fn or(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Or)
 }
 
-pub fn ne(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, "#")?;
-    Ok((res.0, Tokens::Ne))
+pub fn not(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Not)
 }
 
-pub fn lt(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, "<")?;
-    Ok((res.0, Tokens::Lt))
+pub fn star(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Star)
 }
 
-pub fn gt(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, ">")?;
-    Ok((res.0, Tokens::Gt))
+pub fn slash(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Slash)
 }
 
-pub fn ge(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, ">=")?;
-    Ok((res.0, Tokens::Ge))
+pub fn percent(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Percent)
 }
 
-pub fn le(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, "<=")?;
-    Ok((res.0, Tokens::Le))
+pub fn amp(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Amp)
 }
 
-pub fn plus(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, "+")?;
-    Ok((res.0, Tokens::Plus))
+pub fn pipe(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Pipe)
 }
 
-pub fn minus(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, "-")?;
-    Ok((res.0, Tokens::Minus))
+pub fn caret(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Caret)
 }
 
-pub fn star(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, "*")?;
-    Ok((res.0, Tokens::Star))
+pub fn shl(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Shl)
 }
 
-pub fn slash(input: &str) -> IResult<&str, Tokens> {
-    let res = parse_tag(input, "/")?;
-    Ok((res.0, Tokens::Slash))
+pub fn shr(input: &[Token]) -> IResult<&[Token], Token> {
+    parse_tag(input, &Tokens::Shr)
 }
@@ -1,5 +1,51 @@
+use std::fmt;
+
+use crate::diagnostics::Span;
+
+/// The kinds of tokens of SPL. Keywords are reserved and cannot be used as identifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Tokens {
-    Other,
+    // Keywords
+    Array,
+    Break,
+    Case,
+    Const,
+    Continue,
+    Do,
+    Else,
+    End,
+    False,
+    For,
+    If,
+    Import,
+    Of,
+    Proc,
+    Record,
+    Ref,
+    Repeat,
+    Return,
+    Step,
+    To,
+    True,
+    Type,
+    Until,
+    Var,
+    While,
+
+    // Punctuation
+    LParen,
+    RParen,
+    LBrack,
+    RBrack,
+    LCurl,
+    RCurl,
+    Asgn,
+    Colon,
+    Comma,
+    Semic,
+    Dot,
+
+    // Operators
     Eq,
     Ne,
     Lt,
@@ -10,6 +56,200 @@ pub enum Tokens {
     Minus,
     Star,
     Slash,
+    Percent,
+    Amp,
+    Pipe,
+    Caret,
+    Shl,
+    Shr,
+    And,
+    Or,
+    Not,
+
     Ident(String),
+    /// Decimal, hexadecimal (`0x1F`) and character (`'a'`, `'\n'`) literals
     Intlit(i32),
+    /// A string literal like `"lib/io.spl"`, without the quotes
+    Strlit(String),
+
+    /// The end of the input, always the last token
+    Eof,
+}
+
+impl Tokens {
+    pub const KEYWORDS: [(&str, Self); 25] = [
+        ("array", Self::Array),
+        ("break", Self::Break),
+        ("case", Self::Case),
+        ("const", Self::Const),
+        ("continue", Self::Continue),
+        ("do", Self::Do),
+        ("else", Self::Else),
+        ("end", Self::End),
+        ("false", Self::False),
+        ("for", Self::For),
+        ("if", Self::If),
+        ("import", Self::Import),
+        ("of", Self::Of),
+        ("proc", Self::Proc),
+        ("record", Self::Record),
+        ("ref", Self::Ref),
+        ("repeat", Self::Repeat),
+        ("return", Self::Return),
+        ("step", Self::Step),
+        ("to", Self::To),
+        ("true", Self::True),
+        ("type", Self::Type),
+        ("until", Self::Until),
+        ("var", Self::Var),
+        ("while", Self::While),
+    ];
+
+    /// Punctuation and operators, longer ones first.
+    pub const SYMBOLS: [(&str, Self); 30] = [
+        (":=", Self::Asgn),
+        ("<=", Self::Le),
+        (">=", Self::Ge),
+        ("&&", Self::And),
+        ("||", Self::Or),
+        ("<<", Self::Shl),
+        (">>", Self::Shr),
+        ("(", Self::LParen),
+        (")", Self::RParen),
+        ("[", Self::LBrack),
+        ("]", Self::RBrack),
+        ("{", Self::LCurl),
+        ("}", Self::RCurl),
+        (":", Self::Colon),
+        (",", Self::Comma),
+        (";", Self::Semic),
+        (".", Self::Dot),
+        ("=", Self::Eq),
+        ("#", Self::Ne),
+        ("<", Self::Lt),
+        (">", Self::Gt),
+        ("+", Self::Plus),
+        ("-", Self::Minus),
+        ("*", Self::Star),
+        ("/", Self::Slash),
+        ("%", Self::Percent),
+        ("&", Self::Amp),
+        ("|", Self::Pipe),
+        ("^", Self::Caret),
+        ("!", Self::Not),
+    ];
+
+    /// The name of the token as used by the reference compiler, e.g. `LPAREN`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Array => "ARRAY",
+            Self::Break => "BREAK",
+            Self::Case => "CASE",
+            Self::Const => "CONST",
+            Self::Continue => "CONTINUE",
+            Self::Do => "DO",
+            Self::Else => "ELSE",
+            Self::End => "END",
+            Self::False => "FALSE",
+            Self::For => "FOR",
+            Self::If => "IF",
+            Self::Import => "IMPORT",
+            Self::Of => "OF",
+            Self::Proc => "PROC",
+            Self::Record => "RECORD",
+            Self::Ref => "REF",
+            Self::Repeat => "REPEAT",
+            Self::Return => "RETURN",
+            Self::Step => "STEP",
+            Self::To => "TO",
+            Self::True => "TRUE",
+            Self::Type => "TYPE",
+            Self::Until => "UNTIL",
+            Self::Var => "VAR",
+            Self::While => "WHILE",
+            Self::LParen => "LPAREN",
+            Self::RParen => "RPAREN",
+            Self::LBrack => "LBRACK",
+            Self::RBrack => "RBRACK",
+            Self::LCurl => "LCURL",
+            Self::RCurl => "RCURL",
+            Self::Asgn => "ASGN",
+            Self::Colon => "COLON",
+            Self::Comma => "COMMA",
+            Self::Semic => "SEMIC",
+            Self::Dot => "DOT",
+            Self::Eq => "EQ",
+            Self::Ne => "NE",
+            Self::Lt => "LT",
+            Self::Gt => "GT",
+            Self::Ge => "GE",
+            Self::Le => "LE",
+            Self::Plus => "PLUS",
+            Self::Minus => "MINUS",
+            Self::Star => "STAR",
+            Self::Slash => "SLASH",
+            Self::Percent => "PERCENT",
+            Self::Amp => "AMP",
+            Self::Pipe => "PIPE",
+            Self::Caret => "CARET",
+            Self::Shl => "SHL",
+            Self::Shr => "SHR",
+            Self::And => "LAND",
+            Self::Or => "LOR",
+            Self::Not => "LNOT",
+            Self::Ident(_) => "IDENT",
+            Self::Intlit(_) => "INTLIT",
+            Self::Strlit(_) => "STRLIT",
+            Self::Eof => "-- EOF --",
+        }
+    }
+}
+
+/// The token as it is written in SPL.
+impl fmt::Display for Tokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = Self::KEYWORDS
+            .iter()
+            .chain(&Self::SYMBOLS)
+            .find(|(_, kind)| kind == self)
+            .map(|(text, _)| *text);
+
+        match (self, text) {
+            (_, Some(text)) => write!(f, "{text}"),
+            (Self::Ident(name), _) => write!(f, "{name}"),
+            (Self::Intlit(value), _) => write!(f, "{value}"),
+            (Self::Strlit(value), _) => write!(f, "\"{value}\""),
+            _ => write!(f, "end of file"),
+        }
+    }
+}
+
+/// A token together with its position in the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: Tokens,
+    pub span: Span,
+}
+
+/// Prints the token like the `--tokens` phase of the reference compiler.
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TOKEN = {}", self.kind.name())?;
+        match &self.kind {
+            Tokens::Ident(name) => write!(f, ", name = {name}")?,
+            Tokens::Intlit(value) => write!(f, ", value = {value}")?,
+            Tokens::Strlit(value) => write!(f, ", value = {value}")?,
+            _ => {}
+        }
+        write!(f, ", line = {}", self.span.line)
+    }
+}
+
+/// A `//` comment. Comments are not part of the token stream,
+/// the lexer keeps them for the formatter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// The comment including the leading `//`
+    pub text: String,
+    pub span: Span,
 }
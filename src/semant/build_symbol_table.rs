@@ -2,28 +2,45 @@ use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::{
     absyn::{
-        absyn::{Definition, Program, TypeExpression},
+        absyn::{Definition, Expression, Program, TypeExpression},
+        const_definition::ConstDefinition,
         parameter_definition::ParameterDefinition,
         procedure_definition::ProcedureDefinition,
+        record_type_expression::RecordTypeExpression,
         type_definition::TypeDefinition,
         variable_definition::VariableDefinition,
     },
+    spl_builtins::PROCEDURES,
     table::{
-        entry::{Entry, Parameter, ProcedureEntry, TypeEntry, VariableEntry},
+        entry::{ConstEntry, Entry, Parameter, ProcedureEntry, TypeEntry, VariableEntry},
         symbol_table::SymbolTable,
-        types::{ArrayType, Type},
+        types::{ArrayType, RecordField, RecordType, Type},
     },
 };
 
-use super::{SemanticError, table_initializer};
+use super::{SemanticError, SemanticErrorKind, SemanticErrors, constant, table_initializer};
 
 /// Builds the global symbol table from the given program.
 ///
 /// Initializes a new global symbol table, inserts built-in types and procedures,
 /// then iterates over all top-level definitions in the program and adds them
 /// to the symbol table.
-/// Returns a reference-counted pointer to the global symbol table or a `SemanticError`.
-pub fn build_symbol_table(program: &Program) -> Result<Rc<RefCell<SymbolTable>>, SemanticError> {
+/// Returns a reference-counted pointer to the global symbol table or all `SemanticErrors`.
+pub fn build_symbol_table(program: &Program) -> Result<Rc<RefCell<SymbolTable>>, SemanticErrors> {
+    let mut errors = Vec::new();
+    let table = enter_program(program, &mut errors);
+    SemanticErrors::check(errors).map(|()| table)
+}
+
+/// Builds the global symbol table like `build_symbol_table`, but goes on after errors.
+///
+/// Erroneous definitions are collected in `errors`. Names with an invalid type are
+/// entered with `Type::Error`, of several definitions with the same name only the
+/// first one is entered.
+pub fn enter_program(
+    program: &Program,
+    errors: &mut Vec<SemanticError>,
+) -> Rc<RefCell<SymbolTable>> {
     // Create a new empty global symbol table wrapped in Rc<RefCell> for shared mutability
     let global_table = Rc::new(RefCell::new(SymbolTable::new()));
 
@@ -31,39 +48,76 @@ pub fn build_symbol_table(program: &Program) -> Result<Rc<RefCell<SymbolTable>>,
     table_initializer::init_symbol_table(&global_table);
 
     // Iterate over all definitions in the program and enter them into the global symbol table
-    program
-        .definitions
-        .iter()
-        .try_for_each(|def| enter_global_def(def, &global_table))?;
+    for def in &program.definitions {
+        enter_global_def(def, &global_table, errors);
+    }
 
-    // Return the initialized global symbol table
-    Ok(global_table)
+    global_table
 }
 
-/// Enters a global definition (procedure or type) into the symbol table.
+/// Enters a global definition (procedure, type or constant) into the symbol table.
 ///
 /// Matches on the definition type and delegates to the appropriate handler.
-/// Updates the symbol table accordingly or returns a `SemanticError`.
+/// Updates the symbol table accordingly and collects all `SemanticError`s.
 pub fn enter_global_def(
     def: &Definition,
     table: &Rc<RefCell<SymbolTable>>,
-) -> Result<(), SemanticError> {
+    errors: &mut Vec<SemanticError>,
+) {
     match def {
         // If it's a procedure definition, process and insert it
         Definition::ProcedureDefinition(procedure_definition) => {
-            let (name, entry) = enter_procedure_def(procedure_definition, table)?;
+            let (name, entry) = enter_procedure_def(procedure_definition, table, errors);
+            let is_builtin = PROCEDURES.iter().any(|(builtin, ..)| *builtin == name);
             let mut t = table.borrow_mut();
-            t.enter(name, entry)?;
+            if is_builtin {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::RedeclarationOfBuiltin(name),
+                    procedure_definition.span,
+                ));
+            } else if let Err(e) = t.enter(name, entry) {
+                errors.push(e.at(procedure_definition.span));
+            }
         }
         // If it's a type definition, process and insert it
         Definition::TypeDefinition(type_definition) => {
             let mut t = table.borrow_mut();
-            let (name, entry) = enter_type_def(type_definition, &t)?;
-            t.enter(name, entry)?;
+            let (name, entry) = enter_type_def(type_definition, &t, errors);
+            if let Err(e) = t.enter(name, entry) {
+                errors.push(e.at(type_definition.span));
+            }
+        }
+        // If it's a constant definition, evaluate and insert it
+        Definition::ConstDefinition(const_definition) => {
+            let mut t = table.borrow_mut();
+            let (name, entry) = enter_const_def(const_definition, &t, errors);
+            if let Err(e) = t.enter(name, entry) {
+                errors.push(e.at(const_definition.span));
+            }
         }
+        // The definitions of imported files are part of the program already
+        Definition::ImportDefinition(_) => {}
     }
+}
 
-    Ok(())
+/// Processes a constant definition and returns the corresponding symbol table entry.
+///
+/// Evaluates the constant expression with the constants defined so far.
+/// Constants that cannot be evaluated are entered with `Type::Error`.
+pub fn enter_const_def(
+    def: &ConstDefinition,
+    table: &SymbolTable,
+    errors: &mut Vec<SemanticError>,
+) -> (String, Entry) {
+    let entry = constant::evaluate(&def.value, table).unwrap_or_else(|e| {
+        errors.push(e);
+        ConstEntry {
+            typ: Type::Error,
+            value: 0,
+        }
+    });
+
+    (def.name.clone(), Entry::ConstEntry(entry))
 }
 
 /// Processes a type definition and returns the corresponding symbol table entry.
@@ -73,99 +127,180 @@ pub fn enter_global_def(
 pub fn enter_type_def(
     def: &TypeDefinition,
     table: &SymbolTable,
-) -> Result<(String, Entry), SemanticError> {
+    errors: &mut Vec<SemanticError>,
+) -> (String, Entry) {
     // Convert the abstract syntax type expression into an internal Type representation
     let entry = TypeEntry {
-        typ: type_expression_to_type(&def.type_expression, table)?,
+        typ: type_expression_to_type(&def.type_expression, table, errors),
     };
 
-    Ok((def.name.clone(), Entry::TypeEntry(entry)))
+    (def.name.clone(), Entry::TypeEntry(entry))
 }
 
 /// Processes a procedure definition and returns the corresponding symbol table entry.
 ///
 /// Creates a new local symbol table for the procedure, enters parameters and variables,
-/// and collects parameter metadata. Returns the name and `ProcedureEntry`.
+/// and collects parameter metadata and the return type. Returns the name and `ProcedureEntry`.
 pub fn enter_procedure_def(
     def: &ProcedureDefinition,
     table: &Rc<RefCell<SymbolTable>>,
-) -> Result<(String, Entry), SemanticError> {
+    errors: &mut Vec<SemanticError>,
+) -> (String, Entry) {
     // Create a new local symbol table for the procedure with a reference to the global one
     let mut local_table = SymbolTable {
         entries: HashMap::new(),
         upper_level: Some(Rc::downgrade(table)),
     };
 
-    // Insert all parameters into the local symbol table
-    def.parameters
-        .iter()
-        .try_for_each(|def| enter_param_def(def, &mut local_table))?;
-
-    // Collect parameter metadata (name, type, reference status)
+    // Insert all parameters into the local symbol table and
+    // collect parameter metadata (name, type, reference status)
     let parameters = def
         .parameters
         .iter()
-        .map(|param| {
-            let param_type = match type_expression_to_type(&param.type_expression, &local_table) {
-                Ok(typ) => typ,
-                Err(err) => return Err(err),
-            };
-            Ok(Parameter {
-                name: param.name.clone(),
-                typ: param_type,
-                is_reference: param.is_reference,
-            })
+        .map(|param| Parameter {
+            name: param.name.clone(),
+            typ: enter_param_def(param, &mut local_table, errors),
+            is_reference: param.is_reference,
         })
-        .collect::<Result<Vec<Parameter>, SemanticError>>()?;
+        .collect();
 
     // Insert all local variables into the procedure's local symbol table
-    def.variables
-        .iter()
-        .try_for_each(|def| enter_var_def(def, &mut local_table))?;
+    for var in &def.variables {
+        enter_var_def(var, &mut local_table, errors);
+    }
+
+    // Functions return values of primitive types only
+    let return_type = def.return_type.as_ref().map(|te| {
+        let typ = type_expression_to_type(te, &local_table, errors);
+        if typ.is_composite() {
+            errors.push(SemanticError::new(
+                SemanticErrorKind::IllegalReturnType(def.name.clone()),
+                te.span(),
+            ));
+            return Type::Error;
+        }
+        typ
+    });
 
-    // Construct the procedure entry with its local symbol table and parameters
+    // Construct the procedure entry with its local symbol table, parameters and result type
     let entry = ProcedureEntry {
         local_table,
         parameters,
+        return_type,
+        side_effects: None,
+        is_eliminated: false,
     };
 
-    Ok((def.name.clone(), Entry::ProcedureEntry(entry)))
+    (def.name.clone(), Entry::ProcedureEntry(entry))
 }
 
 /// Converts a type expression (abstract syntax) to a concrete Type representation.
 ///
-/// Handles named types and array types by recursively resolving base types.
-/// Returns an error if the type is unknown or invalid.
+/// Handles named types, array types and record types by recursively resolving base types.
+/// Unknown or invalid types are reported and resolved to `Type::Error`.
 pub fn type_expression_to_type(
     type_ex: &TypeExpression,
     table: &SymbolTable,
-) -> Result<Type, SemanticError> {
+    errors: &mut Vec<SemanticError>,
+) -> Type {
     match type_ex {
         // For array types, recursively resolve the base type and wrap it with size info
         TypeExpression::ArrayTypeExpression(array_type_expression) => {
-            Ok(Type::ArrayType(ArrayType {
-                base_type: Box::new(type_expression_to_type(
-                    &array_type_expression.base_type,
-                    table,
-                )?),
-                size: array_type_expression.array_size,
-            }))
-        }
-        // For named types, look them up in the symbol table
-        TypeExpression::NamedTypeExpression(nte) => {
-            let Some(entry) = table.lookup(nte) else {
-                return Err(SemanticError {
-                    msg: format!("Type {nte} not found"),
-                });
-            };
-            // Validate that the entry is actually a type
-            match entry {
-                Entry::TypeEntry(type_entry) => Ok(type_entry.typ),
-                _ => Err(SemanticError {
-                    msg: format!("{nte} is not a type"),
+            let size = array_size(&array_type_expression.array_size, table, errors);
+            match (
+                type_expression_to_type(&array_type_expression.base_type, table, errors),
+                size,
+            ) {
+                (Type::Error, _) | (_, None) => Type::Error,
+                (base_type, Some(size)) => Type::ArrayType(ArrayType {
+                    base_type: Box::new(base_type),
+                    size,
                 }),
             }
         }
+        // For record types, resolve the type of every field
+        TypeExpression::RecordTypeExpression(record_type_expression) => {
+            record_type(record_type_expression, table, errors)
+        }
+        // For named types, look them up in the symbol table and validate
+        // that the entry is actually a type
+        TypeExpression::NamedTypeExpression(nte, span) => match table.lookup(nte) {
+            Some(Entry::TypeEntry(type_entry)) => type_entry.typ,
+            Some(_) => {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::NotAType(nte.clone()),
+                    *span,
+                ));
+                Type::Error
+            }
+            None => {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::UndefinedType(nte.clone()),
+                    *span,
+                ));
+                Type::Error
+            }
+        },
+    }
+}
+
+/// Resolves the fields of a record type, field names must be unique.
+/// A single erroneous field makes the whole record erroneous.
+fn record_type(
+    def: &RecordTypeExpression,
+    table: &SymbolTable,
+    errors: &mut Vec<SemanticError>,
+) -> Type {
+    let mut fields: Vec<RecordField> = Vec::new();
+    let mut is_error = false;
+    for field in &def.fields {
+        let typ = type_expression_to_type(&field.type_expression, table, errors);
+        if fields.iter().any(|f| f.name == field.name) {
+            errors.push(SemanticError::new(
+                SemanticErrorKind::RedeclarationAsField(field.name.clone()),
+                field.span,
+            ));
+            is_error = true;
+        }
+        is_error |= typ.is_error();
+        fields.push(RecordField {
+            name: field.name.clone(),
+            typ,
+        });
+    }
+
+    if is_error {
+        Type::Error
+    } else {
+        Type::RecordType(RecordType { fields })
+    }
+}
+
+/// Evaluates the size of an array type, a non-negative integer constant.
+/// Returns `None` if the size is erroneous.
+fn array_size(
+    expr: &Expression,
+    table: &SymbolTable,
+    errors: &mut Vec<SemanticError>,
+) -> Option<usize> {
+    match constant::evaluate(expr, table) {
+        Ok(ConstEntry {
+            typ: Type::Error, ..
+        }) => None,
+        Ok(ConstEntry { typ, value }) if typ == Type::INT && value >= 0 => {
+            usize::try_from(value).ok()
+        }
+        Ok(_) => {
+            errors.push(SemanticError::new(
+                SemanticErrorKind::IllegalArraySize,
+                expr.span(),
+            ));
+            None
+        }
+        Err(e) => {
+            errors.push(e);
+            None
+        }
     }
 }
 
@@ -176,31 +311,49 @@ pub fn type_expression_to_type(
 pub fn enter_var_def(
     def: &VariableDefinition,
     table: &mut SymbolTable,
-) -> Result<(), SemanticError> {
+    errors: &mut Vec<SemanticError>,
+) {
     let entry = VariableEntry {
-        typ: type_expression_to_type(&def.type_expression, table)?,
+        typ: type_expression_to_type(&def.type_expression, table, errors),
         is_reference: false,
     };
 
-    table.enter(def.name.clone(), Entry::VariableEntry(entry))?;
-
-    Ok(())
+    if let Err(e) = table.enter(def.name.clone(), Entry::VariableEntry(entry)) {
+        errors.push(e.at(def.span));
+    }
 }
 
 /// Enters a parameter definition into the symbol table.
 ///
 /// Converts the parameter's type expression to a Type and inserts a `VariableEntry`.
-/// The parameter's reference flag is preserved.
+/// The parameter's reference flag is preserved, arrays and records must be passed by reference.
+/// Returns the type of the parameter.
 pub fn enter_param_def(
     def: &ParameterDefinition,
     table: &mut SymbolTable,
-) -> Result<(), SemanticError> {
+    errors: &mut Vec<SemanticError>,
+) -> Type {
+    let typ = type_expression_to_type(&def.type_expression, table, errors);
+    if typ.is_composite() && !def.is_reference {
+        errors.push(SemanticError::new(
+            SemanticErrorKind::MustBeAReferenceParameter(def.name.clone()),
+            def.span,
+        ));
+    }
+
     let entry = VariableEntry {
-        typ: type_expression_to_type(&def.type_expression, table)?,
+        typ: typ.clone(),
         is_reference: def.is_reference,
     };
 
-    table.enter(def.name.clone(), Entry::VariableEntry(entry))?;
-
-    Ok(())
+    if table
+        .enter(def.name.clone(), Entry::VariableEntry(entry))
+        .is_err()
+    {
+        errors.push(SemanticError::new(
+            SemanticErrorKind::RedeclarationAsParameter(def.name.clone()),
+            def.span,
+        ));
+    }
+    typ
 }
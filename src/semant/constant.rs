@@ -0,0 +1,114 @@
+use crate::{
+    absyn::{
+        absyn::{Expression, Variable},
+        binary_expression::Operator,
+        unary_expression::UnaryOperator,
+    },
+    table::{
+        entry::{ConstEntry, Entry},
+        symbol_table::SymbolTable,
+        types::Type,
+    },
+};
+
+use super::{SemanticError, SemanticErrorKind};
+
+/// Evaluates a constant expression during the semantic analysis.
+///
+/// Constant expressions consist of literals, named constants defined before
+/// and operators. The first error stops the evaluation. Constants with an
+/// erroneous definition evaluate to `Type::Error`, which is not reported again.
+pub fn evaluate(expr: &Expression, table: &SymbolTable) -> Result<ConstEntry, SemanticError> {
+    match expr {
+        Expression::IntLiteral(value, _) => Ok(ConstEntry {
+            typ: Type::INT,
+            value: *value,
+        }),
+        Expression::BoolLiteral(value, _) => Ok(ConstEntry {
+            typ: Type::BOOL,
+            value: i32::from(*value),
+        }),
+        Expression::VariableExpression(var) => match var.as_ref() {
+            Variable::NamedVariable(name, span) => match table.lookup(name) {
+                Some(Entry::ConstEntry(entry)) => Ok(entry),
+                Some(_) => Err(SemanticError::new(SemanticErrorKind::NotAConstant, *span)),
+                None => Err(SemanticError::new(
+                    SemanticErrorKind::UndefinedVariable(name.clone()),
+                    *span,
+                )),
+            },
+            Variable::ArrayAccess(_) | Variable::FieldAccess(_) => Err(SemanticError::new(
+                SemanticErrorKind::NotAConstant,
+                var.span(),
+            )),
+        },
+        Expression::CallExpression(call) => Err(SemanticError::new(
+            SemanticErrorKind::NotAConstant,
+            call.span,
+        )),
+        Expression::StringLiteral(_, span) => {
+            Err(SemanticError::new(SemanticErrorKind::NotAConstant, *span))
+        }
+        Expression::UnaryExpression(unary) => {
+            let operand = evaluate(&unary.operand, table)?;
+            if operand.typ.is_error() {
+                return Ok(operand);
+            }
+            let typ = unary
+                .operator
+                .result_type(&operand.typ)
+                .map_err(|kind| SemanticError::new(kind, unary.span))?;
+            let value = match unary.operator {
+                UnaryOperator::Minus => operand.value.checked_neg(),
+                UnaryOperator::Not => Some(1 - operand.value),
+            };
+            value
+                .map(|value| ConstEntry { typ, value })
+                .ok_or_else(|| SemanticError::new(SemanticErrorKind::ConstantOverflow, unary.span))
+        }
+        Expression::BinaryExpression(binary) => {
+            let left = evaluate(&binary.left, table)?;
+            let right = evaluate(&binary.right, table)?;
+            if left.typ.is_error() {
+                return Ok(left);
+            }
+            if right.typ.is_error() {
+                return Ok(right);
+            }
+            let typ = binary
+                .operator
+                .result_type(&left.typ, &right.typ)
+                .map_err(|kind| SemanticError::new(kind, binary.span))?;
+
+            let (l, r) = (left.value, right.value);
+            let value = match binary.operator {
+                Operator::Add => l.checked_add(r),
+                Operator::Sub => l.checked_sub(r),
+                Operator::Mul => l.checked_mul(r),
+                Operator::Div | Operator::Mod if r == 0 => {
+                    return Err(SemanticError::new(
+                        SemanticErrorKind::ConstantDivisionByZero,
+                        binary.span,
+                    ));
+                }
+                Operator::Div => l.checked_div(r),
+                Operator::Mod => l.checked_rem(r),
+                Operator::BitXor => Some(l ^ r),
+                Operator::Shl => Some(l << (r & 31)),
+                Operator::Shr => Some(l >> (r & 31)),
+                Operator::Equ => Some(i32::from(l == r)),
+                Operator::Neq => Some(i32::from(l != r)),
+                Operator::Lst => Some(i32::from(l < r)),
+                Operator::Lse => Some(i32::from(l <= r)),
+                Operator::Grt => Some(i32::from(l > r)),
+                Operator::Gre => Some(i32::from(l >= r)),
+                // Booleans are 0 or 1, the logical operators work like the bitwise ones
+                Operator::And | Operator::BitAnd => Some(l & r),
+                Operator::Or | Operator::BitOr => Some(l | r),
+            };
+            value
+                .map(|value| ConstEntry { typ, value })
+                .ok_or_else(|| SemanticError::new(SemanticErrorKind::ConstantOverflow, binary.span))
+        }
+    }
+}
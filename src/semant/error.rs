@@ -0,0 +1,305 @@
+use std::fmt;
+
+use crate::diagnostics::Span;
+
+/// The semantic errors of SPL. Messages and exit codes are the ones of the
+/// reference compiler, so both compilers can be used with the same scripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SemanticErrorKind {
+    UndefinedType(String),
+    NotAType(String),
+    RedeclarationAsType(String),
+    MustBeAReferenceParameter(String),
+    RedeclarationAsProcedure(String),
+    /// A procedure with the name of a builtin procedure, e.g. `printi`
+    RedeclarationOfBuiltin(String),
+    RedeclarationAsParameter(String),
+    RedeclarationAsVariable(String),
+    IllegalAssignment,
+    AssignmentRequiresIntegerVariable,
+    IfConditionMustBeBoolean,
+    WhileConditionMustBeBoolean,
+    UndefinedProcedure(String),
+    CallOfNonProcedure(String),
+    /// Arguments are counted from 1
+    ArgumentTypeMismatch(String, usize),
+    ArgumentMustBeAVariable(String, usize),
+    ArgumentCountMismatch(String),
+    OperandTypeMismatch,
+    ComparisonNonInteger,
+    ArithmeticOperatorNonInteger,
+    /// `&&`, `||` or `!` applied to a non-boolean operand, unknown to the reference compiler
+    LogicalOperatorNonBoolean,
+    UndefinedVariable(String),
+    NotAVariable(String),
+    IndexingNonArray,
+    IndexingWithNonInteger,
+    MainIsMissing,
+    MainIsNotAProcedure,
+    MainMustNotHaveParameters,
+    /// The following errors concern functions, which the reference compiler doesn't know
+    IllegalReturnType(String),
+    ReturnValueInProcedure(String),
+    MissingReturnValue(String),
+    ReturnTypeMismatch(String),
+    /// The end of a function can be reached without a `return`
+    MissingReturn(String),
+    /// A procedure without return value used in an expression
+    CallOfProcedureWithoutValue(String),
+    /// Errors of the loops besides `while`, also unknown to the reference compiler
+    RepeatConditionMustBeBoolean,
+    /// The variable, the start or the end of a `for` loop is not an integer
+    ForRequiresInteger,
+    ForStepIsZero,
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+    /// Errors of named constants and constant array sizes, also unknown to the reference compiler
+    RedeclarationAsConstant(String),
+    /// A variable or a call in a constant expression
+    NotAConstant,
+    ConstantDivisionByZero,
+    ConstantOverflow,
+    /// An array size that is negative or not an integer
+    IllegalArraySize,
+    /// Errors of records, also unknown to the reference compiler
+    RedeclarationAsField(String),
+    UndefinedField(String),
+    FieldAccessNonRecord,
+    /// Errors of case statements, also unknown to the reference compiler
+    CaseSelectorMustBeInteger,
+    /// A label that is not an integer, e.g. `true`
+    CaseLabelNonInteger,
+    DuplicateCaseLabel(i32),
+}
+
+impl SemanticErrorKind {
+    /// The exit code of the compiler if this is the first error of a program.
+    pub const fn exit_code(&self) -> i32 {
+        match self {
+            Self::UndefinedType(_) => 101,
+            Self::NotAType(_) => 102,
+            Self::RedeclarationAsType(_) => 103,
+            Self::MustBeAReferenceParameter(_) => 104,
+            Self::RedeclarationAsProcedure(_) | Self::RedeclarationOfBuiltin(_) => 105,
+            Self::RedeclarationAsParameter(_) => 106,
+            Self::RedeclarationAsVariable(_) => 107,
+            Self::IllegalAssignment => 108,
+            Self::AssignmentRequiresIntegerVariable => 109,
+            Self::IfConditionMustBeBoolean => 110,
+            Self::WhileConditionMustBeBoolean => 111,
+            Self::UndefinedProcedure(_) => 112,
+            Self::CallOfNonProcedure(_) => 113,
+            Self::ArgumentTypeMismatch(..) => 114,
+            Self::ArgumentMustBeAVariable(..) => 115,
+            Self::ArgumentCountMismatch(_) => 116,
+            Self::OperandTypeMismatch => 117,
+            Self::ComparisonNonInteger => 118,
+            Self::ArithmeticOperatorNonInteger => 119,
+            Self::UndefinedVariable(_) => 120,
+            Self::NotAVariable(_) => 121,
+            Self::IndexingNonArray => 122,
+            Self::IndexingWithNonInteger => 123,
+            Self::MainIsMissing => 125,
+            Self::MainIsNotAProcedure => 126,
+            Self::MainMustNotHaveParameters => 127,
+            Self::LogicalOperatorNonBoolean => 128,
+            Self::IllegalReturnType(_) => 129,
+            Self::ReturnValueInProcedure(_) => 130,
+            Self::MissingReturnValue(_) => 131,
+            Self::ReturnTypeMismatch(_) => 132,
+            Self::MissingReturn(_) => 133,
+            Self::CallOfProcedureWithoutValue(_) => 134,
+            Self::RepeatConditionMustBeBoolean => 135,
+            Self::ForRequiresInteger => 136,
+            Self::ForStepIsZero => 137,
+            Self::BreakOutsideLoop => 138,
+            Self::ContinueOutsideLoop => 139,
+            Self::RedeclarationAsConstant(_) => 140,
+            Self::NotAConstant => 141,
+            Self::ConstantDivisionByZero => 142,
+            Self::ConstantOverflow => 143,
+            Self::IllegalArraySize => 144,
+            Self::RedeclarationAsField(_) => 145,
+            Self::UndefinedField(_) => 146,
+            Self::FieldAccessNonRecord => 147,
+            Self::CaseSelectorMustBeInteger => 148,
+            Self::CaseLabelNonInteger => 149,
+            Self::DuplicateCaseLabel(_) => 150,
+        }
+    }
+}
+
+impl fmt::Display for SemanticErrorKind {
+    #[expect(clippy::too_many_lines)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UndefinedType(name) => write!(f, "undefined type {name}"),
+            Self::NotAType(name) => write!(f, "{name} is not a type"),
+            Self::RedeclarationAsType(name) => write!(f, "redeclaration of {name} as type"),
+            Self::MustBeAReferenceParameter(name) => {
+                write!(f, "parameter {name} must be a reference parameter")
+            }
+            Self::RedeclarationAsProcedure(name) => {
+                write!(f, "redeclaration of {name} as procedure")
+            }
+            Self::RedeclarationOfBuiltin(name) => {
+                write!(f, "redeclaration of predefined procedure {name}")
+            }
+            Self::RedeclarationAsParameter(name) => {
+                write!(f, "redeclaration of {name} as parameter")
+            }
+            Self::RedeclarationAsVariable(name) => {
+                write!(f, "redeclaration of {name} as variable")
+            }
+            Self::IllegalAssignment => write!(f, "assignment has different types"),
+            Self::AssignmentRequiresIntegerVariable => {
+                write!(f, "assignment requires integer variable")
+            }
+            Self::IfConditionMustBeBoolean => {
+                write!(f, "'if' test expression must be of type boolean")
+            }
+            Self::WhileConditionMustBeBoolean => {
+                write!(f, "'while' test expression must be of type boolean")
+            }
+            Self::UndefinedProcedure(name) => write!(f, "undefined procedure {name}"),
+            Self::CallOfNonProcedure(name) => write!(f, "call of non-procedure {name}"),
+            Self::ArgumentTypeMismatch(name, i) => {
+                write!(f, "argument #{i} type mismatch in call of procedure {name}")
+            }
+            Self::ArgumentMustBeAVariable(name, i) => {
+                write!(
+                    f,
+                    "argument #{i} must be a variable in call of procedure {name}"
+                )
+            }
+            Self::ArgumentCountMismatch(name) => {
+                write!(f, "argument count mismatch in call of procedure {name}")
+            }
+            Self::OperandTypeMismatch => write!(f, "expression combines different types"),
+            Self::ComparisonNonInteger => write!(f, "comparison requires integer operands"),
+            Self::ArithmeticOperatorNonInteger => {
+                write!(f, "arithmetic operation requires integer operands")
+            }
+            Self::LogicalOperatorNonBoolean => {
+                write!(f, "logical operation requires boolean operands")
+            }
+            Self::UndefinedVariable(name) => write!(f, "undefined variable {name}"),
+            Self::NotAVariable(name) => write!(f, "{name} is not a variable"),
+            Self::IndexingNonArray => write!(f, "illegal indexing a non-array"),
+            Self::IndexingWithNonInteger => write!(f, "illegal indexing with a non-integer"),
+            Self::MainIsMissing => write!(f, "procedure 'main' is missing"),
+            Self::MainIsNotAProcedure => write!(f, "'main' is not a procedure"),
+            Self::MainMustNotHaveParameters => {
+                write!(f, "procedure 'main' must not have any parameters")
+            }
+            Self::IllegalReturnType(name) => {
+                write!(f, "function {name} must not return an array or record")
+            }
+            Self::ReturnValueInProcedure(name) => {
+                write!(f, "procedure {name} cannot return a value")
+            }
+            Self::MissingReturnValue(name) => {
+                write!(f, "return without value in function {name}")
+            }
+            Self::ReturnTypeMismatch(name) => {
+                write!(f, "return value type mismatch in function {name}")
+            }
+            Self::MissingReturn(name) => {
+                write!(f, "function {name} may end without returning a value")
+            }
+            Self::CallOfProcedureWithoutValue(name) => {
+                write!(f, "procedure {name} does not return a value")
+            }
+            Self::RepeatConditionMustBeBoolean => {
+                write!(f, "'until' test expression must be of type boolean")
+            }
+            Self::ForRequiresInteger => {
+                write!(f, "'for' loop requires integer variable and bounds")
+            }
+            Self::ForStepIsZero => write!(f, "'for' step must not be zero"),
+            Self::BreakOutsideLoop => write!(f, "'break' outside of a loop"),
+            Self::ContinueOutsideLoop => write!(f, "'continue' outside of a loop"),
+            Self::RedeclarationAsConstant(name) => {
+                write!(f, "redeclaration of {name} as constant")
+            }
+            Self::NotAConstant => write!(f, "expression is not constant"),
+            Self::ConstantDivisionByZero => {
+                write!(f, "division by zero in constant expression")
+            }
+            Self::ConstantOverflow => write!(f, "integer overflow in constant expression"),
+            Self::IllegalArraySize => {
+                write!(f, "array size must be a non-negative integer")
+            }
+            Self::RedeclarationAsField(name) => write!(f, "redeclaration of {name} as field"),
+            Self::UndefinedField(name) => write!(f, "undefined field {name}"),
+            Self::FieldAccessNonRecord => write!(f, "illegal field access of a non-record"),
+            Self::CaseSelectorMustBeInteger => {
+                write!(f, "'case' selector must be of type integer")
+            }
+            Self::CaseLabelNonInteger => write!(f, "'case' label must be an integer"),
+            Self::DuplicateCaseLabel(value) => write!(f, "duplicate 'case' label {value}"),
+        }
+    }
+}
+
+// Represents a semantic error with its kind and the code it refers to
+#[derive(Debug)]
+pub struct SemanticError {
+    pub kind: SemanticErrorKind,
+    pub span: Option<Span>,
+}
+
+impl SemanticError {
+    pub const fn new(kind: SemanticErrorKind, span: Span) -> Self {
+        Self {
+            kind,
+            span: Some(span),
+        }
+    }
+
+    // Attaches a span to errors that were created without one, e.g. by `SymbolTable::enter`
+    pub fn at(self, span: Span) -> Self {
+        Self {
+            span: self.span.or(Some(span)),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+/// All semantic errors of a program, sorted by position.
+/// Errors without a position, like a missing `main`, come last.
+#[derive(Debug)]
+pub struct SemanticErrors(pub Vec<SemanticError>);
+
+impl SemanticErrors {
+    // Sorts the errors by position, `Ok` if there are none
+    pub(super) fn check(mut errors: Vec<SemanticError>) -> Result<(), Self> {
+        if errors.is_empty() {
+            return Ok(());
+        }
+        errors.sort_by_key(|e| e.span.map_or(usize::MAX, |span| span.start));
+        Err(Self(errors))
+    }
+
+    /// The exit code of the reference compiler, which stops at the first error.
+    pub fn exit_code(&self) -> i32 {
+        self.0.first().map_or(0, |e| e.kind.exit_code())
+    }
+}
+
+impl fmt::Display for SemanticErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors = self.0.iter().map(ToString::to_string).collect::<Vec<_>>();
+        write!(f, "{}", errors.join("\n"))
+    }
+}
+
+impl std::error::Error for SemanticErrors {}
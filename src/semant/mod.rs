@@ -1,276 +1,543 @@
 // Declare submodules used for building and initializing the symbol table
 pub mod build_symbol_table;
+mod constant;
+mod error;
 pub mod table_initializer;
 mod utils;
 
-use std::{cell::RefCell, fmt, rc::Rc};
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+pub use error::{SemanticError, SemanticErrorKind, SemanticErrors};
 
 use crate::{
     absyn::{
-        absyn::{Definition, Expression, Statement, Variable},
+        absyn::{Definition, Expression, Program, Statement, Variable},
+        call_statement::CallStatement,
+        case_statement::CaseStatement,
+        for_statement::ForStatement,
         procedure_definition::ProcedureDefinition,
     },
+    spl_builtins::PROCEDURES,
     table::{
-        entry::Entry,
+        entry::{ConstEntry, Entry},
         symbol_table::SymbolTable,
         types::{PrimitiveType, Type},
     },
 };
 
-// Represents a semantic error with a message (and optional position info)
-#[derive(Debug)]
-pub struct SemanticError {
-    pub msg: String,
-    // Position could be added for better error tracking
-    // pos: i64,
-}
-
-impl fmt::Display for SemanticError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "SemanticError: \"{}\"", self.msg)?;
-        // write!(f, "          pos: {}", self.pos)?;
-        Ok(())
+/// Builds the symbol table and checks all procedures of the program.
+/// The analysis goes on after errors, all of them are reported at once.
+pub fn check_program(program: &mut Program) -> Result<Rc<RefCell<SymbolTable>>, SemanticErrors> {
+    let mut errors = Vec::new();
+    let table = build_symbol_table::enter_program(program, &mut errors);
+
+    // Only the first of several definitions with the same name is in the table,
+    // procedures named like a builtin are not entered at all
+    let mut checked = PROCEDURES
+        .iter()
+        .map(|(name, ..)| (*name).to_string())
+        .collect::<HashSet<_>>();
+    for def in &mut program.definitions {
+        if let Definition::ProcedureDefinition(p) = def.as_mut()
+            && checked.insert(p.name.clone())
+        {
+            check_def_proc(p, &table, &mut errors);
+        }
     }
-}
 
-impl std::error::Error for SemanticError {}
+    check_main(program, &table.borrow(), &mut errors);
+
+    SemanticErrors::check(errors).map(|()| table)
+}
 
 /* --- Global Declarations and Checks --------------------- */
 
-// Check a global definition (either type or procedure)
-pub fn check_def_global(
-    def: &mut Definition,
-    table: &Rc<RefCell<SymbolTable>>,
-) -> Result<(), SemanticError> {
-    match def {
-        Definition::TypeDefinition(_) => Ok(()), // Type definitions need no further checking here
-        Definition::ProcedureDefinition(p) => check_def_proc(p, table), // Check procedure body
-    }
+// Check that `main` is a procedure without parameters and return value
+fn check_main(program: &Program, table: &SymbolTable, errors: &mut Vec<SemanticError>) {
+    // The first definition of `main` is the one in the symbol table
+    let span = program
+        .definitions
+        .iter()
+        .find_map(|def| match def.as_ref() {
+            Definition::ProcedureDefinition(p) if p.name == "main" => Some(p.span),
+            Definition::TypeDefinition(t) if t.name == "main" => Some(t.span),
+            Definition::ConstDefinition(c) if c.name == "main" => Some(c.span),
+            _ => None,
+        });
+
+    let kind = match table.lookup("main") {
+        None => SemanticErrorKind::MainIsMissing,
+        Some(Entry::ProcedureEntry(entry)) if entry.return_type.is_some() => {
+            SemanticErrorKind::MainIsNotAProcedure
+        }
+        Some(Entry::ProcedureEntry(entry)) if entry.parameters.is_empty() => return,
+        Some(Entry::ProcedureEntry(_)) => SemanticErrorKind::MainMustNotHaveParameters,
+        Some(_) => SemanticErrorKind::MainIsNotAProcedure,
+    };
+    errors.push(SemanticError { kind, span });
 }
 
 // Check a full procedure definition and validate its body
 fn check_def_proc(
     proc: &mut ProcedureDefinition,
     table: &Rc<RefCell<SymbolTable>>,
-) -> Result<(), SemanticError> {
+    errors: &mut Vec<SemanticError>,
+) {
     // Get the corresponding local symbol table from the procedure entry
-    let table = table.borrow();
-    let local_table: &SymbolTable = &table
-        .lookup(&proc.name)
-        .and_then(|e| {
-            if let Entry::ProcedureEntry(e) = e {
-                Some(e)
-            } else {
-                None
-            }
-        })
-        .expect("check_def_proc: given procedure not in symbol table")
-        .local_table;
-    drop(table);
+    let Some(Entry::ProcedureEntry(entry)) = table.borrow().lookup(&proc.name) else {
+        // The name is used by another definition, which is reported already
+        return;
+    };
 
     // Check all statements in the procedure body
-    proc.body
-        .iter_mut()
-        .try_for_each(|s| check_statement(s, local_table))?;
+    let context = Context {
+        proc_name: &proc.name,
+        return_type: entry.return_type.as_ref(),
+        in_loop: false,
+    };
+    for s in &mut proc.body {
+        check_statement(s, &entry.local_table, &context, errors);
+    }
+
+    // The end of a function must not be reachable
+    if entry.return_type.is_some() && !proc.body.iter().any(Statement::always_returns) {
+        errors.push(SemanticError::new(
+            SemanticErrorKind::MissingReturn(proc.name.clone()),
+            proc.span,
+        ));
+    }
+}
 
-    Ok(())
+// Where a statement is located: the procedure, needed for its `return` statements,
+// and whether `break` and `continue` have a loop to leave
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    proc_name: &'a str,
+    return_type: Option<&'a Type>,
+    in_loop: bool,
+}
+
+impl Context<'_> {
+    // The context of a loop body
+    const fn in_loop(&self) -> Self {
+        Self {
+            in_loop: true,
+            ..*self
+        }
+    }
 }
 
 /* --- Statement Checks ----------------------------------- */
 
 // Check a single statement (e.g., if, while, call, assign, compound, etc.)
-fn check_statement(statement: &mut Statement, table: &SymbolTable) -> Result<(), SemanticError> {
+fn check_statement(
+    statement: &mut Statement,
+    table: &SymbolTable,
+    context: &Context,
+    errors: &mut Vec<SemanticError>,
+) {
     match statement {
         Statement::IfStatement(s) => {
             // Ensure the condition is of type boolean
-            let cond_expr_type = check_expression(&mut s.condition, table)?;
-            if !cond_expr_type.is_bool() {
-                return Err(SemanticError {
-                    msg: format!("IfConditionMustBeBoolean: {s:?}"),
-                });
+            let cond_expr_type = check_expression(&mut s.condition, table, errors);
+            if !cond_expr_type.conforms_to(&Type::BOOL) {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::IfConditionMustBeBoolean,
+                    s.condition.span(),
+                ));
             }
 
             // Check both branches of the if-statement
-            check_statement(&mut s.then_branch, table)?;
+            check_statement(&mut s.then_branch, table, context, errors);
             if let Some(ref mut s) = s.else_branch {
-                check_statement(s, table)?;
+                check_statement(s, table, context, errors);
             }
-
-            Ok(())
         }
 
-        Statement::EmptyStatement => Ok(()), // No check needed for empty statements
+        Statement::EmptyStatement(_) => {} // No check needed for empty statements
 
         Statement::CallStatement(s) => {
-            // Check if the procedure exists in the symbol table
-            let proc = table.lookup(&s.name).ok_or_else(|| SemanticError {
-                msg: format!("UndefinedIdentifier: {s:?}"),
-            })?;
-
-            // Ensure it's actually a procedure entry
-            let Entry::ProcedureEntry(proc) = proc else {
-                return Err(SemanticError {
-                    msg: format!("CallOfNonProcedure: {proc:?} {s:?}"),
-                });
-            };
-
-            // Check the argument count
-            if s.arguments.len() != proc.parameters.len() {
-                return Err(SemanticError {
-                    msg: format!(
-                        "ArgumentCountMismatch: {:?} {:?}",
-                        s.arguments, proc.parameters
-                    ),
-                });
-            }
+            // A returned value is discarded
+            check_call(s, table, errors);
+        }
 
-            // Check types of each argument and ensure reference arguments are variables
-            for (i, (arg, param)) in s
-                .arguments
-                .iter_mut()
-                .zip(proc.parameters.iter())
-                .enumerate()
-            {
-                let arg_type = check_expression(arg, table)?;
-                if arg_type != param.typ {
-                    return Err(SemanticError {
-                        msg: format!(
-                            "ArgumentTypeMismatch: {}(): arg {i}: [{param:?}] {arg:?}",
-                            s.name
-                        ),
-                    });
-                }
-                if param.is_reference && !arg.is_variable() {
-                    return Err(SemanticError {
-                        msg: format!(
-                            "ArgumentMustBeAVariable: {}(): arg {i}: [{param:?}] {arg:?}",
-                            s.name
-                        ),
-                    });
+        Statement::ReturnStatement(s) => {
+            let value_type = s
+                .value
+                .as_mut()
+                .map(|value| check_expression(value, table, errors));
+
+            // Functions return a value of their type, procedures return nothing
+            let kind = match (value_type, context.return_type) {
+                (None, None) => return,
+                (Some(value_type), Some(return_type)) if value_type.conforms_to(return_type) => {
+                    return;
                 }
-            }
-
-            Ok(())
+                (Some(_), Some(_)) => SemanticErrorKind::ReturnTypeMismatch,
+                (Some(_), None) => SemanticErrorKind::ReturnValueInProcedure,
+                (None, Some(_)) => SemanticErrorKind::MissingReturnValue,
+            };
+            errors.push(SemanticError::new(
+                kind(context.proc_name.to_string()),
+                s.span,
+            ));
         }
 
         Statement::WhileStatement(s) => {
             // Check that while condition is boolean
-            let cond_expr_type = check_expression(&mut s.condition, table)?;
-            if !cond_expr_type.is_bool() {
-                return Err(SemanticError {
-                    msg: format!("WhileConditionMustBeBoolean: {s:?}"),
-                });
+            let cond_expr_type = check_expression(&mut s.condition, table, errors);
+            if !cond_expr_type.conforms_to(&Type::BOOL) {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::WhileConditionMustBeBoolean,
+                    s.condition.span(),
+                ));
             }
 
             // Check the loop body
-            check_statement(&mut s.body, table)?;
+            check_statement(&mut s.body, table, &context.in_loop(), errors);
+        }
 
-            Ok(())
+        Statement::ForStatement(s) => check_for_statement(s, table, context, errors),
+
+        Statement::RepeatStatement(s) => {
+            check_statement(&mut s.body, table, &context.in_loop(), errors);
+
+            // Check that the condition after the body is boolean
+            let cond_expr_type = check_expression(&mut s.condition, table, errors);
+            if !cond_expr_type.conforms_to(&Type::BOOL) {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::RepeatConditionMustBeBoolean,
+                    s.condition.span(),
+                ));
+            }
+        }
+
+        Statement::CaseStatement(s) => check_case_statement(s, table, context, errors),
+
+        Statement::BreakStatement(span) => {
+            if !context.in_loop {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::BreakOutsideLoop,
+                    *span,
+                ));
+            }
+        }
+
+        Statement::ContinueStatement(span) => {
+            if !context.in_loop {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::ContinueOutsideLoop,
+                    *span,
+                ));
+            }
         }
 
         Statement::AssignStatement(s) => {
             // Get types of both sides of the assignment
-            let target_type = check_variable(&mut s.target, table)?;
-            let value_type = check_expression(&mut s.value, table)?;
-
-            // Ensure they are the same and not assigning to an array
-            if target_type.is_array() || target_type != value_type {
-                return Err(SemanticError {
-                    msg: format!("IllegalAssignment: {s:?}"),
-                });
+            let target_type = check_variable(&mut s.target, table, errors);
+            let value_type = check_expression(&mut s.value, table, errors);
+
+            // Ensure they are the same and the target is not an array or record
+            if !value_type.conforms_to(&target_type) {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::IllegalAssignment,
+                    s.span,
+                ));
+            } else if target_type.is_composite() {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::AssignmentRequiresIntegerVariable,
+                    s.span,
+                ));
             }
+        }
+
+        Statement::CompoundStatement(s, _) => {
+            // Check all statements inside compound (block) statement
+            for s in s {
+                check_statement(s, table, context, errors);
+            }
+        }
+    }
+}
+
+// Check a for loop: the variable, the start and the end are integers
+fn check_for_statement(
+    statement: &mut ForStatement,
+    table: &SymbolTable,
+    context: &Context,
+    errors: &mut Vec<SemanticError>,
+) {
+    let types = [
+        check_variable(&mut statement.variable, table, errors),
+        check_expression(&mut statement.start, table, errors),
+        check_expression(&mut statement.end, table, errors),
+    ];
+    if !types.iter().all(|t| t.conforms_to(&Type::INT)) {
+        errors.push(SemanticError::new(
+            SemanticErrorKind::ForRequiresInteger,
+            statement.span,
+        ));
+    }
+    if statement.step == 0 {
+        errors.push(SemanticError::new(
+            SemanticErrorKind::ForStepIsZero,
+            statement.span,
+        ));
+    }
+
+    check_statement(&mut statement.body, table, &context.in_loop(), errors);
+}
+
+// Check a case statement: the selector is an integer, the labels are distinct
+// integer constants, whose values are stored for the code generation
+fn check_case_statement(
+    statement: &mut CaseStatement,
+    table: &SymbolTable,
+    context: &Context,
+    errors: &mut Vec<SemanticError>,
+) {
+    let selector_type = check_expression(&mut statement.selector, table, errors);
+    if !selector_type.conforms_to(&Type::INT) {
+        errors.push(SemanticError::new(
+            SemanticErrorKind::CaseSelectorMustBeInteger,
+            statement.selector.span(),
+        ));
+    }
 
-            Ok(())
+    let mut seen = HashSet::new();
+    for branch in &mut statement.branches {
+        branch.values.clear();
+        for label in &branch.labels {
+            let kind = match constant::evaluate(label, table) {
+                Ok(ConstEntry {
+                    typ: Type::Error, ..
+                }) => continue,
+                Ok(ConstEntry { typ, value }) if typ == Type::INT => {
+                    if seen.insert(value) {
+                        branch.values.push(value);
+                        continue;
+                    }
+                    SemanticErrorKind::DuplicateCaseLabel(value)
+                }
+                Ok(_) => SemanticErrorKind::CaseLabelNonInteger,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            errors.push(SemanticError::new(kind, label.span()));
         }
+        check_statement(&mut branch.statement, table, context, errors);
+    }
+    if let Some(ref mut s) = statement.default {
+        check_statement(s, table, context, errors);
+    }
+}
 
-        Statement::CompoundStatement(s) =>
-        // Check all statements inside compound (block) statement
-        {
-            s.iter_mut().try_for_each(|s| check_statement(s, table))
+// Check a call of a procedure and return its result type, `None` for a procedure
+// without return value and `Type::Error` if the procedure is erroneous
+fn check_call(
+    call: &mut CallStatement,
+    table: &SymbolTable,
+    errors: &mut Vec<SemanticError>,
+) -> Option<Type> {
+    // Check if the procedure exists in the symbol table and is a procedure
+    let proc = match table.lookup(&call.name) {
+        Some(Entry::ProcedureEntry(proc)) => Some(proc),
+        Some(_) => {
+            errors.push(SemanticError::new(
+                SemanticErrorKind::CallOfNonProcedure(call.name.clone()),
+                call.span,
+            ));
+            None
+        }
+        None => {
+            errors.push(SemanticError::new(
+                SemanticErrorKind::UndefinedProcedure(call.name.clone()),
+                call.span,
+            ));
+            None
         }
+    };
+
+    // The arguments are checked even if the procedure is unknown
+    let arg_types = call
+        .arguments
+        .iter_mut()
+        .map(|arg| check_expression(arg, table, errors))
+        .collect::<Vec<_>>();
+    let Some(proc) = proc else {
+        return Some(Type::Error);
+    };
+
+    // Check the argument count
+    if call.arguments.len() != proc.parameters.len() {
+        errors.push(SemanticError::new(
+            SemanticErrorKind::ArgumentCountMismatch(call.name.clone()),
+            call.span,
+        ));
+        return proc.return_type;
     }
+
+    // Check types of each argument and ensure reference arguments are variables
+    for (i, ((arg, arg_type), param)) in call
+        .arguments
+        .iter()
+        .zip(arg_types)
+        .zip(proc.parameters.iter())
+        .enumerate()
+    {
+        if !arg_type.conforms_to(&param.typ) {
+            errors.push(SemanticError::new(
+                SemanticErrorKind::ArgumentTypeMismatch(call.name.clone(), i + 1),
+                arg.span(),
+            ));
+        } else if param.is_reference && (!arg.is_variable() || is_constant(arg, table)) {
+            errors.push(SemanticError::new(
+                SemanticErrorKind::ArgumentMustBeAVariable(call.name.clone(), i + 1),
+                arg.span(),
+            ));
+        }
+    }
+
+    proc.return_type
 }
 
 /* --- Expression Checks ---------------------------------- */
 
-// Check an expression and return its resulting type
-fn check_expression<'a>(
-    expr: &'a mut Expression,
-    table: &'a SymbolTable,
-) -> Result<Type, SemanticError> {
+// Check an expression and return its resulting type, `Type::Error` if it is erroneous
+fn check_expression(
+    expr: &mut Expression,
+    table: &SymbolTable,
+    errors: &mut Vec<SemanticError>,
+) -> Type {
     match expr {
         Expression::BinaryExpression(expr) => {
             // Recursively check both operands
-            let left_type = check_expression(&mut expr.left, table)?;
-            let right_type = check_expression(&mut expr.right, table)?;
+            let left_type = check_expression(&mut expr.left, table, errors);
+            let right_type = check_expression(&mut expr.right, table, errors);
 
             // Validate and get resulting type from the operator
-            let Some(result_type) = expr.operator.result_type(&left_type, &right_type) else {
-                return Err(SemanticError {
-                    msg: format!("OperandTypeMismatch: {expr:?}"),
-                });
-            };
-
-            Ok(result_type)
+            expr.operator
+                .result_type(&left_type, &right_type)
+                .unwrap_or_else(|kind| {
+                    errors.push(SemanticError::new(kind, expr.span));
+                    Type::Error
+                })
         }
         Expression::UnaryExpression(expr) => {
-            let right_type = check_expression(&mut expr.operand, table)?;
-
-            let Some(result_type) = expr.operator.result_type(&right_type) else {
-                return Err(SemanticError {
-                    msg: format!("OperandTypeMismatch: {expr:?}"),
-                });
-            };
-
-            Ok(result_type)
+            let right_type = check_expression(&mut expr.operand, table, errors);
+
+            expr.operator
+                .result_type(&right_type)
+                .unwrap_or_else(|kind| {
+                    errors.push(SemanticError::new(kind, expr.span));
+                    Type::Error
+                })
+        }
+        Expression::IntLiteral(..) => Type::PrimitiveType(PrimitiveType::Int), // Constant int
+        Expression::BoolLiteral(..) => Type::PrimitiveType(PrimitiveType::Bool), // Constant bool
+        Expression::StringLiteral(..) => Type::PrimitiveType(PrimitiveType::String),
+        Expression::VariableExpression(var) => match var.as_ref() {
+            // Named constants may be used like variables in expressions
+            Variable::NamedVariable(name, _)
+                if let Some(Entry::ConstEntry(entry)) = table.lookup(name) =>
+            {
+                entry.typ
+            }
+            _ => check_variable(var, table, errors), // Delegate to variable check
+        },
+        Expression::CallExpression(call) => {
+            // Only functions may be called in expressions
+            check_call(call, table, errors).unwrap_or_else(|| {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::CallOfProcedureWithoutValue(call.name.clone()),
+                    call.span,
+                ));
+                Type::Error
+            })
         }
-        Expression::IntLiteral(_) => Ok(Type::PrimitiveType(PrimitiveType::Int)), // Constant int
-        Expression::VariableExpression(var) => check_variable(var, table), // Delegate to variable check
     }
 }
 
+// Check whether an expression is a named constant
+fn is_constant(expr: &Expression, table: &SymbolTable) -> bool {
+    matches!(expr, Expression::VariableExpression(var)
+        if matches!(var.as_ref(), Variable::NamedVariable(name, _)
+            if matches!(table.lookup(name), Some(Entry::ConstEntry(_)))))
+}
+
 /* --- Variable Checks ------------------------------------ */
 
-// Check that a variable is defined and return its type
-fn check_variable(var: &mut Variable, table: &SymbolTable) -> Result<Type, SemanticError> {
+// Check that a variable is defined and return its type, `Type::Error` if it is erroneous
+fn check_variable(
+    var: &mut Variable,
+    table: &SymbolTable,
+    errors: &mut Vec<SemanticError>,
+) -> Type {
     match var {
-        Variable::NamedVariable(var_name) => {
-            // Lookup variable in symbol table
-            let entry = table.lookup(var_name).ok_or_else(|| SemanticError {
-                msg: format!("UndefinedIdentifier: {var:?}"),
-            })?;
-
-            // Ensure it's a variable
-            let Entry::VariableEntry(entry) = entry else {
-                return Err(SemanticError {
-                    msg: format!("NotAVariable: {entry:?} {var:?}"),
-                });
+        Variable::NamedVariable(var_name, span) => {
+            // Lookup variable in symbol table and ensure it's a variable
+            let kind = match table.lookup(var_name) {
+                Some(Entry::VariableEntry(entry)) => return entry.typ,
+                Some(_) => SemanticErrorKind::NotAVariable(var_name.clone()),
+                None => SemanticErrorKind::UndefinedVariable(var_name.clone()),
             };
-
-            Ok(entry.typ)
+            errors.push(SemanticError::new(kind, *span));
+            Type::Error
         }
 
         Variable::ArrayAccess(arr_acc) => {
-            // Ensure the array is an actual array type
-            let array_type = check_variable(&mut arr_acc.array, table)?;
-            let Type::ArrayType(array_type) = array_type else {
-                return Err(SemanticError {
-                    msg: format!("IndexingNonArray: {var:?}"),
-                });
-            };
-
-            // Store the type in the AST node for future use
-            arr_acc.typ = Some(array_type.clone());
+            let array_type = check_variable(&mut arr_acc.array, table, errors);
 
             // Check that the index is an integer
-            let index_type = check_expression(&mut arr_acc.index, table)?;
-            if !index_type.is_int() {
-                return Err(SemanticError {
-                    msg: format!("IndexTypeMismatch: {var:?}"),
-                });
+            let index_type = check_expression(&mut arr_acc.index, table, errors);
+            if !index_type.conforms_to(&Type::INT) {
+                errors.push(SemanticError::new(
+                    SemanticErrorKind::IndexingWithNonInteger,
+                    arr_acc.index.span(),
+                ));
             }
 
-            Ok(*array_type.base_type)
+            // Ensure the array is an actual array type
+            match array_type {
+                Type::ArrayType(array_type) => {
+                    // Store the type in the AST node for future use
+                    arr_acc.typ = Some(array_type.clone());
+                    *array_type.base_type
+                }
+                Type::Error => Type::Error,
+                Type::PrimitiveType(_) | Type::RecordType(_) => {
+                    errors.push(SemanticError::new(
+                        SemanticErrorKind::IndexingNonArray,
+                        arr_acc.array.span(),
+                    ));
+                    Type::Error
+                }
+            }
+        }
+
+        Variable::FieldAccess(field_acc) => {
+            let record_type = check_variable(&mut field_acc.record, table, errors);
+
+            // Ensure the variable is a record with a field of this name
+            let kind = match record_type {
+                Type::RecordType(record_type) => {
+                    if let Some((_, typ)) = record_type.field(&field_acc.field) {
+                        let typ = typ.clone();
+                        // Store the type in the AST node for future use
+                        field_acc.typ = Some(record_type);
+                        return typ;
+                    }
+                    SemanticErrorKind::UndefinedField(field_acc.field.clone())
+                }
+                Type::Error => return Type::Error,
+                Type::PrimitiveType(_) | Type::ArrayType(_) => {
+                    SemanticErrorKind::FieldAccessNonRecord
+                }
+            };
+            errors.push(SemanticError::new(kind, field_acc.span));
+            Type::Error
         }
     }
 }
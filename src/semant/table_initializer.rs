@@ -4,7 +4,6 @@ use crate::spl_builtins::{NAMED_TYPES, PROCEDURES};
 use crate::table::{
     entry::{Entry, ProcedureEntry, TypeEntry},
     symbol_table::SymbolTable,
-    types::Type,
 };
 
 /// Initializes the global symbol table with built-in types and procedures.
@@ -16,12 +15,11 @@ pub fn init_symbol_table(s_t: &Rc<RefCell<SymbolTable>>) {
     let mut symbol_table = s_t.borrow_mut();
 
     // Insert all built-in types (from NAMED_TYPES) into the symbol table
-    for t in NAMED_TYPES {
-        // Each type is inserted as a TypeEntry; for simplicity, all map to Type::INT here
+    for (name, typ) in NAMED_TYPES {
         symbol_table
             .enter(
-                t.to_string(),                                  // Convert type name to a String
-                Entry::TypeEntry(TypeEntry { typ: Type::INT }), // Insert a type entry with INT type
+                name.to_string(),                    // Convert type name to a String
+                Entry::TypeEntry(TypeEntry { typ }), // Insert a type entry with its primitive type
             )
             .unwrap(); // Unwrap to panic on error (should not fail here)
     }
@@ -40,6 +38,9 @@ pub fn init_symbol_table(s_t: &Rc<RefCell<SymbolTable>>) {
                         upper_level: Some(Rc::downgrade(s_t)), // Link to the enclosing symbol table
                     },
                     parameters: params.to_vec(), // Copy parameter list
+                    return_type: None,           // Builtins return nothing
+                    side_effects: None,          // Filled in by the call graph
+                    is_eliminated: false,        // Builtins are never removed
                 }),
             )
             .unwrap(); // Again, unwrap to handle only expected successful cases
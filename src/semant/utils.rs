@@ -1,5 +1,10 @@
 use crate::{
-    absyn::{absyn::Expression, binary_expression::Operator, unary_expression::UnaryOperator},
+    absyn::{
+        absyn::{Expression, Statement},
+        binary_expression::Operator,
+        unary_expression::UnaryOperator,
+    },
+    semant::SemanticErrorKind,
     table::types::{PrimitiveType, Type},
 };
 
@@ -11,60 +16,129 @@ impl Type {
     // Constant for the primitive boolean type
     pub const BOOL: Self = Self::PrimitiveType(PrimitiveType::Bool);
 
-    // Check if a type is an array type
-    pub const fn is_array(&self) -> bool {
-        matches!(self, Self::ArrayType(_))
+    // Constant for the type of string literals
+    pub const STRING: Self = Self::PrimitiveType(PrimitiveType::String);
+
+    // Check if a type is an array or record type, which cannot be assigned,
+    // passed by value or returned as a whole
+    pub const fn is_composite(&self) -> bool {
+        matches!(self, Self::ArrayType(_) | Self::RecordType(_))
     }
 
-    // Check if a type is boolean (i.e., matches the BOOL constant)
-    pub fn is_bool(&self) -> bool {
-        self == &Self::BOOL
+    // Check if a type belongs to an erroneous expression, which is already reported
+    pub const fn is_error(&self) -> bool {
+        matches!(self, Self::Error)
     }
 
-    // Check if a type is integer (i.e., matches the INT constant)
-    pub fn is_int(&self) -> bool {
-        self == &Self::INT
+    // Check if a value of this type may be used where `expected` is required;
+    // erroneous types are compatible with everything
+    pub fn conforms_to(&self, expected: &Self) -> bool {
+        self == expected || self.is_error() || expected.is_error()
     }
 }
 
 // Implement utility method for Expression to check if it's a variable expression
 impl Expression {
-    // Returns true if the expression is a variable (e.g., `x`, `arr[i]`)
+    // Returns true if the expression is a variable (e.g., `x`, `arr[i]`, `t.kind`)
     pub const fn is_variable(&self) -> bool {
         matches!(self, Self::VariableExpression(_))
     }
 }
 
+// Implement the check for functions that end without a return value
+impl Statement {
+    // Returns true if every path through the statement ends with a `return`;
+    // loops may not be entered or be left by `break`, so they never count
+    pub fn always_returns(&self) -> bool {
+        match self {
+            Self::ReturnStatement(_) => true,
+            Self::IfStatement(s) => {
+                s.then_branch.always_returns()
+                    && s.else_branch.as_ref().is_some_and(Self::always_returns)
+            }
+            Self::CaseStatement(s) => {
+                s.branches.iter().all(|b| b.statement.always_returns())
+                    && s.default.as_ref().is_some_and(Self::always_returns)
+            }
+            Self::CompoundStatement(statements, _) => statements.iter().any(|s| s.always_returns()),
+            Self::AssignStatement(_)
+            | Self::WhileStatement(_)
+            | Self::ForStatement(_)
+            | Self::RepeatStatement(_)
+            | Self::CallStatement(_)
+            | Self::BreakStatement(_)
+            | Self::ContinueStatement(_)
+            | Self::EmptyStatement(_) => false,
+        }
+    }
+}
+
 // Operator implementation for determining the result type of a binary operation
 impl Operator {
-    // Returns the resulting type of applying the operator to two operand types
-    pub fn result_type(self, left_type: &Type, right_type: &Type) -> Option<Type> {
-        // All binary operations defined here require integer operands
-        if !left_type.is_int() || !right_type.is_int() {
-            return None;
+    // Comparisons and logical operators result in BOOL
+    pub const fn is_boolean(self) -> bool {
+        self.is_comparison() || matches!(self, Self::And | Self::Or)
+    }
+
+    // Comparisons require INT operands and result in BOOL
+    pub const fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            Self::Equ | Self::Neq | Self::Lst | Self::Lse | Self::Grt | Self::Gre
+        )
+    }
+
+    // Returns the resulting type of applying the operator to two operand types,
+    // or the error of the reference compiler for invalid operands
+    pub fn result_type(
+        self,
+        left_type: &Type,
+        right_type: &Type,
+    ) -> Result<Type, SemanticErrorKind> {
+        let is_comparison = self.is_comparison();
+
+        // Both operands must have the same type
+        if !left_type.conforms_to(right_type) {
+            return Err(SemanticErrorKind::OperandTypeMismatch);
+        }
+
+        // Logical operations require and result in BOOL
+        if matches!(self, Self::And | Self::Or) {
+            return if left_type.conforms_to(&Type::BOOL) && right_type.conforms_to(&Type::BOOL) {
+                Ok(Type::BOOL)
+            } else {
+                Err(SemanticErrorKind::LogicalOperatorNonBoolean)
+            };
+        }
+
+        // All other binary operations require integer operands
+        if !left_type.conforms_to(&Type::INT) || !right_type.conforms_to(&Type::INT) {
+            return Err(if is_comparison {
+                SemanticErrorKind::ComparisonNonInteger
+            } else {
+                SemanticErrorKind::ArithmeticOperatorNonInteger
+            });
         }
 
         // Arithmetic operations result in INT
         // Comparison operations result in BOOL
-        Some(match self {
-            Self::Add | Self::Sub | Self::Mul | Self::Div => Type::INT,
-            Self::Equ | Self::Neq | Self::Lst | Self::Lse | Self::Grt | Self::Gre => Type::BOOL,
-        })
+        Ok(if is_comparison { Type::BOOL } else { Type::INT })
     }
 }
 
 // UnaryOperator implementation for determining the result type of a unary operation
 impl UnaryOperator {
     // Returns the resulting type of applying the operator to a single operand type
-    pub fn result_type(self, right_type: &Type) -> Option<Type> {
-        // All unary operators in this language currently only apply to integers
-        if !(right_type.is_int()) {
-            return None;
+    pub fn result_type(self, right_type: &Type) -> Result<Type, SemanticErrorKind> {
+        // Negation keeps INT, logical not keeps BOOL
+        let (typ, error) = match self {
+            Self::Minus => (Type::INT, SemanticErrorKind::ArithmeticOperatorNonInteger),
+            Self::Not => (Type::BOOL, SemanticErrorKind::LogicalOperatorNonBoolean),
+        };
+        if right_type.conforms_to(&typ) {
+            Ok(typ)
+        } else {
+            Err(error)
         }
-
-        // The only defined unary operator is negation (minus), which keeps INT type
-        Some(match self {
-            Self::Minus => Type::INT,
-        })
     }
 }
@@ -1,5 +1,7 @@
 use std::{
-    cell::{Cell, RefMut},
+    cell::{Cell, RefCell, RefMut},
+    collections::VecDeque,
+    fmt::{Display, Write as _},
     io::{self, Write as _},
     process,
     time::Instant,
@@ -10,7 +12,7 @@ use crate::{
     table::{entry::Parameter, types::Type},
 };
 
-pub const NAMED_TYPES: [&str; 1] = ["int"];
+pub const NAMED_TYPES: [(&str, Type); 2] = [("int", Type::INT), ("bool", Type::BOOL)];
 
 thread_local! {
     static START_TIME: Cell<Instant> = unreachable!("START_TIME not initialized!");
@@ -19,36 +21,101 @@ pub fn init_start_time() {
     START_TIME.set(Instant::now());
 }
 
+thread_local! {
+    // Input and output of the SPL program, if they are redirected away from stdin and stdout
+    static REDIRECT: RefCell<Option<Redirect>> = const { RefCell::new(None) };
+}
+
+struct Redirect {
+    input: VecDeque<u8>,
+    output: String,
+}
+
+// Runs an SPL program with the given input and returns everything it printed.
+#[cfg(test)]
+pub fn redirect_io(input: &str, run: impl FnOnce()) -> String {
+    REDIRECT.set(Some(Redirect {
+        input: input.bytes().collect(),
+        output: String::new(),
+    }));
+    run();
+    REDIRECT.take().unwrap().output
+}
+
+fn print(value: impl Display) {
+    REDIRECT.with_borrow_mut(|redirect| {
+        if let Some(redirect) = redirect {
+            write!(redirect.output, "{value}").unwrap();
+        } else {
+            print!("{value}");
+            io::stdout().flush().unwrap();
+        }
+    });
+}
+
+fn read_line() -> String {
+    REDIRECT.with_borrow_mut(|redirect| {
+        if let Some(redirect) = redirect {
+            let len = redirect
+                .input
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(redirect.input.len(), |i| i + 1);
+            String::from_utf8(redirect.input.drain(..len).collect()).unwrap()
+        } else {
+            let mut input = String::new();
+            io::stdin().read_line(&mut input).unwrap();
+            input
+        }
+    })
+}
+
+fn read_char() -> i32 {
+    if let Some(c) = REDIRECT.with_borrow_mut(|redirect| {
+        redirect
+            .as_mut()
+            .map(|redirect| redirect.input.pop_front().expect("end of input"))
+    }) {
+        return i32::from(c);
+    }
+
+    #[cfg(not(feature = "console-crate"))]
+    {
+        use std::{io::Read as _, slice};
+
+        let mut input = 0u8;
+        io::stdin().read_exact(slice::from_mut(&mut input)).unwrap();
+        i32::from(input)
+    }
+    #[cfg(feature = "console-crate")]
+    {
+        use console::Term;
+
+        Term::stdout().read_char().unwrap() as i32
+    }
+}
+
 builtin_procedures! {
     proc printi(i: int) {
-        print!("{i}");
-        io::stdout().flush().unwrap();
+        print(i);
     }
     proc printc(c: int) {
         let c = u8::try_from(c).unwrap_or_else(|_| panic!("Argument to printc() should be a valid ASCII value: {c}")) as char;
-        print!("{c}");
-        io::stdout().flush().unwrap();
+        print(c);
+    }
+    proc prints(s: string) {
+        // The same characters as for printc() are allowed, TAC prints strings with it
+        if let Some(c) = s.chars().find(|c| u8::try_from(*c).is_err()) {
+            panic!("Argument to prints() should only contain valid ASCII values: {c}");
+        }
+        print(s);
     }
     proc readi(ref i: int) {
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
+        let input = read_line();
         *i = input.trim().parse::<i32>().unwrap_or_else(|_| panic!("{input} is not a number"));
     }
     proc readc(ref c: int) {
-        #[cfg(not(feature = "console-crate"))]
-        {
-            use std::{slice, io::Read as _};
-
-            let mut input = 0u8;
-            io::stdin().read_exact(slice::from_mut(&mut input)).unwrap();
-            *c = i32::from(input);
-        }
-        #[cfg(feature = "console-crate")]
-        {
-            use console::Term;
-
-            *c = Term::stdout().read_char().unwrap() as i32;
-        }
+        *c = read_char();
     }
     proc exit() {
         process::exit(0)
@@ -86,7 +153,8 @@ macro_rules! builtin_procedures {
     (@param     $name:ident: $type:ident) => { Parameter::new(String::new(), builtin_procedures!(@type $type), false) };
     (@param ref $name:ident: $type:ident) => { Parameter::new(String::new(), builtin_procedures!(@type $type), true ) };
 
-    (@type int) => { Type::INT };
+    (@type int   ) => { Type::INT    };
+    (@type string) => { Type::STRING };
 
     (@impl |$($ref:ident $($name:ident)?: $type:ident),*|             ) => { None };
     (@impl |                                            | $body:block ) => { Some(&|_| $body) };
@@ -101,7 +169,7 @@ macro_rules! builtin_procedures {
     };
 
     (@arg $args:ident $type:ident(    $name:ident)) => {
-        let builtin_procedures!(@arg_type $type($name)) = *$args.next().unwrap().borrow() else { unreachable!() };
+        let builtin_procedures!(@arg_type $type($name)) = $args.next().unwrap().borrow().clone() else { unreachable!() };
     };
     (@arg $args:ident $type:ident(ref $name:ident)) => {
         let mut $name = RefMut::map($args.next().unwrap().borrow_mut(), |v| {
@@ -110,8 +178,9 @@ macro_rules! builtin_procedures {
         });
     };
 
-    (@arg_type int ($name:ident)) => { Value::Int ($name) };
-    (@arg_type bool($name:ident)) => { Value::Bool($name) };
+    (@arg_type int   ($name:ident)) => { Value::Int   ($name) };
+    (@arg_type bool  ($name:ident)) => { Value::Bool  ($name) };
+    (@arg_type string($name:ident)) => { Value::String($name) };
 }
 use builtin_procedures;
 
@@ -1,18 +1,28 @@
 #![expect(clippy::enum_variant_names)]
 
+use std::rc::Rc;
+
 use super::{symbol_table::SymbolTable, types::Type};
+use crate::optimizations::call_graph::SideEffects;
 
 #[derive(Debug, Clone)]
 pub enum Entry {
     ProcedureEntry(ProcedureEntry),
     VariableEntry(VariableEntry),
     TypeEntry(TypeEntry),
+    ConstEntry(ConstEntry),
 }
 
 #[derive(Debug, Clone)]
 pub struct ProcedureEntry {
     pub local_table: SymbolTable,
     pub parameters: Vec<Parameter>,
+    /// The result type of a function, `None` for procedures.
+    pub return_type: Option<Type>,
+    /// Interprocedural summary, filled in once the call graph is known.
+    pub side_effects: Option<Rc<SideEffects>>,
+    /// Set if the procedure was removed by dead procedure elimination.
+    pub is_eliminated: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +36,13 @@ pub struct TypeEntry {
     pub typ: Type,
 }
 
+#[derive(Debug, Clone)]
+pub struct ConstEntry {
+    pub typ: Type,
+    /// The value of the constant, booleans are stored as 0 and 1 like in the TAC.
+    pub value: i32,
+}
+
 #[derive(Debug, Clone)]
 pub struct Parameter {
     pub name: String,
@@ -2,14 +2,14 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::{Rc, Weak};
 
-use crate::semant::SemanticError;
+use crate::semant::{SemanticError, SemanticErrorKind};
 
 use super::entry::Entry;
 
 #[derive(Debug, Clone)]
 pub struct SymbolTable {
     pub entries: HashMap<String, Entry>,
-    pub upper_level: Option<Weak<RefCell<SymbolTable>>>,
+    pub upper_level: Option<Weak<RefCell<Self>>>,
 }
 
 impl SymbolTable {
@@ -20,11 +20,16 @@ impl SymbolTable {
         }
     }
 
+    // Parameters are entered as variables, their redeclaration is reported by the caller
     pub fn enter(&mut self, name: String, entry: Entry) -> Result<(), SemanticError> {
         if self.entries.contains_key(&name) {
-            return Err(SemanticError {
-                msg: format!("Symbol {name} already defined"),
-            });
+            let kind = match entry {
+                Entry::TypeEntry(_) => SemanticErrorKind::RedeclarationAsType(name),
+                Entry::ProcedureEntry(_) => SemanticErrorKind::RedeclarationAsProcedure(name),
+                Entry::VariableEntry(_) => SemanticErrorKind::RedeclarationAsVariable(name),
+                Entry::ConstEntry(_) => SemanticErrorKind::RedeclarationAsConstant(name),
+            };
+            return Err(SemanticError { kind, span: None });
         }
         self.entries.insert(name, entry);
         Ok(())
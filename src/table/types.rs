@@ -1,9 +1,15 @@
+use std::fmt;
+
 use crate::interpreter::value::Value;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Type {
     ArrayType(ArrayType),
+    RecordType(RecordType),
     PrimitiveType(PrimitiveType),
+    /// The type of an expression or definition with a semantic error. It is accepted
+    /// everywhere, so a single mistake does not cause follow-up errors.
+    Error,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -12,10 +18,24 @@ pub struct ArrayType {
     pub size: usize,
 }
 
+/// Records are equal if their fields have the same names and types in the same order.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RecordType {
+    pub fields: Vec<RecordField>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RecordField {
+    pub name: String,
+    pub typ: Type,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum PrimitiveType {
     Int,
     Bool,
+    /// Only string literals have this type, they are arguments of builtins like `prints`
+    String,
 }
 
 impl Type {
@@ -28,10 +48,51 @@ impl Type {
                 }
                 Value::Array(arr)
             }
+            Self::RecordType(record_type) => Value::Record(
+                record_type
+                    .fields
+                    .iter()
+                    .map(|field| Value::new_refcell(field.typ.default_value()))
+                    .collect(),
+            ),
             Self::PrimitiveType(primitive_type) => match primitive_type {
                 PrimitiveType::Int => Value::Int(0),
                 PrimitiveType::Bool => Value::Bool(false),
+                PrimitiveType::String => unreachable!("there are no string variables"),
             },
+            Self::Error => unreachable!("programs with semantic errors are not run"),
+        }
+    }
+}
+
+impl RecordType {
+    /// The position and the type of the field with the given name.
+    pub fn field(&self, name: &str) -> Option<(usize, &Type)> {
+        self.fields
+            .iter()
+            .enumerate()
+            .find(|(_, field)| field.name == name)
+            .map(|(i, field)| (i, &field.typ))
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ArrayType(array_type) => {
+                write!(f, "array [{}] of {}", array_type.size, array_type.base_type)
+            }
+            Self::RecordType(record_type) => {
+                write!(f, "record")?;
+                for field in &record_type.fields {
+                    write!(f, " {}: {};", field.name, field.typ)?;
+                }
+                write!(f, " end")
+            }
+            Self::PrimitiveType(PrimitiveType::Int) => write!(f, "int"),
+            Self::PrimitiveType(PrimitiveType::Bool) => write!(f, "boolean"),
+            Self::PrimitiveType(PrimitiveType::String) => write!(f, "string"),
+            Self::Error => write!(f, "{{error}}"),
         }
     }
 }
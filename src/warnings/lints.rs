@@ -0,0 +1,493 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    absyn::{
+        absyn::{Expression, Statement, Variable},
+        procedure_definition::ProcedureDefinition,
+    },
+    base_blocks::{BlockContent, BlockGraph},
+    code_gen::quadrupel::{Quadrupel, QuadrupelArg, QuadrupelOp, QuadrupelResult, QuadrupelVar},
+    diagnostics::Span,
+    optimizations::{
+        constant_propagation::{ConstantPropagation, Constness},
+        live_variables::LiveVariables,
+        worklist::{GetVarIdx, Worklist},
+    },
+    table::{
+        entry::{Entry, ProcedureEntry, VariableEntry},
+        symbol_table::SymbolTable,
+        types::Type,
+    },
+};
+
+use super::{Lint, Location, Warning};
+
+/// Runs the lints that only look at a single procedure.
+pub(super) fn check_procedure(
+    proc_def: &ProcedureDefinition,
+    graph: &BlockGraph,
+    proc_entry: &ProcedureEntry,
+    symbol_table: &SymbolTable,
+) -> Vec<Warning> {
+    let name = &proc_def.name;
+    let local_table = &proc_entry.local_table;
+    let accesses = Accesses::new(graph, symbol_table);
+    let mut warnings = Vec::new();
+
+    // Variables that are never read are reported once, not at every assignment
+    let mut unread = HashSet::new();
+    for var in &proc_def.variables {
+        if accesses.read.contains(&var.name) {
+            continue;
+        }
+        let message = if accesses.written.contains(&var.name) {
+            format!("variable `{}` is assigned but never read", var.name)
+        } else {
+            format!("unused variable `{}`", var.name)
+        };
+        warnings.push(
+            Warning::new(Lint::UnusedVariable, name, Location::Procedure, message)
+                .with_span(Some(var.span)),
+        );
+        unread.insert(var.name.as_str());
+    }
+
+    // Reference parameters may also be used to return a value
+    for param in &proc_def.parameters {
+        let used = accesses.read.contains(&param.name)
+            || (param.is_reference && accesses.written.contains(&param.name));
+        if !used {
+            warnings.push(
+                Warning::new(
+                    Lint::UnusedParameter,
+                    name,
+                    Location::Procedure,
+                    format!("unused parameter `{}`", param.name),
+                )
+                .with_span(Some(param.span)),
+            );
+            unread.insert(param.name.as_str());
+        }
+    }
+
+    let is_local_scalar = |var: &QuadrupelVar| {
+        let QuadrupelVar::Spl(var_name) = var else {
+            return false;
+        };
+        let is_value = proc_def.variables.iter().any(|v| v.name == *var_name)
+            || proc_def
+                .parameters
+                .iter()
+                .any(|p| p.name == *var_name && !p.is_reference);
+        is_value
+            && !unread.contains(var_name.as_str())
+            && matches!(
+                local_table.entries.get(var_name),
+                Some(Entry::VariableEntry(VariableEntry {
+                    typ: Type::PrimitiveType(_),
+                    ..
+                }))
+            )
+    };
+    let live_variables = LiveVariables::run(graph, local_table);
+    warnings.extend(dead_stores(name, graph, &live_variables, is_local_scalar));
+
+    warnings.extend(
+        unreachable_statements(proc_def)
+            .into_iter()
+            .map(|(n, span)| {
+                Warning::new(
+                    Lint::UnreachableCode,
+                    name,
+                    Location::Statement(n),
+                    "unreachable statement".to_string(),
+                )
+                .with_span(Some(span))
+            }),
+    );
+
+    let const_prop = ConstantPropagation::run(graph, local_table);
+    let comparisons = comparisons(proc_def);
+    warnings.extend(constant_conditions(
+        name,
+        graph,
+        &const_prop,
+        &comparisons,
+        symbol_table,
+    ));
+
+    warnings
+}
+
+/// The variables a procedure reads and writes.
+struct Accesses {
+    read: HashSet<String>,
+    written: HashSet<String>,
+}
+
+impl Accesses {
+    fn new(graph: &BlockGraph, symbol_table: &SymbolTable) -> Self {
+        let mut read = HashSet::new();
+        let mut written = HashSet::new();
+
+        for block in &graph.blocks {
+            let BlockContent::Code(quads) = &block.content else {
+                continue;
+            };
+            for (quad_idx, quad) in quads.iter().enumerate() {
+                let (is_read, is_written) = if quad.op == QuadrupelOp::Param {
+                    let access = Quadrupel::find_param_access(quads, quad_idx, symbol_table);
+                    // An argument the callee ignores still counts as used
+                    (access.may_read || !access.may_write, access.may_write)
+                } else {
+                    (true, false)
+                };
+
+                for arg in [&quad.arg1, &quad.arg2] {
+                    if let QuadrupelArg::Var(var) = arg {
+                        if is_read {
+                            read.insert(var.to_identifier());
+                        }
+                        if is_written {
+                            written.insert(var.to_identifier());
+                        }
+                    }
+                }
+                if let QuadrupelResult::Var(var) = &quad.result {
+                    written.insert(var.to_identifier());
+                }
+            }
+        }
+
+        Self { read, written }
+    }
+}
+
+/// Assignments whose value is overwritten or never read afterwards.
+fn dead_stores(
+    proc_name: &str,
+    graph: &BlockGraph,
+    live_variables: &LiveVariables,
+    is_candidate: impl Fn(&QuadrupelVar) -> bool,
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for (block_id, block) in graph.blocks.iter().enumerate() {
+        let BlockContent::Code(quads) = &block.content else {
+            continue;
+        };
+        let mut live = live_variables.livout[block_id].clone();
+        let mut block_warnings = Vec::new();
+
+        for (quad_idx, quad) in quads.iter().enumerate().rev() {
+            if let QuadrupelResult::Var(var) = &quad.result
+                && let Some(idx) = live_variables.get_var_idx(var)
+            {
+                if !live[idx] && is_candidate(var) {
+                    block_warnings.push(
+                        Warning::new(
+                            Lint::DeadStore,
+                            proc_name,
+                            Location::Quad { block_id, quad_idx },
+                            format!("value assigned to `{}` is never read", var.to_identifier()),
+                        )
+                        .with_span(quad.span),
+                    );
+                }
+                // Array stores only overwrite a single element
+                if quad.op != QuadrupelOp::ArrayStore {
+                    live.set(idx, false);
+                }
+            }
+
+            for arg in [&quad.arg1, &quad.arg2] {
+                if let QuadrupelArg::Var(var) = arg
+                    && let Some(idx) = live_variables.get_var_idx(var)
+                {
+                    live.set(idx, true);
+                }
+            }
+        }
+
+        warnings.extend(block_warnings.into_iter().rev());
+    }
+
+    warnings
+}
+
+/// Conditional jumps whose operands are constant, replayed from the
+/// constant propagation state at the start of each block.
+fn constant_conditions(
+    proc_name: &str,
+    graph: &BlockGraph,
+    const_prop: &ConstantPropagation,
+    comparisons: &HashMap<(usize, usize), QuadrupelOp>,
+    symbol_table: &SymbolTable,
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for (block_id, block) in graph.blocks.iter().enumerate() {
+        let BlockContent::Code(quads) = &block.content else {
+            continue;
+        };
+        let mut const_state = const_prop.r#in[block_id].clone();
+
+        for (quad_idx, quad) in quads.iter().enumerate() {
+            let fold = |arg: &QuadrupelArg| match arg {
+                QuadrupelArg::Var(var) => {
+                    match const_prop.get_var_idx(var).map(|idx| const_state[idx]) {
+                        Some(Constness::Constant(c)) => QuadrupelArg::Const(c),
+                        _ => arg.clone(),
+                    }
+                }
+                _ => arg.clone(),
+            };
+            let folded = Quadrupel {
+                arg1: fold(&quad.arg1),
+                arg2: fold(&quad.arg2),
+                ..quad.clone()
+            };
+
+            // Short-circuit code jumps on the source condition or on its negation,
+            // boolean values are tested with `# 0` or `= 0`
+            if let Some(jumps) = folded.cmp_const() {
+                let source_op = quad
+                    .span
+                    .and_then(|span| comparisons.get(&(span.start, span.end)))
+                    .copied()
+                    .unwrap_or(QuadrupelOp::Neq);
+                let holds = if quad.op == source_op { jumps } else { !jumps };
+                warnings.push(
+                    Warning::new(
+                        Lint::ConstantCondition,
+                        proc_name,
+                        Location::Quad { block_id, quad_idx },
+                        format!("condition is always {holds}"),
+                    )
+                    .with_span(quad.span),
+                );
+            }
+
+            Constness::from_quad(
+                quad,
+                &mut const_state,
+                |var| const_prop.get_var_idx(var).unwrap(),
+                || {
+                    quad.op == QuadrupelOp::Param
+                        && Quadrupel::find_param_access(quads, quad_idx, symbol_table).may_write
+                },
+            );
+        }
+    }
+
+    warnings
+}
+
+/// The operators of all comparisons of a procedure, by the start and end of their span.
+fn comparisons(proc_def: &ProcedureDefinition) -> HashMap<(usize, usize), QuadrupelOp> {
+    fn visit_statement(statement: &Statement, found: &mut HashMap<(usize, usize), QuadrupelOp>) {
+        match statement {
+            Statement::EmptyStatement(_)
+            | Statement::BreakStatement(_)
+            | Statement::ContinueStatement(_) => {}
+            Statement::CompoundStatement(statements, _) => {
+                for s in statements {
+                    visit_statement(s, found);
+                }
+            }
+            Statement::AssignStatement(assign) => {
+                visit_variable(&assign.target, found);
+                visit_expression(&assign.value, found);
+            }
+            Statement::CallStatement(call) => {
+                for arg in &call.arguments {
+                    visit_expression(arg, found);
+                }
+            }
+            Statement::ReturnStatement(ret) => {
+                if let Some(value) = &ret.value {
+                    visit_expression(value, found);
+                }
+            }
+            Statement::IfStatement(if_statement) => {
+                visit_expression(&if_statement.condition, found);
+                visit_statement(&if_statement.then_branch, found);
+                if let Some(else_branch) = &if_statement.else_branch {
+                    visit_statement(else_branch, found);
+                }
+            }
+            Statement::WhileStatement(while_statement) => {
+                visit_expression(&while_statement.condition, found);
+                visit_statement(&while_statement.body, found);
+            }
+            Statement::ForStatement(for_statement) => {
+                visit_variable(&for_statement.variable, found);
+                visit_expression(&for_statement.start, found);
+                visit_expression(&for_statement.end, found);
+                visit_statement(&for_statement.body, found);
+            }
+            Statement::RepeatStatement(repeat_statement) => {
+                visit_statement(&repeat_statement.body, found);
+                visit_expression(&repeat_statement.condition, found);
+            }
+            Statement::CaseStatement(case_statement) => {
+                visit_expression(&case_statement.selector, found);
+                for branch in &case_statement.branches {
+                    // Each label is compared with the selector
+                    for label in &branch.labels {
+                        let span = label.span();
+                        found.insert((span.start, span.end), QuadrupelOp::Equ);
+                    }
+                    visit_statement(&branch.statement, found);
+                }
+                if let Some(default) = &case_statement.default {
+                    visit_statement(default, found);
+                }
+            }
+        }
+    }
+
+    fn visit_expression(expression: &Expression, found: &mut HashMap<(usize, usize), QuadrupelOp>) {
+        match expression {
+            Expression::BinaryExpression(binary) => {
+                if binary.operator.is_comparison() {
+                    found.insert((binary.span.start, binary.span.end), binary.operator.into());
+                }
+                visit_expression(&binary.left, found);
+                visit_expression(&binary.right, found);
+            }
+            Expression::UnaryExpression(unary) => visit_expression(&unary.operand, found),
+            Expression::IntLiteral(..)
+            | Expression::BoolLiteral(..)
+            | Expression::StringLiteral(..) => {}
+            Expression::VariableExpression(var) => visit_variable(var, found),
+            Expression::CallExpression(call) => {
+                for arg in &call.arguments {
+                    visit_expression(arg, found);
+                }
+            }
+        }
+    }
+
+    fn visit_variable(var: &Variable, found: &mut HashMap<(usize, usize), QuadrupelOp>) {
+        match var {
+            Variable::NamedVariable(..) => {}
+            Variable::ArrayAccess(access) => {
+                visit_variable(&access.array, found);
+                visit_expression(&access.index, found);
+            }
+            Variable::FieldAccess(access) => visit_variable(&access.record, found),
+        }
+    }
+
+    let mut found = HashMap::new();
+    for s in &proc_def.body {
+        visit_statement(s, &mut found);
+    }
+    found
+}
+
+/// Numbers and spans of the first statements that follow a `return`, `break`, `continue`
+/// or a call to `exit`, nested statements are counted in source order.
+fn unreachable_statements(proc_def: &ProcedureDefinition) -> Vec<(usize, Span)> {
+    let mut count = 0;
+    let mut found = Vec::new();
+    walk_statements(proc_def.body.iter(), &mut count, &mut found);
+    found
+}
+
+/// Walks a list of statements and returns whether it never completes.
+/// Only the first unreachable statement of each list is reported.
+fn walk_statements<'a>(
+    statements: impl IntoIterator<Item = &'a Statement>,
+    count: &mut usize,
+    found: &mut Vec<(usize, Span)>,
+) -> bool {
+    let mut diverges = false;
+    let mut reported = false;
+
+    for statement in statements {
+        if diverges && !reported && !is_empty(statement) {
+            found.push((*count + 1, statement.span()));
+            reported = true;
+        }
+        diverges |= walk_statement(statement, count, found);
+    }
+
+    diverges
+}
+
+/// Walks a single statement and returns whether it never completes.
+fn walk_statement(
+    statement: &Statement,
+    count: &mut usize,
+    found: &mut Vec<(usize, Span)>,
+) -> bool {
+    match statement {
+        Statement::EmptyStatement(_) => false,
+        Statement::CompoundStatement(statements, _) => {
+            walk_statements(statements.iter().map(AsRef::as_ref), count, found)
+        }
+        Statement::AssignStatement(_) => {
+            *count += 1;
+            false
+        }
+        Statement::CallStatement(call) => {
+            *count += 1;
+            call.name == "exit"
+        }
+        Statement::ReturnStatement(_)
+        | Statement::BreakStatement(_)
+        | Statement::ContinueStatement(_) => {
+            *count += 1;
+            true
+        }
+        Statement::IfStatement(if_statement) => {
+            *count += 1;
+            let then_diverges = walk_statement(&if_statement.then_branch, count, found);
+            let else_diverges = if_statement
+                .else_branch
+                .as_ref()
+                .is_some_and(|s| walk_statement(s, count, found));
+            then_diverges && else_diverges
+        }
+        Statement::WhileStatement(while_statement) => {
+            *count += 1;
+            // The body might not be executed at all
+            walk_statement(&while_statement.body, count, found);
+            false
+        }
+        Statement::ForStatement(for_statement) => {
+            *count += 1;
+            walk_statement(&for_statement.body, count, found);
+            false
+        }
+        Statement::RepeatStatement(repeat_statement) => {
+            *count += 1;
+            // The body may be left by `break`
+            walk_statement(&repeat_statement.body, count, found);
+            false
+        }
+        Statement::CaseStatement(case_statement) => {
+            *count += 1;
+            // Without `else` no branch may be taken
+            let mut diverges = case_statement.default.is_some();
+            for branch in &case_statement.branches {
+                diverges &= walk_statement(&branch.statement, count, found);
+            }
+            if let Some(default) = &case_statement.default {
+                diverges &= walk_statement(default, count, found);
+            }
+            diverges
+        }
+    }
+}
+
+/// Whether a statement does not contain any statement that is counted.
+fn is_empty(statement: &Statement) -> bool {
+    match statement {
+        Statement::EmptyStatement(_) => true,
+        Statement::CompoundStatement(statements, _) => statements.iter().all(|s| is_empty(s)),
+        _ => false,
+    }
+}
@@ -0,0 +1,329 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+
+use crate::{
+    absyn::absyn::{Definition, Program},
+    base_blocks::{BlockGraph, BlockId},
+    code_gen::Tac,
+    diagnostics::{Diagnostic, SourceMap, Span},
+    optimizations::{
+        array_bounds::BoundsCheck,
+        call_graph::CallGraph,
+        uninitialized_variables::{Uninitialized, UninitializedVariables},
+    },
+    table::{entry::Entry, symbol_table::SymbolTable},
+};
+
+mod lints;
+
+/// A check that may report warnings. Every lint has a stable code and a name
+/// which can be given to `-W` and `-A`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariable,
+    UnusedParameter,
+    DeadStore,
+    UnreachableCode,
+    ConstantCondition,
+    UnusedProcedure,
+    Uninitialized,
+    MaybeUninitialized,
+    OutOfBounds,
+}
+
+impl Lint {
+    pub const ALL: [Self; 9] = [
+        Self::UnusedVariable,
+        Self::UnusedParameter,
+        Self::DeadStore,
+        Self::UnreachableCode,
+        Self::ConstantCondition,
+        Self::UnusedProcedure,
+        Self::Uninitialized,
+        Self::MaybeUninitialized,
+        Self::OutOfBounds,
+    ];
+
+    pub fn code(self) -> String {
+        let idx = Self::ALL.iter().position(|&l| l == self).unwrap();
+        format!("W{:04}", idx + 1)
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::UnusedVariable => "unused-variable",
+            Self::UnusedParameter => "unused-parameter",
+            Self::DeadStore => "dead-store",
+            Self::UnreachableCode => "unreachable-code",
+            Self::ConstantCondition => "constant-condition",
+            Self::UnusedProcedure => "unused-procedure",
+            Self::Uninitialized => "uninitialized",
+            Self::MaybeUninitialized => "maybe-uninitialized",
+            Self::OutOfBounds => "out-of-bounds",
+        }
+    }
+
+    /// Finds a lint by its name or its code.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|l| l.name() == name || l.code().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Comma separated names of all lints, used in the help text of the CLI.
+pub fn available_lints() -> String {
+    Lint::ALL.map(Lint::name).join(", ")
+}
+
+/// What to do with the warnings of a lint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// The lint is not reported
+    Allow,
+    /// The lint is reported as warning
+    Warn,
+    /// The lint is reported as error and compilation is aborted
+    Deny,
+}
+
+/// The level of every lint, as given on the command line. All lints warn by default.
+#[derive(Debug, Clone)]
+pub struct LintLevels {
+    levels: HashMap<Lint, Level>,
+}
+
+impl Default for LintLevels {
+    fn default() -> Self {
+        Self {
+            levels: Lint::ALL.map(|l| (l, Level::Warn)).into(),
+        }
+    }
+}
+
+impl LintLevels {
+    /// Applies `-W` and `-A` in the order they were given, `all` selects every lint.
+    /// With `--deny-warnings` all lints that warn become errors.
+    pub fn from_matches(matches: &clap::ArgMatches) -> anyhow::Result<Self> {
+        let mut levels = Self::default();
+
+        let mut flags = Vec::new();
+        for (id, level) in [("warn", Level::Warn), ("allow", Level::Allow)] {
+            if let (Some(names), Some(indices)) =
+                (matches.get_many::<String>(id), matches.indices_of(id))
+            {
+                flags.extend(indices.zip(names).map(|(i, name)| (i, name, level)));
+            }
+        }
+        flags.sort_by_key(|(i, ..)| *i);
+
+        for (_, name, level) in flags {
+            if name == "all" {
+                levels.levels.values_mut().for_each(|l| *l = level);
+            } else {
+                let lint = Lint::from_name(name).ok_or_else(|| {
+                    anyhow!(
+                        "unknown lint `{name}`, available lints: [{}]",
+                        available_lints()
+                    )
+                })?;
+                levels.levels.insert(lint, level);
+            }
+        }
+
+        if matches.get_flag("deny_warnings") {
+            for level in levels.levels.values_mut() {
+                if *level == Level::Warn {
+                    *level = Level::Deny;
+                }
+            }
+        }
+
+        Ok(levels)
+    }
+
+    pub fn level(&self, lint: Lint) -> Level {
+        self.levels[&lint]
+    }
+}
+
+/// Where in a procedure a warning was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// The procedure as a whole, e.g. its declarations
+    Procedure,
+    /// A statement of the procedure body, counted in source order starting at 1
+    Statement(usize),
+    /// A quadruple in the block graph of the procedure
+    Quad { block_id: BlockId, quad_idx: usize },
+}
+
+/// A single finding of a lint.
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub lint: Lint,
+    pub proc_name: String,
+    pub location: Location,
+    pub message: String,
+    /// The code the warning refers to, if it is known
+    pub span: Option<Span>,
+}
+
+impl Warning {
+    pub fn new(lint: Lint, proc_name: &str, location: Location, message: String) -> Self {
+        Self {
+            lint,
+            proc_name: proc_name.to_string(),
+            location,
+            message,
+            span: None,
+        }
+    }
+
+    pub fn with_span(self, span: Option<Span>) -> Self {
+        Self { span, ..self }
+    }
+
+    /// The warning as diagnostic, falls back to the location in the procedure if there is no span.
+    fn to_diagnostic(&self, level: Level) -> Diagnostic {
+        let diagnostic = if level == Level::Deny {
+            Diagnostic::error(self.message.clone())
+        } else {
+            Diagnostic::warning(self.message.clone())
+        };
+        let location = match self.location {
+            Location::Procedure => format!("procedure `{}`", self.proc_name),
+            Location::Statement(n) => format!("procedure `{}`, statement {n}", self.proc_name),
+            Location::Quad { block_id, quad_idx } => format!(
+                "procedure `{}`, block {block_id}, quad {quad_idx}",
+                self.proc_name
+            ),
+        };
+        diagnostic
+            .with_code(self.lint.code())
+            .with_span(self.span)
+            .with_location(location)
+    }
+}
+
+/// Runs all lints on the program, the warnings are sorted by procedure and position.
+/// Expects the side-effect summaries of the call graph in the symbol table.
+pub fn check_program(
+    absyn: &Program,
+    tac: &Tac,
+    symbol_table: &Rc<RefCell<SymbolTable>>,
+    call_graph: &CallGraph,
+) -> Vec<Warning> {
+    let unused_procs = call_graph.unreachable_from("main");
+    let mut warnings = Vec::new();
+
+    for def in &absyn.definitions {
+        let Definition::ProcedureDefinition(proc_def) = def.as_ref() else {
+            continue;
+        };
+        let name = &proc_def.name;
+
+        if unused_procs.contains(&name.as_str()) {
+            warnings.push(
+                Warning::new(
+                    Lint::UnusedProcedure,
+                    name,
+                    Location::Procedure,
+                    format!("procedure `{name}` is never called"),
+                )
+                .with_span(Some(proc_def.span)),
+            );
+        }
+
+        let Some(Entry::ProcedureEntry(proc_entry)) = symbol_table.borrow().lookup(name) else {
+            unreachable!()
+        };
+        let graph = BlockGraph::from_tac(&tac.proc_table[name]);
+        warnings.extend(lints::check_procedure(
+            proc_def,
+            &graph,
+            &proc_entry,
+            &symbol_table.borrow(),
+        ));
+    }
+
+    for (proc_name, uninit) in UninitializedVariables::check_program(tac, symbol_table) {
+        warnings.extend(uninit.uses.into_iter().map(|u| {
+            let (lint, message) = match u.kind {
+                Uninitialized::Always => (Lint::Uninitialized, "is used before it is assigned"),
+                Uninitialized::Maybe => (
+                    Lint::MaybeUninitialized,
+                    "may be used before it is assigned",
+                ),
+            };
+            Warning::new(
+                lint,
+                &proc_name,
+                Location::Quad {
+                    block_id: u.block_id,
+                    quad_idx: u.quad_idx,
+                },
+                format!("variable `{}` {message}", u.var),
+            )
+            .with_span(u.span)
+        }));
+    }
+
+    for (proc_name, check) in BoundsCheck::check_program(tac, symbol_table) {
+        warnings.extend(check.unsafe_accesses().map(|access| {
+            Warning::new(
+                Lint::OutOfBounds,
+                &proc_name,
+                Location::Quad {
+                    block_id: access.block_id,
+                    quad_idx: access.quad_idx,
+                },
+                format!(
+                    "index out of bounds: the length of `{}` is {} but the index is {}",
+                    access.array,
+                    access.len,
+                    access.fmt_index()
+                ),
+            )
+            .with_span(access.span)
+        }));
+    }
+
+    warnings.sort_by(|a, b| {
+        let start = |w: &Warning| w.span.map_or(usize::MAX, |span| span.start);
+        (&a.proc_name, start(a)).cmp(&(&b.proc_name, start(b)))
+    });
+    warnings
+}
+
+/// Prints all warnings that are not allowed with the code they refer to, followed by a summary.
+/// Fails if any of them is denied.
+pub fn emit(warnings: &[Warning], levels: &LintLevels, sources: &SourceMap) -> anyhow::Result<()> {
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    let (mut warned, mut denied) = (0, 0);
+
+    for warning in warnings {
+        let level = levels.level(warning.lint);
+        match level {
+            Level::Allow => continue,
+            Level::Warn => warned += 1,
+            Level::Deny => denied += 1,
+        }
+        eprintln!("{}\n", sources.render(&warning.to_diagnostic(level)));
+    }
+
+    if warned > 0 {
+        eprintln!(
+            "{}: {warned} warning{} emitted",
+            "warning".yellow().bold(),
+            plural(warned)
+        );
+    }
+    if denied > 0 {
+        bail!("aborting due to {denied} denied warning{}", plural(denied));
+    }
+
+    Ok(())
+}